@@ -0,0 +1,122 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use qrustcode::{
+    bench,
+    gf256::{Polynomial, ReedSolomon, QR_CODE},
+    renderer::debug,
+    Encoder,
+    Mode,
+    Workspace,
+};
+
+fn segment(c: &mut Criterion) {
+    let mut group = c.benchmark_group("segment");
+    let mut codewords = Vec::with_capacity(3706);
+
+    for &(mode, message) in [
+        (Mode::Numeric, &"0123456789".repeat(50)[..]),
+        (Mode::Alphanumeric, &"HELLO WORLD ".repeat(50)[..]),
+        (Mode::Byte, &"hello, world ".repeat(50)[..]),
+        (Mode::Kanji, &"ハロー・ワールド".repeat(50)[..]),
+    ].iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", mode)), message, |b, message| {
+            b.iter(|| bench::segment(mode, 27, black_box(message), &mut codewords))
+        });
+    }
+
+    group.finish();
+}
+
+fn reed_solomon(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reed_solomon");
+
+    // version 40-L block
+    let data = (0..118).collect::<Vec<u8>>();
+    let mut ec = [0; 30];
+    let reed_solomon = ReedSolomon::new(&QR_CODE, 30, 0);
+    group.bench_function("lfsr", |b| b.iter(|| reed_solomon.encode(black_box(&data), &mut ec)));
+
+    let dividend = Polynomial::new(&QR_CODE, &data).mul(&Polynomial::monomial(&QR_CODE, 30, 1));
+    let generator = Polynomial::generator(&QR_CODE, 30, 0);
+    group.bench_function("polynomial_division", |b| b.iter(|| black_box(&dividend).div_rem(&generator)));
+
+    // every block of version 40-L
+    let mut codewords = Vec::with_capacity(3706);
+    let mut interleaved = Vec::with_capacity(3706);
+    group.bench_function("interleave_40_L", |b| b.iter(|| {
+        codewords.clear();
+        codewords.extend((0..2956).map(|i| i as u8));
+        bench::error_correction(&mut codewords, 40, "L", &mut interleaved);
+    }));
+
+    group.finish();
+}
+
+fn matrix(c: &mut Criterion) {
+    let mut group = c.benchmark_group("matrix");
+
+    for &version in [1, 10, 40].iter() {
+        let codewords = (0..3706).map(|i| i as u8).collect::<Vec<u8>>();
+
+        group.bench_with_input(BenchmarkId::new("function_patterns", version), &version, |b, &version| {
+            b.iter(|| bench::function_patterns(black_box(version)))
+        });
+
+        let blank = bench::function_patterns(version);
+        group.bench_with_input(BenchmarkId::new("placement", version), &version, |b, _| {
+            b.iter_batched_ref(
+                || blank.clone(),
+                |matrix| bench::placement(matrix, &codewords),
+                criterion::BatchSize::SmallInput,
+            )
+        });
+
+        let mut placed = blank.clone();
+        bench::placement(&mut placed, &codewords);
+        group.bench_with_input(BenchmarkId::new("masking", version), &version, |b, _| {
+            b.iter_batched_ref(|| placed.clone(), bench::masking, criterion::BatchSize::SmallInput)
+        });
+    }
+
+    group.finish();
+}
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    let mut encoder = Encoder::new().version(10).ec_level("M");
+    let matrix = encoder.encode("https://github.com/AurevoirXavier/qrustcode").unwrap().as_matrix();
+    let region_map = encoder.region_map().unwrap();
+
+    group.bench_function("debug_svg_10", |b| b.iter(|| debug::svg(black_box(&matrix), &region_map)));
+
+    group.finish();
+}
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode");
+    let message = "https://github.com/AurevoirXavier/qrustcode";
+
+    let mut encoder = Encoder::new().ec_level("M");
+    group.bench_function("as_matrix", |b| b.iter(|| encoder.encode(black_box(message)).unwrap().as_matrix()));
+
+    let mut workspace = Workspace::new();
+    group.bench_function("workspace", |b| b.iter(|| {
+        encoder.encode_into(black_box(message), &mut workspace).unwrap();
+    }));
+
+    let messages = (0..1000).map(|i| format!("https://example.com/shipment/{:08}", i)).collect::<Vec<String>>();
+    for &workers in [1, 0].iter() {
+        let encoder = Encoder::new().ec_level("M").workers(workers);
+
+        group.bench_with_input(BenchmarkId::new("batch_1000", workers), &workers, |b, _| {
+            b.iter(|| encoder.encode_batch(black_box(&messages)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, segment, reed_solomon, matrix, render, encode);
+criterion_main!(benches);
@@ -0,0 +1,125 @@
+/*
+ * C interface of qrustcode, a QR code encoder.
+ *
+ * Link with libqrustcode_ffi.a (plus -lpthread -ldl -lm) or libqrustcode_ffi.so.
+ *
+ * Every function which returns `int` returns a `qrustcode_status`. Handles are owned by the caller until they are
+ * passed to their `_free` function, and a NULL handle is reported as QRUSTCODE_NULL_POINTER.
+ *
+ * This header is written by hand, tests/c.rs checks that it declares every exported function and status code.
+ */
+
+#ifndef QRUSTCODE_H
+#define QRUSTCODE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum qrustcode_status {
+    QRUSTCODE_OK = 0,
+    QRUSTCODE_NULL_POINTER = 1,
+    /* mode or ec level is out of range, or an image is larger than 16384 pixels per side */
+    QRUSTCODE_INVALID_ARGUMENT = 2,
+    /* version is not in 1 ~ 40 */
+    QRUSTCODE_INVALID_VERSION = 3,
+    QRUSTCODE_INVALID_VERSION_RANGE = 4,
+    QRUSTCODE_INVALID_SIZE = 5,
+    /* mask is not in 0 ~ 7 */
+    QRUSTCODE_INVALID_MASK = 6,
+    /* the data doesn't fit in the version and ec level */
+    QRUSTCODE_DATA_TOO_LONG = 7,
+    /* the mode can't encode a character of the data */
+    QRUSTCODE_UNSUPPORTED_CHARACTER = 8,
+    /* modes other than auto and byte need UTF-8 data */
+    QRUSTCODE_INVALID_UTF8 = 9,
+    /* the buffer is NULL or smaller than the size written to `written` */
+    QRUSTCODE_BUFFER_TOO_SMALL = 10,
+    QRUSTCODE_INTERNAL_ERROR = 11
+} qrustcode_status;
+
+typedef enum qrustcode_mode {
+    /* the most compact mode for the data */
+    QRUSTCODE_MODE_AUTO = 0,
+    QRUSTCODE_MODE_NUMERIC = 1,
+    QRUSTCODE_MODE_ALPHANUMERIC = 2,
+    QRUSTCODE_MODE_BYTE = 3,
+    QRUSTCODE_MODE_KANJI = 4
+} qrustcode_mode;
+
+typedef enum qrustcode_ec_level {
+    QRUSTCODE_EC_L = 0,
+    QRUSTCODE_EC_M = 1,
+    QRUSTCODE_EC_Q = 2,
+    QRUSTCODE_EC_H = 3
+} qrustcode_ec_level;
+
+/* settings of an encode: mode, version, ec level and mask */
+typedef struct qrustcode_encoder qrustcode_encoder;
+
+/* an encoded symbol */
+typedef struct qrustcode_symbol qrustcode_symbol;
+
+/* auto mode, smallest version, ec level L, best mask */
+qrustcode_encoder *qrustcode_encoder_new(void);
+void qrustcode_encoder_free(qrustcode_encoder *encoder);
+
+/* a `qrustcode_mode` */
+int qrustcode_encoder_set_mode(qrustcode_encoder *encoder, int mode);
+/* 1 ~ 40, or 0 for the smallest version which fits */
+int qrustcode_encoder_set_version(qrustcode_encoder *encoder, size_t version);
+/* a `qrustcode_ec_level` */
+int qrustcode_encoder_set_ec_level(qrustcode_encoder *encoder, int ec_level);
+/* 0 ~ 7, or -1 for the mask with the lowest penalty */
+int qrustcode_encoder_set_mask(qrustcode_encoder *encoder, int mask);
+
+/* encode `len` bytes, `*symbol` is a new symbol on success and NULL otherwise */
+int qrustcode_encode(const qrustcode_encoder *encoder, const uint8_t *data, size_t len, qrustcode_symbol **symbol);
+
+void qrustcode_symbol_free(qrustcode_symbol *symbol);
+/* modules per side, 0 for NULL */
+size_t qrustcode_symbol_width(const qrustcode_symbol *symbol);
+/* 1 ~ 40, 0 for NULL */
+size_t qrustcode_symbol_version(const qrustcode_symbol *symbol);
+/* 0 ~ 7, -1 for NULL */
+int qrustcode_symbol_mask(const qrustcode_symbol *symbol);
+/* 1 for a dark module, 0 for a light one, -1 if (x, y) is outside of the symbol */
+int qrustcode_symbol_module(const qrustcode_symbol *symbol, size_t x, size_t y);
+
+/*
+ * The output functions write into a caller buffer of `capacity` bytes. `*written` receives the size of the output
+ * even if the buffer is too small, so passing a NULL buffer asks for the size.
+ */
+
+/* width * width bytes row by row, 1 for dark modules */
+int qrustcode_symbol_modules(const qrustcode_symbol *symbol, uint8_t *buffer, size_t capacity, size_t *written);
+/* NUL terminated SVG, `*written` counts the NUL */
+int qrustcode_symbol_svg(
+    const qrustcode_symbol *symbol,
+    size_t scale,
+    size_t quiet_zone,
+    char *buffer,
+    size_t capacity,
+    size_t *written
+);
+/* 1 bit grayscale PNG */
+int qrustcode_symbol_png(
+    const qrustcode_symbol *symbol,
+    size_t scale,
+    size_t quiet_zone,
+    uint8_t *buffer,
+    size_t capacity,
+    size_t *written
+);
+
+/* static description of a `qrustcode_status` */
+const char *qrustcode_status_message(int status);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,397 @@
+// C ABI of the encoder, see `include/qrustcode.h` for the documentation of every function
+//
+// handles are boxed and owned by the caller until they are passed to their `_free` function,
+// a null handle is an error, never undefined behavior
+#![allow(clippy::missing_safety_doc)]
+
+extern crate qrustcode;
+
+use std::{
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    slice,
+};
+
+use qrustcode::{Encoder, Error, Matrix, Renderer};
+
+// status codes of `qrustcode_status`
+pub const QRUSTCODE_OK: c_int = 0;
+pub const QRUSTCODE_NULL_POINTER: c_int = 1;
+pub const QRUSTCODE_INVALID_ARGUMENT: c_int = 2;
+pub const QRUSTCODE_INVALID_VERSION: c_int = 3;
+pub const QRUSTCODE_INVALID_VERSION_RANGE: c_int = 4;
+pub const QRUSTCODE_INVALID_SIZE: c_int = 5;
+pub const QRUSTCODE_INVALID_MASK: c_int = 6;
+pub const QRUSTCODE_DATA_TOO_LONG: c_int = 7;
+pub const QRUSTCODE_UNSUPPORTED_CHARACTER: c_int = 8;
+pub const QRUSTCODE_INVALID_UTF8: c_int = 9;
+pub const QRUSTCODE_BUFFER_TOO_SMALL: c_int = 10;
+pub const QRUSTCODE_INTERNAL_ERROR: c_int = 11;
+
+// `qrustcode_mode`, 0 detects the mode
+const MODES: [Option<&str>; 5] = [None, Some("Numeric"), Some("Alphanumeric"), Some("Byte"), Some("Kanji")];
+
+// `qrustcode_ec_level`
+const EC_LEVELS: [&str; 4] = ["L", "M", "Q", "H"];
+
+// side of the largest svg or png, a 1 bit png of this size is 32 MiB before compression
+const MAX_PIXELS: usize = 1 << 14;
+
+// settings of `qrustcode_encoder`, an `Encoder` is built from them on every encode
+pub struct QrustcodeEncoder {
+    mode: Option<&'static str>,
+    version: Option<usize>,
+    ec_level: &'static str,
+    mask: Option<usize>,
+}
+
+pub struct QrustcodeSymbol {
+    matrix: Matrix,
+}
+
+fn status(e: &Error) -> c_int {
+    match e {
+        Error::InvalidVersion(_) => QRUSTCODE_INVALID_VERSION,
+        Error::InvalidVersionRange(_, _) => QRUSTCODE_INVALID_VERSION_RANGE,
+        Error::InvalidSize(_) => QRUSTCODE_INVALID_SIZE,
+        Error::InvalidMask(_) => QRUSTCODE_INVALID_MASK,
+        Error::InvalidEcLevel(_)
+        | Error::InvalidMode(_)
+        | Error::LogoTooLarge { .. }
+        | Error::EmptyImage
+        | Error::LayersMismatch(_, _) => QRUSTCODE_INVALID_ARGUMENT,
+        Error::DataTooLong { .. } => QRUSTCODE_DATA_TOO_LONG,
+        Error::UnsupportedCharacter { .. } => QRUSTCODE_UNSUPPORTED_CHARACTER,
+    }
+}
+
+// a panic must not unwind into C, every exported function returns `fallback` instead
+fn guard<T, F: FnOnce() -> T>(fallback: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+// pixels per side of an image of `symbol`, `None` if it overflows or is larger than `MAX_PIXELS`
+fn pixels(symbol: &QrustcodeSymbol, scale: usize, quiet_zone: usize) -> Option<usize> {
+    quiet_zone
+        .checked_mul(2)
+        .and_then(|quiet_zone| quiet_zone.checked_add(symbol.matrix.width()))
+        // `Renderer::scale` raises 0 to 1
+        .and_then(|modules| modules.checked_mul(scale.max(1)))
+        .filter(|&pixels| pixels <= MAX_PIXELS)
+}
+
+// copy `bytes` into the caller's buffer, `written` always receives the required capacity
+unsafe fn write_buffer(bytes: &[u8], buffer: *mut u8, capacity: usize, written: *mut usize) -> c_int {
+    if !written.is_null() { *written = bytes.len(); }
+    if buffer.is_null() || capacity < bytes.len() { return QRUSTCODE_BUFFER_TOO_SMALL; }
+
+    ptr::copy_nonoverlapping(bytes.as_ptr(), buffer, bytes.len());
+
+    QRUSTCODE_OK
+}
+
+#[no_mangle]
+pub extern "C" fn qrustcode_encoder_new() -> *mut QrustcodeEncoder {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(QrustcodeEncoder { mode: None, version: None, ec_level: "L", mask: None }))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_encoder_free(encoder: *mut QrustcodeEncoder) {
+    guard((), || if !encoder.is_null() { drop(Box::from_raw(encoder)); })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_encoder_set_mode(encoder: *mut QrustcodeEncoder, mode: c_int) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let encoder = match encoder.as_mut() {
+            Some(encoder) => encoder,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+
+        // negative values wrap to huge indexes
+        match MODES.get(mode as usize) {
+            Some(&mode) => {
+                encoder.mode = mode;
+
+                QRUSTCODE_OK
+            }
+            _ => QRUSTCODE_INVALID_ARGUMENT,
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_encoder_set_version(encoder: *mut QrustcodeEncoder, version: usize) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let encoder = match encoder.as_mut() {
+            Some(encoder) => encoder,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+
+        match version {
+            0 => encoder.version = None,
+            1..=40 => encoder.version = Some(version),
+            _ => return QRUSTCODE_INVALID_VERSION,
+        }
+
+        QRUSTCODE_OK
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_encoder_set_ec_level(encoder: *mut QrustcodeEncoder, ec_level: c_int) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let encoder = match encoder.as_mut() {
+            Some(encoder) => encoder,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+
+        match EC_LEVELS.get(ec_level as usize) {
+            Some(&ec_level) => {
+                encoder.ec_level = ec_level;
+
+                QRUSTCODE_OK
+            }
+            None => QRUSTCODE_INVALID_ARGUMENT,
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_encoder_set_mask(encoder: *mut QrustcodeEncoder, mask: c_int) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let encoder = match encoder.as_mut() {
+            Some(encoder) => encoder,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+
+        match mask {
+            -1 => encoder.mask = None,
+            0..=7 => encoder.mask = Some(mask as usize),
+            _ => return QRUSTCODE_INVALID_MASK,
+        }
+
+        QRUSTCODE_OK
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_encode(
+    encoder: *const QrustcodeEncoder,
+    data: *const u8,
+    len: usize,
+    symbol: *mut *mut QrustcodeSymbol,
+) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let settings = match encoder.as_ref() {
+            Some(settings) => settings,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+        if (data.is_null() && len != 0) || symbol.is_null() { return QRUSTCODE_NULL_POINTER; }
+        *symbol = ptr::null_mut();
+
+        let data = if len == 0 { &[][..] } else { slice::from_raw_parts(data, len) };
+
+        let mut encoder = Encoder::new().ec_level(settings.ec_level);
+        if let Some(mode) = settings.mode { encoder = encoder.mode(mode); }
+        if let Some(version) = settings.version { encoder = encoder.version(version); }
+        if let Some(mask) = settings.mask { encoder = encoder.mask(mask); }
+
+        let encoded = match (std::str::from_utf8(data), settings.mode) {
+            (_, Some("Byte")) => encoder.encode_bytes(data),
+            (Ok(message), _) => encoder.encode(message),
+            // only Byte mode takes bytes which aren't UTF-8
+            (Err(_), Some(_)) => return QRUSTCODE_INVALID_UTF8,
+            (Err(_), None) => encoder.encode_bytes(data),
+        };
+
+        match encoded {
+            Ok(encoder) => {
+                *symbol = Box::into_raw(Box::new(QrustcodeSymbol { matrix: encoder.as_matrix() }));
+
+                QRUSTCODE_OK
+            }
+            Err(e) => status(&e),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_free(symbol: *mut QrustcodeSymbol) {
+    guard((), || if !symbol.is_null() { drop(Box::from_raw(symbol)); })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_width(symbol: *const QrustcodeSymbol) -> usize {
+    guard(0, || symbol.as_ref().map_or(0, |symbol| symbol.matrix.width()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_version(symbol: *const QrustcodeSymbol) -> usize {
+    guard(0, || symbol.as_ref().map_or(0, |symbol| (symbol.matrix.width() - 17) / 4))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_mask(symbol: *const QrustcodeSymbol) -> c_int {
+    guard(-1, || symbol.as_ref().and_then(|symbol| symbol.matrix.mask()).map_or(-1, |mask| mask as c_int))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_module(symbol: *const QrustcodeSymbol, x: usize, y: usize) -> c_int {
+    guard(-1, || match symbol.as_ref() {
+        Some(symbol) if x < symbol.matrix.width() && y < symbol.matrix.width() => symbol.matrix.is_dark(x, y) as c_int,
+        _ => -1,
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_modules(
+    symbol: *const QrustcodeSymbol,
+    buffer: *mut u8,
+    capacity: usize,
+    written: *mut usize,
+) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let symbol = match symbol.as_ref() {
+            Some(symbol) => symbol,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+        let width = symbol.matrix.width();
+        let modules = (0..width)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| symbol.matrix.is_dark(x, y) as u8)
+            .collect::<Vec<u8>>();
+
+        write_buffer(&modules, buffer, capacity, written)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_svg(
+    symbol: *const QrustcodeSymbol,
+    scale: usize,
+    quiet_zone: usize,
+    buffer: *mut c_char,
+    capacity: usize,
+    written: *mut usize,
+) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let symbol = match symbol.as_ref() {
+            Some(symbol) => symbol,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+        if pixels(symbol, scale, quiet_zone).is_none() { return QRUSTCODE_INVALID_ARGUMENT; }
+
+        // NUL terminated
+        let mut svg = Renderer::new(&symbol.matrix).scale(scale).quiet_zone(quiet_zone).svg().into_bytes();
+        svg.push(0);
+
+        write_buffer(&svg, buffer as *mut u8, capacity, written)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn qrustcode_symbol_png(
+    symbol: *const QrustcodeSymbol,
+    scale: usize,
+    quiet_zone: usize,
+    buffer: *mut u8,
+    capacity: usize,
+    written: *mut usize,
+) -> c_int {
+    guard(QRUSTCODE_INTERNAL_ERROR, || {
+        let symbol = match symbol.as_ref() {
+            Some(symbol) => symbol,
+            None => return QRUSTCODE_NULL_POINTER,
+        };
+        if pixels(symbol, scale, quiet_zone).is_none() { return QRUSTCODE_INVALID_ARGUMENT; }
+
+        let png = Renderer::new(&symbol.matrix).scale(scale).quiet_zone(quiet_zone).png();
+
+        write_buffer(&png, buffer, capacity, written)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn qrustcode_status_message(status: c_int) -> *const c_char {
+    let message: &'static [u8] = guard(b"internal error\0", || match status {
+        QRUSTCODE_OK => b"ok\0",
+        QRUSTCODE_NULL_POINTER => b"null pointer\0",
+        QRUSTCODE_INVALID_ARGUMENT => b"invalid argument\0",
+        QRUSTCODE_INVALID_VERSION => b"version is not in 1 ~ 40\0",
+        QRUSTCODE_INVALID_VERSION_RANGE => b"min version is greater than max version\0",
+        QRUSTCODE_INVALID_SIZE => b"size can't hold any version\0",
+        QRUSTCODE_INVALID_MASK => b"mask is not in 0 ~ 7\0",
+        QRUSTCODE_DATA_TOO_LONG => b"data doesn't fit in the version and ec level\0",
+        QRUSTCODE_UNSUPPORTED_CHARACTER => b"the mode can't encode a character of the data\0",
+        QRUSTCODE_INVALID_UTF8 => b"the mode needs UTF-8 data\0",
+        QRUSTCODE_BUFFER_TOO_SMALL => b"buffer is too small\0",
+        QRUSTCODE_INTERNAL_ERROR => b"internal error\0",
+        _ => b"unknown status\0",
+    });
+
+    message.as_ptr() as *const c_char
+}
+
+#[test]
+fn test_encode() {
+    unsafe {
+        let encoder = qrustcode_encoder_new();
+        assert_eq!(qrustcode_encoder_set_ec_level(encoder, 1), QRUSTCODE_OK);
+        assert_eq!(qrustcode_encoder_set_mask(encoder, 3), QRUSTCODE_OK);
+
+        let mut symbol = ptr::null_mut();
+        assert_eq!(qrustcode_encode(encoder, b"01234567".as_ptr(), 8, &mut symbol), QRUSTCODE_OK);
+        assert_eq!((qrustcode_symbol_width(symbol), qrustcode_symbol_version(symbol), qrustcode_symbol_mask(symbol)), (21, 1, 3));
+        assert_eq!(qrustcode_symbol_module(symbol, 0, 0), 1);
+        assert_eq!(qrustcode_symbol_module(symbol, 21, 0), -1);
+
+        // ask for the size first
+        let mut len = 0;
+        assert_eq!(qrustcode_symbol_png(symbol, 2, 4, ptr::null_mut(), 0, &mut len), QRUSTCODE_BUFFER_TOO_SMALL);
+        let mut png = vec![0; len];
+        assert_eq!(qrustcode_symbol_png(symbol, 2, 4, png.as_mut_ptr(), len, &mut len), QRUSTCODE_OK);
+        assert_eq!(qrustcode::decode_image(&png).unwrap().data, b"01234567");
+
+        qrustcode_symbol_free(symbol);
+        qrustcode_encoder_free(encoder);
+    }
+}
+
+#[test]
+fn test_errors() {
+    unsafe {
+        let encoder = qrustcode_encoder_new();
+        let mut symbol = ptr::null_mut();
+
+        assert_eq!(qrustcode_encoder_set_mode(encoder, 5), QRUSTCODE_INVALID_ARGUMENT);
+        assert_eq!(qrustcode_encoder_set_mode(encoder, -1), QRUSTCODE_INVALID_ARGUMENT);
+        assert_eq!(qrustcode_encoder_set_version(encoder, 41), QRUSTCODE_INVALID_VERSION);
+        assert_eq!(qrustcode_encoder_set_mask(encoder, 8), QRUSTCODE_INVALID_MASK);
+        assert_eq!(qrustcode_encoder_set_ec_level(ptr::null_mut(), 0), QRUSTCODE_NULL_POINTER);
+
+        assert_eq!(qrustcode_encoder_set_mode(encoder, 1), QRUSTCODE_OK);
+        assert_eq!(qrustcode_encode(encoder, b"12a".as_ptr(), 3, &mut symbol), QRUSTCODE_UNSUPPORTED_CHARACTER);
+        assert_eq!(qrustcode_encode(encoder, b"\xff".as_ptr(), 1, &mut symbol), QRUSTCODE_INVALID_UTF8);
+        assert!(symbol.is_null());
+
+        assert_eq!(qrustcode_encoder_set_mode(encoder, 0), QRUSTCODE_OK);
+        assert_eq!(qrustcode_encoder_set_version(encoder, 1), QRUSTCODE_OK);
+        assert_eq!(qrustcode_encode(encoder, [b'a'; 18].as_ptr(), 18, &mut symbol), QRUSTCODE_DATA_TOO_LONG);
+        assert_eq!(qrustcode_encode(encoder, ptr::null(), 1, &mut symbol), QRUSTCODE_NULL_POINTER);
+
+        // images which overflow or are too large to allocate
+        assert_eq!(qrustcode_encode(encoder, b"1".as_ptr(), 1, &mut symbol), QRUSTCODE_OK);
+        let mut len = 0;
+        assert_eq!(qrustcode_symbol_svg(symbol, usize::MAX, 4, ptr::null_mut(), 0, &mut len), QRUSTCODE_INVALID_ARGUMENT);
+        assert_eq!(qrustcode_symbol_png(symbol, 2, usize::MAX / 2, ptr::null_mut(), 0, &mut len), QRUSTCODE_INVALID_ARGUMENT);
+        assert_eq!(qrustcode_symbol_png(symbol, 1, 8182, ptr::null_mut(), 0, &mut len), QRUSTCODE_INVALID_ARGUMENT);
+        assert_eq!(len, 0);
+
+        qrustcode_symbol_free(symbol);
+        qrustcode_encoder_free(encoder);
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    env,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// the staticlib is built next to the test binaries in `target/<profile>/deps`
+fn staticlib() -> PathBuf {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+
+    [deps.join("libqrustcode_ffi.a"), deps.parent().unwrap().join("libqrustcode_ffi.a")]
+        .iter()
+        .find(|path| path.exists())
+        .expect("libqrustcode_ffi.a is not built")
+        .clone()
+}
+
+#[test]
+fn test_c() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let exe = env::temp_dir().join(format!("qrustcode-ffi-test-{}", std::process::id()));
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .args(["-std=c99", "-Wall", "-Werror", "-I"])
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/test.c"))
+        .arg(staticlib())
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&exe)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(&exe).output().unwrap();
+    fs::remove_file(&exe).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+// every exported function is declared in the header
+#[test]
+fn test_header() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source = fs::read_to_string(manifest_dir.join("src/lib.rs")).unwrap();
+    let header = fs::read_to_string(manifest_dir.join("include/qrustcode.h")).unwrap();
+
+    let functions = source.split("extern \"C\" fn ")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('(').unwrap()])
+        .collect::<Vec<&str>>();
+    assert_eq!(functions.len(), 16);
+
+    for function in functions {
+        assert!(header.contains(&format!(" *{}(", function)) || header.contains(&format!(" {}(", function)), "{}", function);
+    }
+    // status codes have the same values
+    for line in source.lines().filter(|line| line.starts_with("pub const QRUSTCODE_")) {
+        let (name, value) = line["pub const ".len()..line.len() - 1].split_once(": c_int = ").unwrap();
+
+        assert!(header.contains(&format!("{} = {}", name, value)), "{}", name);
+    }
+}
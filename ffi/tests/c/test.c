@@ -0,0 +1,80 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "qrustcode.h"
+
+#define CHECK(call, expect) do { \
+    int status = (call); \
+    if (status != (expect)) { \
+        fprintf(stderr, "%s:%d: %s returned %d: %s\n", __FILE__, __LINE__, #call, status, qrustcode_status_message(status)); \
+        return 1; \
+    } \
+} while (0)
+
+int main(void) {
+    qrustcode_encoder *encoder = qrustcode_encoder_new();
+    qrustcode_symbol *symbol = NULL;
+    size_t written = 0;
+
+    CHECK(qrustcode_encoder_set_ec_level(encoder, QRUSTCODE_EC_M), QRUSTCODE_OK);
+    CHECK(qrustcode_encoder_set_version(encoder, 2), QRUSTCODE_OK);
+    CHECK(qrustcode_encoder_set_mask(encoder, 4), QRUSTCODE_OK);
+    CHECK(qrustcode_encode(encoder, (const uint8_t *) "HELLO WORLD", 11, &symbol), QRUSTCODE_OK);
+
+    assert(qrustcode_symbol_width(symbol) == 25);
+    assert(qrustcode_symbol_version(symbol) == 2);
+    assert(qrustcode_symbol_mask(symbol) == 4);
+    /* finder pattern corners and the separator */
+    assert(qrustcode_symbol_module(symbol, 0, 0) == 1);
+    assert(qrustcode_symbol_module(symbol, 24, 0) == 1);
+    assert(qrustcode_symbol_module(symbol, 7, 0) == 0);
+    assert(qrustcode_symbol_module(symbol, 25, 0) == -1);
+
+    /* modules agree with the single module query */
+    uint8_t modules[25 * 25];
+    CHECK(qrustcode_symbol_modules(symbol, modules, sizeof modules, &written), QRUSTCODE_OK);
+    assert(written == 25 * 25);
+    for (size_t y = 0; y < 25; y++) {
+        for (size_t x = 0; x < 25; x++) { assert(modules[y * 25 + x] == qrustcode_symbol_module(symbol, x, y)); }
+    }
+
+    /* ask for the size, then render */
+    CHECK(qrustcode_symbol_svg(symbol, 4, 4, NULL, 0, &written), QRUSTCODE_BUFFER_TOO_SMALL);
+    char *svg = malloc(written);
+    CHECK(qrustcode_symbol_svg(symbol, 4, 4, svg, written, &written), QRUSTCODE_OK);
+    assert(strlen(svg) + 1 == written);
+    assert(strncmp(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"132\"", 51) == 0);
+    free(svg);
+
+    uint8_t small[8];
+    CHECK(qrustcode_symbol_png(symbol, 1, 0, small, sizeof small, &written), QRUSTCODE_BUFFER_TOO_SMALL);
+    uint8_t *png = malloc(written);
+    CHECK(qrustcode_symbol_png(symbol, 1, 0, png, written, &written), QRUSTCODE_OK);
+    assert(memcmp(png, "\x89PNG\r\n\x1a\n", 8) == 0);
+    free(png);
+    /* the image size is checked before anything is rendered */
+    CHECK(qrustcode_symbol_png(symbol, SIZE_MAX, 4, NULL, 0, &written), QRUSTCODE_INVALID_ARGUMENT);
+
+    qrustcode_symbol_free(symbol);
+
+    /* failures of `Encoder` map to status codes, no symbol is returned */
+    CHECK(qrustcode_encoder_set_mode(encoder, QRUSTCODE_MODE_NUMERIC), QRUSTCODE_OK);
+    CHECK(qrustcode_encode(encoder, (const uint8_t *) "12a", 3, &symbol), QRUSTCODE_UNSUPPORTED_CHARACTER);
+    assert(symbol == NULL);
+    CHECK(qrustcode_encoder_set_mode(encoder, QRUSTCODE_MODE_BYTE), QRUSTCODE_OK);
+    CHECK(qrustcode_encode(encoder, (const uint8_t *) "\xff\xfe", 2, &symbol), QRUSTCODE_OK);
+    qrustcode_symbol_free(symbol);
+    CHECK(qrustcode_encode(encoder, (const uint8_t *) "0123456789012345678901234567890", 31, &symbol), QRUSTCODE_DATA_TOO_LONG);
+    CHECK(qrustcode_encoder_set_version(encoder, 41), QRUSTCODE_INVALID_VERSION);
+    CHECK(qrustcode_encoder_set_mask(encoder, 8), QRUSTCODE_INVALID_MASK);
+    CHECK(qrustcode_encoder_set_ec_level(encoder, 4), QRUSTCODE_INVALID_ARGUMENT);
+    CHECK(qrustcode_encoder_set_mode(NULL, QRUSTCODE_MODE_AUTO), QRUSTCODE_NULL_POINTER);
+    assert(strcmp(qrustcode_status_message(QRUSTCODE_INVALID_MASK), "mask is not in 0 ~ 7") == 0);
+
+    qrustcode_encoder_free(encoder);
+    qrustcode_encoder_free(NULL);
+
+    return 0;
+}
@@ -0,0 +1,67 @@
+// Base45 of RFC 9285, its 45 characters are those of Alphanumeric mode in the same order
+use crate::prelude::*;
+
+use crate::decoder::DecodeError;
+
+const ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+const INVALID: DecodeError = DecodeError::InvalidPayload("invalid Base45");
+
+// every 2 bytes as 3 characters, least significant first, a last odd byte as 2
+pub fn encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(2) * 3);
+
+    for chunk in bytes.chunks(2) {
+        let (mut n, count) = match chunk {
+            &[a, b] => ((a as usize) << 8 | b as usize, 3),
+            _ => (chunk[0] as usize, 2),
+        };
+
+        for _ in 0..count {
+            text.push(ALPHABET[n % 45] as char);
+            n /= 45;
+        }
+    }
+
+    text
+}
+
+pub fn decode(text: &str) -> Result<Vec<u8>, DecodeError> {
+    let values = text.bytes()
+        .map(|b| ALPHABET.iter().position(|&c| c == b).ok_or(INVALID))
+        .collect::<Result<Vec<usize>, DecodeError>>()?;
+
+    let mut bytes = Vec::with_capacity(values.len() / 3 * 2 + 1);
+    for chunk in values.chunks(3) {
+        let n = chunk.iter().rev().fold(0, |n, &value| n * 45 + value);
+
+        match chunk.len() {
+            3 if n <= 0xffff => bytes.extend_from_slice(&(n as u16).to_be_bytes()),
+            2 if n <= 0xff => bytes.push(n as u8),
+            _ => return Err(INVALID),
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[test]
+fn test_base45() {
+    // examples of RFC 9285
+    for &(bytes, text) in [
+        (&b"AB"[..], "BB8"),
+        (&b"Hello!!"[..], "%69 VD92EX0"),
+        (&b"base-45"[..], "UJCLQE7W581"),
+        (&b"ietf!"[..], "QED8WEX0"),
+        (&b""[..], ""),
+        (&[0xff, 0xff][..], "FGW"),
+    ].iter() {
+        assert_eq!(encode(bytes), text);
+        assert_eq!(decode(text).unwrap(), bytes);
+    }
+
+    // 65536, a single character left, and a character outside the alphabet
+    for text in ["GGW", "BB8A", "bb8"].iter() {
+        assert_eq!(decode(text), Err(INVALID));
+    }
+}
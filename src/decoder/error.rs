@@ -0,0 +1,36 @@
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    // the bytes are not a PBM, PGM, PPM or PNG image, or a variant of them which isn't supported
+    UnsupportedImage,
+    // the image is truncated or broken, with the reason
+    InvalidImage(&'static str),
+    // there is no symbol in the image, or its size isn't 21 ~ 177 modules
+    NotFound,
+    // both copies of the format information have more than 3 wrong bits
+    InvalidFormat,
+    // index of the first block with more errors than its ec codewords can correct
+    TooManyErrors(usize),
+    // the corrected data has a malformed or unsupported segment
+    InvalidData,
+    // the message isn't in the form `Decoded::decompress` expects, with the reason
+    InvalidPayload(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnsupportedImage => write!(f, "image is not a supported PBM, PGM, PPM or PNG"),
+            DecodeError::InvalidImage(reason) => write!(f, "invalid image: {}", reason),
+            DecodeError::NotFound => write!(f, "no symbol found"),
+            DecodeError::InvalidFormat => write!(f, "format information can't be read"),
+            DecodeError::TooManyErrors(block) => write!(f, "block {} has too many errors to correct", block),
+            DecodeError::InvalidData => write!(f, "data segments are malformed"),
+            DecodeError::InvalidPayload(reason) => write!(f, "invalid payload: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
@@ -0,0 +1,325 @@
+use crate::prelude::*;
+
+use super::{
+    DecodeError,
+    inflate::zlib_decompress,
+};
+
+const TRUNCATED: DecodeError = DecodeError::InvalidImage("truncated image");
+
+// grayscale pixels row by row, 0 is black and 255 is white
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, pixels: Vec<u8>) -> Image {
+        assert_eq!(pixels.len(), width * height);
+
+        Image { width, height, pixels }
+    }
+
+    // PBM(P1, P4), PGM(P2, P5), PPM(P6) or PNG, told apart by the magic number, colors are converted to luminance
+    pub fn parse(bytes: &[u8]) -> Result<Image, DecodeError> {
+        let mut planes = planes(bytes)?;
+        if planes.len() == 1 { return Ok(planes.remove(0)); }
+
+        let (red, green, blue) = (&planes[0], &planes[1], &planes[2]);
+        let pixels = (0..red.pixels.len()).map(|i| luminance(red.pixels[i], green.pixels[i], blue.pixels[i])).collect();
+
+        Ok(Image::new(red.width, red.height, pixels))
+    }
+
+    // red, green and blue planes of the same formats as `parse`, a grayscale image is the same in all of them
+    pub fn parse_channels(bytes: &[u8]) -> Result<[Image; 3], DecodeError> {
+        let mut planes = planes(bytes)?;
+        if planes.len() == 1 { return Ok([planes[0].clone(), planes[0].clone(), planes.remove(0)]); }
+
+        let blue = planes.pop().unwrap();
+        let green = planes.pop().unwrap();
+
+        Ok([planes.pop().unwrap(), green, blue])
+    }
+
+    pub fn width(&self) -> usize { self.width }
+
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u8 { self.pixels[y * self.width + x] }
+
+    pub(crate) fn pixels(&self) -> &[u8] { &self.pixels }
+}
+
+// one plane of a grayscale image, or red, green and blue planes of a color one
+fn planes(bytes: &[u8]) -> Result<Vec<Image>, DecodeError> {
+    match bytes {
+        [b'P', kind @ b'1'..=b'6', ..] if *kind != b'3' => netpbm(*kind, &bytes[2..]),
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => png(&bytes[8..]),
+        _ => Err(DecodeError::UnsupportedImage)
+    }
+}
+
+fn luminance(r: u8, g: u8, b: u8) -> u8 { ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8 }
+
+// interleaved samples of every pixel into one plane per channel
+fn split(width: usize, height: usize, channels: usize, samples: &[u8]) -> Vec<Image> {
+    (0..channels)
+        .map(|channel| Image::new(width, height, samples.iter().skip(channel).step_by(channels).cloned().collect()))
+        .collect()
+}
+
+// header fields of a netpbm image, separated by whitespace and `#` comments
+struct Header<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Header<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            match byte {
+                b'#' => while self.bytes.get(self.position).is_some_and(|&byte| byte != b'\n') { self.position += 1; },
+                _ if byte.is_ascii_whitespace() => self.position += 1,
+                _ => break
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, DecodeError> {
+        self.skip_whitespace();
+
+        let start = self.position;
+        while self.bytes.get(self.position).is_some_and(u8::is_ascii_digit) { self.position += 1; }
+        if start == self.position { return Err(DecodeError::InvalidImage("invalid netpbm header")); }
+
+        self.bytes[start..self.position]
+            .iter()
+            .try_fold(0usize, |n, &digit| n.checked_mul(10)?.checked_add((digit - b'0') as usize))
+            .ok_or(DecodeError::InvalidImage("invalid netpbm header"))
+    }
+
+    // a single `0` or `1` of P1, digits don't need whitespace between them
+    fn bit(&mut self) -> Result<u8, DecodeError> {
+        self.skip_whitespace();
+
+        let bit = match self.bytes.get(self.position) {
+            Some(b'0') => 0,
+            Some(b'1') => 1,
+            _ => return Err(TRUNCATED)
+        };
+        self.position += 1;
+
+        Ok(bit)
+    }
+
+    // binary data starts after a single whitespace
+    fn raster(&self) -> &'a [u8] { self.bytes.get(self.position + 1..).unwrap_or(&[]) }
+}
+
+// P1, P4: 1 is black; P2, P5, P6: `max` is white
+fn netpbm(kind: u8, bytes: &[u8]) -> Result<Vec<Image>, DecodeError> {
+    let mut header = Header { bytes, position: 0 };
+    let width = header.number()?;
+    let height = header.number()?;
+    let max = if kind == b'1' || kind == b'4' { 1 } else { header.number()? };
+    if max == 0 || max > 0xffff { return Err(DecodeError::InvalidImage("invalid netpbm header")); }
+
+    let channels = if kind == b'6' { 3 } else { 1 };
+    let len = width.checked_mul(height)
+        .and_then(|len| len.checked_mul(channels))
+        .ok_or(DecodeError::InvalidImage("invalid netpbm header"))?;
+    let mut pixels = Vec::with_capacity(len);
+    match kind {
+        b'1' => for _ in 0..len { pixels.push(255 - 255 * header.bit()?); },
+        b'2' => for _ in 0..len { pixels.push((header.number()?.min(max) * 255 / max) as u8); },
+        b'4' => {
+            let stride = width.div_ceil(8);
+            let raster = header.raster();
+            if raster.len() < stride * height { return Err(TRUNCATED); }
+
+            for row in raster.chunks(stride).take(height) {
+                pixels.extend((0..width).map(|x| if row[x / 8] & 0x80 >> (x % 8) != 0 { 0 } else { 255 }));
+            }
+        }
+        _ => {
+            let bytes_per_sample = if max < 256 { 1 } else { 2 };
+            let raster = header.raster();
+            if raster.len() < len * bytes_per_sample { return Err(TRUNCATED); }
+
+            pixels.extend(raster.chunks(bytes_per_sample).take(len).map(|sample| {
+                let value = sample.iter().fold(0, |value, &byte| value << 8 | byte as usize);
+
+                (value.min(max) * 255 / max) as u8
+            }));
+        }
+    }
+
+    Ok(split(width, height, channels, &pixels))
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+
+    if pa <= pb && pa <= pc { a } else if pb <= pc { b } else { c }
+}
+
+// non-interlaced PNG of any color type over a white background, a palette is expanded to its colors
+fn png(bytes: &[u8]) -> Result<Vec<Image>, DecodeError> {
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut data = vec![];
+
+    let mut chunks = bytes;
+    loop {
+        if chunks.len() < 12 { return Err(TRUNCATED); }
+
+        let len = u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+        let body = chunks.get(8..8 + len).ok_or(TRUNCATED)?;
+        match &chunks[4..8] {
+            b"IHDR" if len == 13 => header = Some(body),
+            b"PLTE" => palette = body,
+            b"IDAT" => data.extend_from_slice(body),
+            b"IEND" => break,
+            _ => ()
+        }
+        chunks = chunks.get(12 + len..).ok_or(TRUNCATED)?;
+    }
+
+    let header = header.ok_or(DecodeError::InvalidImage("missing IHDR chunk"))?;
+    let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let (depth, color_type) = (header[8] as usize, header[9]);
+    let channels = match (color_type, depth) {
+        (0, 1) | (0, 2) | (0, 4) | (0, 8) | (0, 16) => 1,
+        (3, 1) | (3, 2) | (3, 4) | (3, 8) => 1,
+        (2, 8) | (2, 16) => 3,
+        (4, 8) | (4, 16) => 2,
+        (6, 8) | (6, 16) => 4,
+        _ => return Err(DecodeError::UnsupportedImage)
+    };
+    if header[12] != 0 { return Err(DecodeError::UnsupportedImage); }
+
+    let raw = zlib_decompress(&data)?;
+    let stride = (width * channels * depth).div_ceil(8);
+    // filters work on whole pixels, at least one byte
+    let bpp = (channels * depth).div_ceil(8);
+    if raw.len() < (stride + 1) * height { return Err(TRUNCATED); }
+
+    let mut rows = vec![0; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, rest) = rows.split_at_mut(y * stride);
+        let prev = if y == 0 { None } else { Some(&done[(y - 1) * stride..]) };
+        let row = &mut rest[..stride];
+
+        for i in 0..stride {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = prev.map_or(0, |prev| prev[i]);
+            let c = if i >= bpp { prev.map_or(0, |prev| prev[i - bpp]) } else { 0 };
+
+            row[i] = line[i].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(DecodeError::InvalidImage("invalid PNG filter"))
+            });
+        }
+    }
+
+    // sample `i` of a row scaled to 0 ~ 255
+    let sample = |row: &[u8], i: usize| -> u8 {
+        match depth {
+            8 => row[i],
+            16 => row[2 * i],
+            _ => {
+                let bit = i * depth;
+                let value = row[bit / 8] >> (8 - depth - bit % 8) & ((1 << depth) - 1) as u8;
+
+                if color_type == 3 { value } else { (value as usize * 255 / ((1 << depth) - 1)) as u8 }
+            }
+        }
+    };
+    let over_white = |gray: u8, alpha: u8| ((gray as u32 * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8;
+
+    // gray or red, green and blue of every pixel
+    let planes = if color_type & 2 == 0 { 1 } else { 3 };
+    let mut samples = Vec::with_capacity(width * height * planes);
+    for row in rows.chunks(stride) {
+        for x in 0..width {
+            let i = x * channels;
+
+            match color_type {
+                0 => samples.push(sample(row, i)),
+                2 => samples.extend((0..3).map(|j| sample(row, i + j))),
+                3 => {
+                    let color = palette.get(3 * sample(row, i) as usize..)
+                        .filter(|color| color.len() >= 3)
+                        .ok_or(DecodeError::InvalidImage("invalid PNG palette"))?;
+
+                    samples.extend_from_slice(&color[..3]);
+                }
+                4 => samples.push(over_white(sample(row, i), sample(row, i + 1))),
+                _ => samples.extend((0..3).map(|j| over_white(sample(row, i + j), sample(row, i + 3))))
+            }
+        }
+    }
+
+    Ok(split(width, height, planes, &samples))
+}
+
+#[test]
+fn test_netpbm() {
+    let image = Image::parse(b"P1\n# comment\n3 2\n1 0 1\n011").unwrap();
+    assert_eq!((image.width(), image.height()), (3, 2));
+    assert_eq!(image.pixels(), [0, 255, 0, 255, 0, 0]);
+
+    let image = Image::parse(b"P4 3 2\n\xa0\x60").unwrap();
+    assert_eq!(image.pixels(), [0, 255, 0, 255, 0, 0]);
+
+    let image = Image::parse(b"P2 2 1 4 0 4").unwrap();
+    assert_eq!(image.pixels(), [0, 255]);
+
+    let image = Image::parse(b"P5 2 1 65535\n\x80\x00\xff\xff").unwrap();
+    assert_eq!(image.pixels(), [127, 255]);
+
+    assert_eq!(Image::parse(b"P3 1 1 255 0 0 0"), Err(DecodeError::UnsupportedImage));
+    assert_eq!(Image::parse(b"P4 8 2\n\xff"), Err(TRUNCATED));
+}
+
+#[test]
+fn test_png() {
+    use crate::{encoder::Encoder, renderer::Renderer};
+
+    let mut encoder = Encoder::new().ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let renderer = Renderer::new(&matrix).scale(3);
+    let image = Image::parse(&renderer.png()).unwrap();
+
+    assert_eq!(image, Image::parse(&renderer.pbm()).unwrap());
+
+    // 2 x 2 RGBA, filtered by hand with 1(sub) and 4(paeth)
+    //     [(0, 0, 0, 255), (255, 255, 255, 255)], [(255, 0, 0, 255), (0, 0, 0, 0)]
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut push_chunk = |kind: &[u8], body: &[u8]| {
+        png.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(body);
+        png.extend_from_slice(&[0; 4]);
+    };
+    push_chunk(b"IHDR", &[0, 0, 0, 2, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+    push_chunk(b"IDAT", &crate::renderer::png::zlib_stored(&[
+        1, 0, 0, 0, 255, 255, 255, 255, 0,
+        4, 255, 0, 0, 0, 1, 1, 1, 1,
+    ]));
+    push_chunk(b"IEND", &[]);
+
+    // red -> 76, transparent -> white
+    assert_eq!(Image::parse(&png).unwrap().pixels(), [0, 255, 76, 255]);
+}
@@ -0,0 +1,253 @@
+use crate::prelude::*;
+
+use super::DecodeError;
+
+const TRUNCATED: DecodeError = DecodeError::InvalidImage("truncated deflate stream");
+const INVALID: DecodeError = DecodeError::InvalidImage("invalid deflate stream");
+const CHECKSUM: DecodeError = DecodeError::InvalidImage("zlib checksum doesn't match");
+
+// base and extra bits of length symbols 257 ~ 285
+pub(crate) const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258
+];
+pub(crate) const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+// base and extra bits of distance symbols 0 ~ 29
+pub(crate) const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577
+];
+pub(crate) const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13
+];
+
+// order of code length code lengths in a dynamic block header
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+// deflate packs bits from the least significant bit of every byte
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, count: u8) -> Result<u32, DecodeError> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.bytes.get(self.position / 8).ok_or(TRUNCATED)?;
+
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << i;
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+
+    // skip to the next byte boundary, return the bytes from there
+    fn align(&mut self) -> &'a [u8] {
+        self.position = self.position.div_ceil(8) * 8;
+
+        self.bytes.get(self.position / 8..).unwrap_or(&[])
+    }
+}
+
+// canonical Huffman code, the symbols are sorted by their codes
+struct Huffman {
+    // counts[length]
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; 16];
+        for &length in lengths { counts[length as usize] += 1; }
+        counts[0] = 0;
+
+        let mut offsets = [0; 16];
+        for length in 1..15 { offsets[length + 1] = offsets[length] + counts[length]; }
+
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    // read one bit at a time, codes of the same length are consecutive from `first`
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, DecodeError> {
+        let (mut code, mut first, mut index) = (0, 0, 0);
+
+        for &count in self.counts[1..].iter() {
+            code |= reader.bits(1)? as usize;
+            let count = count as usize;
+            if code < first + count { return Ok(self.symbols[index + code - first]); }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(INVALID)
+    }
+}
+
+fn fixed_huffman() -> (Huffman, Huffman) {
+    let mut lengths = [8; 288];
+    for length in lengths[144..256].iter_mut() { *length = 9; }
+    for length in lengths[256..280].iter_mut() { *length = 7; }
+
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_huffman(reader: &mut BitReader) -> Result<(Huffman, Huffman), DecodeError> {
+    let literals = reader.bits(5)? as usize + 257;
+    let distances = reader.bits(5)? as usize + 1;
+    let code_lengths = reader.bits(4)? as usize + 4;
+
+    let mut lengths = [0; 19];
+    for &i in CODE_LENGTH_ORDER[..code_lengths].iter() { lengths[i] = reader.bits(3)? as u8; }
+    let code_length_huffman = Huffman::new(&lengths);
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (length, repeat) = match code_length_huffman.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or(INVALID)?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            18 => (0, 11 + reader.bits(7)?),
+            _ => return Err(INVALID)
+        };
+
+        for _ in 0..repeat { lengths.push(length); }
+    }
+    if lengths.len() != literals + distances { return Err(INVALID); }
+
+    Ok((Huffman::new(&lengths[..literals]), Huffman::new(&lengths[literals..])))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    (literal, distance): &(Huffman, Huffman),
+    output: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    loop {
+        let symbol = literal.decode(reader)? as usize;
+        if symbol < 256 {
+            output.push(symbol as u8);
+
+            continue;
+        }
+        if symbol == 256 { return Ok(()); }
+
+        let symbol = symbol - 257;
+        if symbol >= 29 { return Err(INVALID); }
+        let length = LENGTH_BASES[symbol] as usize + reader.bits(LENGTH_EXTRA_BITS[symbol])? as usize;
+
+        let symbol = distance.decode(reader)? as usize;
+        if symbol >= 30 { return Err(INVALID); }
+        let distance = DISTANCE_BASES[symbol] as usize + reader.bits(DISTANCE_EXTRA_BITS[symbol])? as usize;
+        if distance > output.len() { return Err(INVALID); }
+
+        // the copy may overlap the bytes it produces
+        for _ in 0..length { output.push(output[output.len() - distance]); }
+    }
+}
+
+// raw deflate stream, RFC 1951, with the number of bytes it takes up
+pub(crate) fn inflate(bytes: &[u8]) -> Result<(Vec<u8>, usize), DecodeError> {
+    let mut reader = BitReader { bytes, position: 0 };
+    let mut output = vec![];
+
+    loop {
+        let last = reader.bits(1)? == 1;
+
+        match reader.bits(2)? {
+            0 => {
+                let bytes = reader.align();
+                if bytes.len() < 4 { return Err(TRUNCATED); }
+
+                let len = u16::from_le_bytes([bytes[0], bytes[1]]);
+                if len != !u16::from_le_bytes([bytes[2], bytes[3]]) { return Err(INVALID); }
+
+                let block = bytes.get(4..4 + len as usize).ok_or(TRUNCATED)?;
+                output.extend_from_slice(block);
+                reader.position += (4 + len as usize) * 8;
+            }
+            1 => inflate_block(&mut reader, &fixed_huffman(), &mut output)?,
+            2 => {
+                let huffman = dynamic_huffman(&mut reader)?;
+                inflate_block(&mut reader, &huffman, &mut output)?;
+            }
+            _ => return Err(INVALID)
+        }
+
+        if last { return Ok((output, reader.position.div_ceil(8))); }
+    }
+}
+
+// zlib wrapped deflate stream, RFC 1950, the Adler-32 checksum of the output ends it
+pub(crate) fn zlib_decompress(bytes: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    use crate::renderer::png::adler32;
+
+    if bytes.len() < 2 { return Err(TRUNCATED); }
+
+    let header = u16::from_be_bytes([bytes[0], bytes[1]]);
+    // deflate without a preset dictionary
+    if bytes[0] & 0x0f != 8 || !header.is_multiple_of(31) || bytes[1] & 0x20 != 0 { return Err(INVALID); }
+
+    let (output, len) = inflate(&bytes[2..])?;
+    let checksum = &bytes[2 + len..];
+    if checksum.len() < 4 { return Err(TRUNCATED); }
+    // nothing follows the stream
+    if checksum.len() > 4 { return Err(INVALID); }
+    if checksum != adler32(&output).to_be_bytes() { return Err(CHECKSUM); }
+
+    Ok(output)
+}
+
+#[test]
+fn test_inflate() {
+    use crate::renderer::png::zlib_stored;
+
+    // stored blocks, more than one
+    let bytes = (0..70000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+    assert_eq!(zlib_decompress(&zlib_stored(&bytes)).unwrap(), bytes);
+    assert_eq!(zlib_decompress(&zlib_stored(&[])).unwrap(), Vec::<u8>::new());
+
+    // fixed Huffman codes, python: zlib.compress(b"hello hello hello hello")
+    assert_eq!(
+        zlib_decompress(&[
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0x68, 0x03, 0x08, 0xb1,
+        ]).unwrap(),
+        b"hello hello hello hello".to_vec()
+    );
+
+    // dynamic Huffman codes, python: zlib.compress(message, 9)
+    let message = "abbcacacaabcabaacbaabdbaaaa caaaadaaba aabbadc aa bbaaabaac baaa adbabcbddaabbcabacbcdbcacbabbab aba";
+    assert_eq!(
+        zlib_decompress(&[
+            0x78, 0xda, 0x1d, 0x8c, 0x81, 0x15, 0x00, 0x40, 0x08, 0x41, 0x57, 0xb1, 0x9a, 0xaf, 0xfd, 0x67,
+            0x38, 0xee, 0xf5, 0xa2, 0x22, 0x86, 0x78, 0xe5, 0x32, 0x76, 0xda, 0x5c, 0xc1, 0x56, 0x86, 0xd7,
+            0xdd, 0x2a, 0xe0, 0x4b, 0x59, 0x4c, 0x9c, 0x53, 0x1b, 0xe4, 0x9a, 0x09, 0x37, 0xdf, 0x8f, 0x08,
+            0xb9, 0x65, 0xb2, 0x17, 0xd4, 0xcb, 0x03, 0x36, 0xd1, 0x24, 0x6a,
+        ]).unwrap(),
+        message.as_bytes().to_vec()
+    );
+
+    assert_eq!(zlib_decompress(&[0x78, 0x9c, 0xcb]), Err(TRUNCATED));
+
+    // the checksum is missing, wrong or followed by garbage
+    let zlib = zlib_stored(b"hello");
+    assert_eq!(zlib_decompress(&zlib[..zlib.len() - 1]), Err(TRUNCATED));
+    let mut corrupted = zlib.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+    assert_eq!(zlib_decompress(&corrupted), Err(CHECKSUM));
+    let mut garbage = zlib.clone();
+    garbage.push(0);
+    assert_eq!(zlib_decompress(&garbage), Err(INVALID));
+}
@@ -0,0 +1,76 @@
+use super::{
+    DecodeError,
+    Image,
+};
+use crate::encoder::Matrix;
+
+impl Image {
+    // sample the module grid of the symbol, dark modules are 1
+    //
+    // the symbol must be upright and axis-aligned with a light quiet zone, like the output of `Renderer`, a scan or a
+    // screenshot of one; rotated or perspective-distorted photos are not located. A light on dark symbol is located by
+    // its light modules and sampled as it is, `decode` reads it reversed
+    pub fn to_matrix(&self) -> Result<Matrix, DecodeError> {
+        let pixels = self.pixels();
+        let (min, max) = pixels.iter().fold((255, 0), |(min, max), &pixel| (pixel.min(min), pixel.max(max)));
+        if min == max { return Err(DecodeError::NotFound); }
+
+        let threshold = (min as u16 + max as u16).div_ceil(2) as u8;
+        let is_dark = |x: usize, y: usize| self.pixel(x, y) < threshold;
+
+        let (left, top, width, height, modules) = self.locate(&is_dark).or_else(|_| self.locate(&|x, y| !is_dark(x, y)))?;
+
+        // the center of every module
+        Ok(Matrix((0..modules)
+            .map(|y| (0..modules)
+                .map(|x| is_dark(left + (2 * x + 1) * width / (2 * modules), top + (2 * y + 1) * height / (2 * modules)) as u8)
+                .collect())
+            .collect()))
+    }
+
+    // (left, top, width, height, modules) of the symbol drawn in `ink`
+    fn locate(&self, ink: &dyn Fn(usize, usize) -> bool) -> Result<(usize, usize, usize, usize, usize), DecodeError> {
+        // bounding box of the ink, it is the symbol without its quiet zone
+        let (mut left, mut top, mut right, mut bottom) = (self.width(), self.height(), 0, 0);
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if ink(x, y) {
+                    left = left.min(x);
+                    top = top.min(y);
+                    right = right.max(x);
+                    bottom = bottom.max(y);
+                }
+            }
+        }
+        let (width, height) = (right + 1 - left, bottom + 1 - top);
+
+        // the top left finder pattern is 7 modules of ink wide along the top and the left edge
+        let horizontal = (left..=right).take_while(|&x| ink(x, top)).count();
+        let vertical = (top..=bottom).take_while(|&y| ink(left, y)).count();
+        if horizontal < 7 || vertical < 7 { return Err(DecodeError::NotFound); }
+
+        // modules = size / (run / 7), version = (modules - 17) / 4, both rounded
+        let modules = (7 * 2 * (width + height) + horizontal + vertical) / (2 * (horizontal + vertical));
+        if !(19..=179).contains(&modules) { return Err(DecodeError::NotFound); }
+        let version = ((modules - 15) / 4).clamp(1, 40);
+
+        Ok((left, top, width, height, version * 4 + 17))
+    }
+}
+
+#[test]
+fn test_to_matrix() {
+    use crate::{encoder::Encoder, renderer::Renderer};
+
+    for &(message, scale, quiet_zone) in [("01234567", 1, 4), ("HELLO WORLD", 3, 1), (&"a".repeat(500)[..], 5, 0)].iter() {
+        let matrix = Encoder::new().encode(message).unwrap().as_matrix();
+        let image = Image::parse(&Renderer::new(&matrix).scale(scale).quiet_zone(quiet_zone).pbm()).unwrap();
+        let sampled = image.to_matrix().unwrap();
+
+        for y in 0..matrix.width() {
+            for x in 0..matrix.width() { assert_eq!(sampled.is_dark(x, y), matrix.is_dark(x, y)); }
+        }
+    }
+
+    assert_eq!(Image::new(3, 3, vec![255; 9]).to_matrix(), Err(DecodeError::NotFound));
+}
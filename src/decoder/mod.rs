@@ -0,0 +1,22 @@
+mod error;
+mod image;
+pub(crate) mod inflate;
+mod locate;
+mod read;
+
+pub use self::{
+    error::DecodeError,
+    image::Image,
+    read::{decode, Decoded},
+};
+pub(crate) use self::read::orient;
+
+// read the message of a symbol in a PBM, PGM, PPM or PNG image
+pub fn decode_image(bytes: &[u8]) -> Result<Decoded, DecodeError> { decode(&Image::parse(bytes)?.to_matrix()?) }
+
+// read the symbols in the red, green and blue channels of an image, as `Renderer::layers` draws them
+pub fn decode_layers(bytes: &[u8]) -> Result<[Decoded; 3], DecodeError> {
+    let [red, green, blue] = Image::parse_channels(bytes)?;
+
+    Ok([decode(&red.to_matrix()?)?, decode(&green.to_matrix()?)?, decode(&blue.to_matrix()?)?])
+}
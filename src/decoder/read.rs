@@ -0,0 +1,431 @@
+use crate::prelude::*;
+
+use super::DecodeError;
+use crate::encoder::{
+    Matrix,
+    Mode,
+    Segment,
+};
+
+const EC_LEVELS: [char; 4] = ['L', 'M', 'Q', 'H'];
+
+// everything read back from a symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    // index from 1
+    pub version: usize,
+    pub ec_level: char,
+    // mask reference 0 ~ 7
+    pub mask: usize,
+    // Byte segments which aren't UTF-8 are converted lossily, `data` keeps their bytes
+    pub segments: Vec<Segment>,
+    // the message, Kanji and Chinese segments as UTF-8
+    pub data: Vec<u8>,
+    // codewords fixed by error correction
+    pub corrected: usize,
+    // penalties of the 4 conditions for every mask, the same report as `Explanation`
+    pub penalties: [[u32; 4]; 8],
+    // the symbol was light on dark
+    pub inverted: bool,
+    // the symbol was a mirror image, transposed or left and right swapped
+    pub mirrored: bool,
+}
+
+impl Decoded {
+    // the bytes `Encoder::encode_compressed` took, `data` as Base45 of a zlib stream
+    pub fn decompress(&self) -> Result<Vec<u8>, DecodeError> {
+        use super::inflate::zlib_decompress;
+
+        let text = core::str::from_utf8(&self.data).map_err(|_| DecodeError::InvalidPayload("invalid Base45"))?;
+
+        zlib_decompress(&crate::base45::decode(text)?).map_err(|_| DecodeError::InvalidPayload("invalid zlib stream"))
+    }
+}
+
+// modules of the 7 x 7 corner at (fx, fy) which don't match a finder pattern
+fn finder_errors(matrix: &Matrix, fx: usize, fy: usize) -> usize {
+    (0..7)
+        .flat_map(|y| (0..7).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            // the ring 1 module in is light
+            let ring = (x as isize - 3).abs().max((y as isize - 3).abs());
+
+            matrix.is_dark(fx + x, fy + y) != (ring != 2)
+        })
+        .count()
+}
+
+// (distance, ec level, mask) of the format information nearest to the better copy
+fn nearest_format(matrix: &Matrix) -> (u32, usize, usize) {
+    use crate::encoder::qrcode_info::format_bits;
+
+    let copies = matrix.format_info();
+
+    (0..32)
+        .map(|i| {
+            let (ec_level, mask) = (i >> 3, i & 7);
+            let distance = copies.iter().map(|&bits| (bits ^ format_bits(ec_level, mask)).count_ones()).min().unwrap();
+
+            (distance, ec_level, mask)
+        })
+        .min()
+        .unwrap()
+}
+
+// (ec level, mask) of the nearest format information, up to 3 wrong bits in the better copy
+//
+// the complement of a format information is another valid one and a transposed one is within 3 bits of one, so the
+// symbol must be upright: its finder patterns match better than the bottom right corner, which a light on dark symbol
+// or one with left and right swapped fails, and its format information is nearer to a codeword than transposed
+fn read_format(matrix: &Matrix) -> Result<(usize, usize), DecodeError> {
+    let fix = matrix.width() - 7;
+    let worst = [(0, 0), (fix, 0), (0, fix)].iter().map(|&(fx, fy)| finder_errors(matrix, fx, fy)).max().unwrap();
+    if worst >= finder_errors(matrix, fix, fix) { return Err(DecodeError::InvalidFormat); }
+
+    let (distance, ec_level, mask) = nearest_format(matrix);
+    if distance > 3 || distance > nearest_format(&matrix.transposed()).0 { return Err(DecodeError::InvalidFormat); }
+
+    Ok((ec_level, mask))
+}
+
+// the symbol as it was encoded with its (ec level, mask), and whether it was light on dark or a mirror image
+//
+// the other orientations are tried only if the finder patterns or the format information don't read upright, the
+// error is the upright one if none of them reads
+pub(crate) fn orient(matrix: &Matrix) -> Result<(Matrix, (usize, usize), bool, bool), DecodeError> {
+    let width = matrix.width();
+    if !(21..=177).contains(&width) || width % 4 != 1 { return Err(DecodeError::NotFound); }
+
+    let error = match read_format(matrix) {
+        Ok(format) => return Ok((matrix.clone(), format, false, false)),
+        Err(error) => error,
+    };
+
+    let inverted = matrix.inverted();
+    vec![
+        (inverted.clone(), true, false),
+        (matrix.transposed(), false, true),
+        (matrix.mirrored(), false, true),
+        (inverted.transposed(), true, true),
+        (inverted.mirrored(), true, true),
+    ]
+        .into_iter()
+        .find_map(|(matrix, inverted, mirrored)| {
+            read_format(&matrix).ok().map(|format| (matrix, format, inverted, mirrored))
+        })
+        .ok_or(error)
+}
+
+// split interleaved codewords into blocks of data + ec, the reverse of `interleave_with_ec`
+//
+// version: index from 1
+// ec_level: same as `Encoder`
+fn deinterleave(codewords: &[u8], version: usize, ec_level: usize) -> (Vec<Vec<u8>>, usize) {
+    use crate::encoder::qrcode_info::{DATA_DISTRIBUTIONS, EC_CW_PER_BLOCKS};
+
+    let data_distribution = DATA_DISTRIBUTIONS[version - 1][ec_level];
+    let g1_blocks_num = data_distribution[0] as usize;
+    let g1_cw_per_block = data_distribution[1] as usize;
+    let ec_cw_per_block = EC_CW_PER_BLOCKS[version - 1][ec_level] as usize;
+    let data_len = |block: usize| g1_cw_per_block + (block >= g1_blocks_num) as usize;
+
+    let mut blocks = vec![vec![]; g1_blocks_num + data_distribution[2] as usize];
+    let mut codewords = codewords.iter().cloned();
+
+    for i in 0..=g1_cw_per_block {
+        for (j, block) in blocks.iter_mut().enumerate() {
+            if i < data_len(j) { block.extend(codewords.next()); }
+        }
+    }
+    for _ in 0..ec_cw_per_block {
+        for block in blocks.iter_mut() { block.extend(codewords.next()); }
+    }
+
+    (blocks, ec_cw_per_block)
+}
+
+// bits from the most significant bit of every byte
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn remaining(&self) -> usize { self.bytes.len() * 8 - self.position }
+
+    fn bits(&mut self, count: usize) -> Result<u16, DecodeError> {
+        if count > self.remaining() { return Err(DecodeError::InvalidData); }
+
+        let mut value = 0;
+        for _ in 0..count {
+            value = value << 1 | (self.bytes[self.position / 8] >> (7 - self.position % 8) & 1) as u16;
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+}
+
+// double-byte characters to UTF-8
+fn double_byte(encoding: &'static encoding_rs::Encoding, bytes: &[u8]) -> Result<String, DecodeError> {
+    use encoding_rs::DecoderResult;
+
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut utf8 = vec![0; decoder.max_utf8_buffer_length_without_replacement(bytes.len()).ok_or(DecodeError::InvalidData)?];
+    let (result, _, written) = decoder.decode_to_utf8_without_replacement(bytes, &mut utf8, true);
+    if result != DecoderResult::InputEmpty { return Err(DecodeError::InvalidData); }
+    utf8.truncate(written);
+
+    String::from_utf8(utf8).map_err(|_| DecodeError::InvalidData)
+}
+
+// segments of the data codewords until the terminator or the end
+//
+// version: index from 1
+fn read_segments(data: &[u8], version: usize) -> Result<Vec<(Mode, Vec<u8>)>, DecodeError> {
+    use crate::encoder::{capacity::indicator_bits, qrcode_info::ALPHANUMERIC_CHARS};
+
+    let mut reader = BitReader { bytes: data, position: 0 };
+    let mut segments = vec![];
+
+    // a terminator shorter than 4 bits is allowed at the end
+    while reader.remaining() >= 4 {
+        let mode = match reader.bits(4)? {
+            0b0000 => break,
+            0b0001 => Mode::Numeric,
+            0b0010 => Mode::Alphanumeric,
+            0b0100 => Mode::Byte,
+            0b1000 => Mode::Kanji,
+            0b1101 => Mode::Chinese,
+            // ECI designator of 1 ~ 3 bytes, the charset is not applied
+            0b0111 => {
+                let first = reader.bits(8)?;
+                if first & 0x80 != 0 { reader.bits(if first & 0x40 == 0 { 8 } else { 16 })?; }
+
+                continue;
+            }
+            // structured append: index, total and parity
+            0b0011 => {
+                reader.bits(16)?;
+
+                continue;
+            }
+            // FNC1 in the first position, or in the second one with an application indicator
+            0b0101 => continue,
+            0b1001 => {
+                reader.bits(8)?;
+
+                continue;
+            }
+            _ => return Err(DecodeError::InvalidData)
+        };
+
+        let count = reader.bits(indicator_bits(mode, version))? as usize;
+        let mut bytes = vec![];
+        match mode {
+            Mode::Numeric => {
+                for i in (0..count).step_by(3) {
+                    let digits = (count - i).min(3);
+                    let value = reader.bits([0, 4, 7, 10][digits])?;
+                    if value >= [1, 10, 100, 1000][digits] { return Err(DecodeError::InvalidData); }
+
+                    bytes.extend_from_slice(&format!("{:01$}", value, digits).into_bytes());
+                }
+            }
+            Mode::Alphanumeric => {
+                for _ in 0..count / 2 {
+                    let value = reader.bits(11)? as usize;
+                    if value >= 45 * 45 { return Err(DecodeError::InvalidData); }
+
+                    bytes.push(ALPHANUMERIC_CHARS[value / 45]);
+                    bytes.push(ALPHANUMERIC_CHARS[value % 45]);
+                }
+                if count % 2 == 1 {
+                    bytes.push(*ALPHANUMERIC_CHARS.get(reader.bits(6)? as usize).ok_or(DecodeError::InvalidData)?);
+                }
+            }
+            Mode::Byte => for _ in 0..count { bytes.push(reader.bits(8)? as u8); },
+            Mode::Kanji | Mode::Chinese => {
+                let mut double_bytes = Vec::with_capacity(2 * count);
+                for _ in 0..count {
+                    let value = reader.bits(13)?;
+                    let value = if mode == Mode::Kanji {
+                        // Shift JIS: the reverse of `kanji_encode`
+                        let value = ((value / 0xc0) << 8) | (value % 0xc0);
+
+                        value + if value < 0x1f00 { 0x8140 } else { 0xc140 }
+                    } else {
+                        // GB 2312
+                        let value = ((value / 0x60) << 8) | (value % 0x60);
+
+                        value + if value < 0x0a00 { 0xa1a1 } else { 0xa6a1 }
+                    };
+
+                    double_bytes.extend_from_slice(&value.to_be_bytes());
+                }
+
+                let encoding = if mode == Mode::Kanji { encoding_rs::SHIFT_JIS } else { encoding_rs::GBK };
+                bytes = double_byte(encoding, &double_bytes)?.into_bytes();
+            }
+            Mode::Unknown => unreachable!()
+        }
+
+        segments.push((mode, bytes));
+    }
+
+    Ok(segments)
+}
+
+// read the message of a symbol, correcting its errors, upright or as `orient` turns it
+pub fn decode(matrix: &Matrix) -> Result<Decoded, DecodeError> {
+    let (matrix, format, inverted, mirrored) = orient(matrix)?;
+
+    Ok(Decoded { inverted, mirrored, ..read_symbol(&matrix, format)? })
+}
+
+// `matrix` upright, with its format information read
+fn read_symbol(matrix: &Matrix, (ec_level, mask): (usize, usize)) -> Result<Decoded, DecodeError> {
+    use crate::gf256::{reed_solomon, QR_CODE};
+
+    let version = (matrix.width() - 17) / 4;
+
+    // copy the data modules into a clean symbol, then masking again reverts the mask
+    let mut symbol = Matrix::blank(version);
+    symbol.with_function_patterns(version);
+    let placement = symbol.placement();
+    for &(x, y) in placement.iter() { symbol.0[y][x] = matrix.0[y][x] & 1; }
+    symbol.data_mask(ec_level, mask);
+    let penalties = symbol.penalties(ec_level);
+
+    // remainder bits are dropped
+    let codewords = placement.chunks_exact(8)
+        .map(|bits| bits.iter().fold(0, |codeword, &(x, y)| codeword << 1 | symbol.0[y][x]))
+        .collect::<Vec<u8>>();
+
+    let (blocks, ec_cw_per_block) = deinterleave(&codewords, version, ec_level);
+    let reed_solomon = reed_solomon(&QR_CODE, ec_cw_per_block, 0);
+    let mut data = vec![];
+    let mut corrected = 0;
+    for (i, mut block) in blocks.into_iter().enumerate() {
+        corrected += reed_solomon.correct(&mut block).ok_or(DecodeError::TooManyErrors(i))?;
+        data.extend_from_slice(&block[..block.len() - ec_cw_per_block]);
+    }
+
+    let segments = read_segments(&data, version)?;
+
+    Ok(Decoded {
+        version,
+        ec_level: EC_LEVELS[ec_level],
+        mask,
+        segments: segments.iter()
+            .map(|(mode, bytes)| Segment { mode: *mode, data: String::from_utf8_lossy(bytes).into_owned() })
+            .collect(),
+        data: segments.into_iter().flat_map(|(_, bytes)| bytes).collect(),
+        corrected,
+        penalties,
+        inverted: false,
+        mirrored: false,
+    })
+}
+
+#[test]
+fn test_decode() {
+    use crate::encoder::Encoder;
+
+    for &(message, ec_level, mode) in [
+        ("01234567", "M", Mode::Numeric),
+        ("0123456789012", "L", Mode::Numeric),
+        ("HELLO WORLD", "Q", Mode::Alphanumeric),
+        ("hello, world!", "H", Mode::Byte),
+        ("ハロー・ワールド", "M", Mode::Kanji),
+        (&"https://github.com/AurevoirXavier/qrustcode ".repeat(20)[..], "L", Mode::Byte),
+        (&"1".repeat(7089)[..], "L", Mode::Numeric),
+    ].iter() {
+        let mut encoder = Encoder::new().ec_level(ec_level);
+        let explanation = encoder.explain(message).unwrap();
+        let matrix = encoder.as_matrix();
+        let decoded = decode(&matrix).unwrap();
+
+        assert_eq!(decoded.data, message.as_bytes());
+        assert_eq!(decoded.segments, vec![Segment::new(mode, message)]);
+        assert_eq!((decoded.version, decoded.ec_level, decoded.mask), (explanation.version, explanation.ec_level, explanation.mask));
+        assert_eq!(decoded.penalties, explanation.penalties);
+        assert_eq!(decoded.corrected, 0);
+    }
+}
+
+#[test]
+fn test_decode_errors() {
+    use crate::encoder::Encoder;
+
+    // 5-Q: 4 blocks of 18 ec codewords, each can correct 9 codewords
+    let mut matrix = Encoder::new().version(5).ec_level("Q").encode("HELLO WORLD").unwrap().as_matrix();
+    let placement = Matrix::blank(5).with_function_patterns(5).placement();
+
+    // flip one bit of the first 36 codewords, 9 per block
+    for bits in placement.chunks(8).take(36) { let (x, y) = bits[3]; matrix.0[y][x] ^= 1; }
+    let decoded = decode(&matrix).unwrap();
+    assert_eq!((decoded.data, decoded.corrected), (b"HELLO WORLD".to_vec(), 36));
+
+    // one more in the first block
+    let (x, y) = placement[36 * 8 + 3];
+    matrix.0[y][x] ^= 1;
+    assert_eq!(decode(&matrix), Err(DecodeError::TooManyErrors(0)));
+
+    // both copies of the format information are light, at least 5 bits away from any format information
+    let mut matrix = Encoder::new().version(1).encode("1").unwrap().as_matrix();
+    for i in (0..9).filter(|&i| i != 6) {
+        matrix.0[8][i] = 2;
+        matrix.0[i][8] = 2;
+    }
+    for i in 13..21 { matrix.0[8][i] = 2; }
+    for i in 14..21 { matrix.0[i][8] = 2; }
+    assert_eq!(decode(&matrix), Err(DecodeError::InvalidFormat));
+
+    // not Base45, and Base45 of bytes which aren't zlib
+    let decoded = decode(&Encoder::new().encode("hello").unwrap().as_matrix()).unwrap();
+    assert_eq!(decoded.decompress(), Err(DecodeError::InvalidPayload("invalid Base45")));
+    let decoded = decode(&Encoder::new().encode(&crate::base45::encode(b"hello")).unwrap().as_matrix()).unwrap();
+    assert_eq!(decoded.decompress(), Err(DecodeError::InvalidPayload("invalid zlib stream")));
+}
+
+#[test]
+fn test_decode_reversed() {
+    use crate::encoder::Encoder;
+
+    for message in ["HELLO WORLD", "01234567", &"hello, world! ".repeat(10)[..]].iter() {
+        for mask in 0..8 {
+            let matrix = Encoder::new().ec_level("M").mask(mask).encode(message).unwrap().as_matrix();
+            assert_eq!(decode(&matrix).map(|decoded| (decoded.inverted, decoded.mirrored)), Ok((false, false)));
+
+            for (symbol, inverted, mirrored) in [
+                (matrix.inverted(), true, false),
+                (matrix.transposed(), false, true),
+                (matrix.mirrored(), false, true),
+                (matrix.inverted().transposed(), true, true),
+                (matrix.inverted().mirrored(), true, true),
+            ].iter() {
+                let decoded = decode(symbol).unwrap();
+                assert_eq!((&decoded.data[..], decoded.inverted, decoded.mirrored), (message.as_bytes(), *inverted, *mirrored));
+            }
+        }
+    }
+
+    // codewords which fail in the orientation the finder patterns and format information tell aren't retried
+    let mut matrix = Encoder::new().version(1).ec_level("L").encode("HELLO WORLD").unwrap().as_matrix();
+    let placement = Matrix::blank(1).with_function_patterns(1).placement();
+    for &(x, y) in placement[..8 * 8].iter() { matrix.0[y][x] ^= 1; }
+    assert_eq!(decode(&matrix), Err(DecodeError::TooManyErrors(0)));
+    assert_eq!(decode(&matrix.inverted().transposed()), Err(DecodeError::TooManyErrors(0)));
+}
+
+#[test]
+fn test_read_segments() {
+    // ECI 26(UTF-8) | Chinese, 1 character | "啊": GB 2312 0xb0a1 - 0xa6a1 = 0x0a00 -> 0x0a * 0x60 + 0x00 = 0x03c0
+    // 0111 00011010 | 1101 00000001 | 0001111000000 | terminator
+    let data = [0x71, 0xad, 0x01, 0x1e, 0x00, 0x00];
+
+    assert_eq!(read_segments(&data, 1), Ok(vec![(Mode::Chinese, "啊".as_bytes().to_vec())]));
+    assert_eq!(read_segments(&[0b1111_0000], 1), Err(DecodeError::InvalidData));
+}
+
@@ -0,0 +1,107 @@
+use crate::prelude::*;
+
+use super::{
+    Encoder,
+    Error,
+    matrix::Matrix,
+    workspace::Workspace,
+};
+
+impl Encoder {
+    // encode every message with the same settings, results are in the input order
+    //
+    // every worker has its own copy of the encoder and a `Workspace`, see `encode_into`
+    pub fn encode_batch<I>(&self, messages: I) -> Vec<Result<Matrix, Error>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str> + Sync,
+    {
+        let messages = messages.into_iter().collect::<Vec<I::Item>>();
+
+        #[cfg(feature = "parallel")]
+        {
+            let workers = match self.workers {
+                0 => std::thread::available_parallelism().map_or(1, |workers| workers.get()),
+                workers => workers,
+            };
+
+            if workers > 1 && messages.len() > 1 { return self.encode_batch_parallel(&messages, workers); }
+        }
+
+        let mut encoder = self.clone();
+        let mut workspace = Workspace::new();
+
+        messages.iter()
+            .map(|message| encoder.encode_into(message.as_ref(), &mut workspace).cloned())
+            .collect()
+    }
+
+    // workers take the next message in turn, so a long message doesn't hold up a whole chunk
+    #[cfg(feature = "parallel")]
+    fn encode_batch_parallel<S: AsRef<str> + Sync>(&self, messages: &[S], workers: usize) -> Vec<Result<Matrix, Error>> {
+        use std::{
+            sync::atomic::{AtomicUsize, Ordering},
+            thread,
+        };
+
+        let next = AtomicUsize::new(0);
+        let mut results = Vec::with_capacity(messages.len());
+        results.resize_with(messages.len(), || None);
+
+        thread::scope(|scope| {
+            let handles = (0..workers.min(messages.len()))
+                .map(|_| scope.spawn(|| {
+                    let mut encoder = self.clone();
+                    let mut workspace = Workspace::new();
+                    let mut encoded = vec![];
+
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= messages.len() { break; }
+
+                        encoded.push((i, encoder.encode_into(messages[i].as_ref(), &mut workspace).cloned()));
+                    }
+
+                    encoded
+                }))
+                .collect::<Vec<_>>();
+
+            for handle in handles {
+                for (i, result) in handle.join().unwrap() { results[i] = Some(result); }
+            }
+        });
+
+        results.into_iter().map(Option::unwrap).collect()
+    }
+}
+
+#[test]
+fn test_encode_batch() {
+    // 4 + 10 + 10 * 20 = 214 bits, 2-Q holds 176 bits
+    let long = "1".repeat(60);
+    let messages = ["01234567", "HELLO WORLD", "ハロー・ワールド", "hello, world", &long[..], "0"];
+
+    for &workers in [1, 3, 0].iter() {
+        let results = Encoder::new().version(2).ec_level("Q").workers(workers).encode_batch(messages.iter());
+        assert_eq!(results.len(), messages.len());
+        assert_eq!(results[4], Err(Error::DataTooLong { bits: 214, capacity: 176 }));
+
+        for (message, result) in messages.iter().zip(results) {
+            let mut encoder = Encoder::new().version(2).ec_level("Q");
+
+            match encoder.encode(message) {
+                Ok(encoder) => assert_eq!(result.unwrap().0, encoder.as_matrix().0),
+                Err(e) => assert_eq!(result.unwrap_err(), e),
+            }
+        }
+    }
+
+    // the same halftone padding and mask as `encode`
+    let image = crate::decoder::Image::new(2, 2, vec![0, 255, 255, 0]);
+    let encoder = Encoder::new().version(6).halftone(&image).workers(2);
+    for (message, result) in messages.iter().zip(encoder.encode_batch(messages.iter())) {
+        assert_eq!(result.unwrap(), encoder.clone().encode(message).unwrap().as_matrix());
+    }
+
+    assert!(Encoder::new().workers(4).encode_batch(Vec::<String>::new()).is_empty());
+}
@@ -0,0 +1,48 @@
+use crate::prelude::*;
+
+use super::{
+    Matrix,
+    Mode,
+    parse_ec_level,
+};
+
+// mode indicator + character count indicator + encoded message, packed into `codewords`
+//
+// version: index from 1
+pub fn segment(mode: Mode, version: usize, message: &str, codewords: &mut Vec<u8>) {
+    use super::{
+        bits::Codewords,
+        capacity::indicator_bits,
+        encode::encode_segment,
+    };
+
+    encode_segment(&mut Codewords::new(codewords), mode, indicator_bits(mode, version), message);
+}
+
+// append ec codewords to data codewords, then interleave them into `interleaved`
+//
+// version: index from 1
+pub fn error_correction(codewords: &mut Vec<u8>, version: usize, ec_level: &str, interleaved: &mut Vec<u8>) {
+    use super::resolve::final_structure::interleave_with_ec;
+
+    let data_cws = codewords.len();
+    interleave_with_ec(codewords, data_cws, version - 1, parse_ec_level(ec_level).unwrap(), interleaved);
+}
+
+// finder, separator, alignment, timing patterns, dark module, reserved areas and version information
+//
+// version: index from 1
+pub fn function_patterns(version: usize) -> Matrix {
+    let mut matrix = Matrix::blank(version);
+    matrix.with_function_patterns(version);
+
+    matrix
+}
+
+pub fn placement(matrix: &mut Matrix, codewords: &[u8]) { matrix.place_codewords(codewords); }
+
+// evaluate every mask in place then apply the best one, in ec level L
+pub fn masking(matrix: &mut Matrix) {
+    let mask = Matrix::best_mask(&matrix.penalties(0));
+    matrix.data_mask(0, mask);
+}
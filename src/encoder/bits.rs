@@ -1,3 +1,5 @@
+use crate::prelude::*;
+
 use super::Encoder;
 
 pub fn push_binary(binary: &mut Vec<u8>, bits_count: usize, mut num: u16) {
@@ -20,6 +22,54 @@ fn test_push_binary() {
     assert_eq!(binary, vec![1, 0, 0, 1, 0, 0, 0, 0, 1, 1, 0, 1]);
 }
 
+// sink of encoded bits
+pub(crate) trait Bits {
+    // append the lowest `bits_count` bits of `num`
+    fn push_bits(&mut self, bits_count: usize, num: u16);
+}
+
+// one `u8` per bit
+impl Bits for Vec<u8> {
+    fn push_bits(&mut self, bits_count: usize, num: u16) { push_binary(self, bits_count, num); }
+}
+
+// bits packed into codewords, from the most significant bit
+pub(crate) struct Codewords<'a> {
+    pub(crate) codewords: &'a mut Vec<u8>,
+    pub(crate) len: usize,
+}
+
+impl<'a> Codewords<'a> {
+    pub(crate) fn new(codewords: &'a mut Vec<u8>) -> Codewords<'a> {
+        codewords.clear();
+
+        Codewords { codewords, len: 0 }
+    }
+}
+
+impl<'a> Bits for Codewords<'a> {
+    fn push_bits(&mut self, bits_count: usize, num: u16) {
+        for i in (0..bits_count).rev() {
+            if self.len.is_multiple_of(8) { self.codewords.push(0); }
+            if num >> i & 1 == 1 { self.codewords[self.len / 8] |= 0x80 >> (self.len % 8); }
+
+            self.len += 1;
+        }
+    }
+}
+
+#[test]
+fn test_codewords() {
+    let mut codewords = vec![0xff];
+    let mut bits = Codewords::new(&mut codewords);
+    bits.push_bits(4, 0b0001);
+    bits.push_bits(10, 8);
+    bits.push_bits(3, 0b101);
+
+    assert_eq!(bits.len, 17);
+    assert_eq!(codewords, vec![0b0001_0000, 0b0010_0010, 0b1000_0000]);
+}
+
 pub fn decimal(binary: &[u8]) -> u8 {
     let mut decimal = 0;
     for (exp, bit) in binary.iter().rev().enumerate() { decimal += bit << exp; }
@@ -28,31 +78,41 @@ pub fn decimal(binary: &[u8]) -> u8 {
 }
 
 impl Encoder {
-    pub fn decimal_data(&mut self) -> &mut Encoder {
+    pub(crate) fn terminate(&mut self) -> &mut Encoder {
         use super::qrcode_info::CAPACITIES;
 
+        let capacity = CAPACITIES[self.version][self.ec_level] as usize;
         let data = &mut self.data;
 
-        // terminator = 4
-        // 12 = 8 + terminator
-        // re = (4 + data.len()) % 8
-        for _ in 0..12 - (4 + data.len()) % 8 { data.push(0); }
+        // terminator is 4 zero bits at most, it can be shortened if the capacity is nearly full
+        let len = data.len() + (capacity - data.len()).min(4);
+        // pad zero bits until the length is a multiple of 8
+        data.resize(len.div_ceil(8) * 8, 0);
 
+        self
+    }
+
+    pub(crate) fn decimal_data(&mut self) -> &mut Encoder {
+        use super::qrcode_info::CAPACITIES;
+
+        let data = &mut self.data;
         let re_cws = (CAPACITIES[self.version][self.ec_level] - data.len() as u16) / 8;
 
         let mut decimals = vec![];
         for binary in data.chunks(8) { decimals.push(decimal(binary)); }
+        let fixed = decimals.len();
 
         let mut paddings = [236u8, 17].iter().cycle();
         for _ in 0..re_cws { decimals.push(*paddings.next().unwrap()); }
 
         *data = decimals;
+        self.halftone_mask = self.halftone_padding(fixed);
 
         self
     }
 
-    pub fn binary_data(&mut self) -> &mut Encoder {
-        use std::mem::swap;
+    pub(crate) fn binary_data(&mut self) -> &mut Encoder {
+        use core::mem::swap;
         use crate::encoder::qrcode_info::remainder_bits;
 
         {
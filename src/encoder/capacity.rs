@@ -0,0 +1,132 @@
+use core::ops::RangeInclusive;
+
+use super::{
+    check_version,
+    error::Error,
+    mode::Mode,
+    parse_ec_level,
+    qrcode_info::{CAPACITIES, INDICATORS},
+    segment::Segment,
+};
+
+// version: index from 1
+pub(crate) fn indicator_bits(mode: Mode, version: usize) -> usize {
+    // 1 - 9, 10 - 26, 27 - 40 versions have same indicator
+    INDICATORS[match version {
+        1..=9 => 0,
+        10..=26 => 1,
+        27..=40 => 2,
+        _ => panic!()
+    }][mode.to_usize()] as usize
+}
+
+// bits of `len` characters, without mode indicator and character count indicator
+pub(crate) fn data_bits(mode: Mode, len: usize) -> usize {
+    match mode {
+        Mode::Numeric => 10 * (len / 3) + [0, 4, 7][len % 3],
+        Mode::Alphanumeric => 11 * (len >> 1) + 6 * (len & 1),
+        Mode::Byte => 8 * len,
+        Mode::Kanji | Mode::Chinese => 13 * len,
+        Mode::Unknown => panic!()
+    }
+}
+
+// version: index from 1
+// ec_level: same as `Encoder`
+pub(crate) fn capacity_bits(version: usize, ec_level: usize) -> usize { CAPACITIES[version - 1][ec_level] as usize }
+
+// the smallest version(index from 1) in `versions` whose capacity can hold `bits(version)`
+pub(crate) fn fit<F: Fn(usize) -> usize>(ec_level: usize, versions: RangeInclusive<usize>, bits: F) -> Option<usize> {
+    versions.into_iter().find(|&version| bits(version) <= capacity_bits(version, ec_level))
+}
+
+// max characters of a single segment in this version(index from 1) and ec level, `Mode::Unknown` counts Byte mode
+// characters which any message can take
+pub fn max_chars(mode: Mode, version: usize, ec_level: &str) -> Result<usize, Error> {
+    let mode = if mode == Mode::Unknown { Mode::Byte } else { mode };
+    let version = check_version(version)? + 1;
+    let bits = capacity_bits(version, parse_ec_level(ec_level)?) - 4 - indicator_bits(mode, version);
+    let chars = match mode {
+        Mode::Numeric => 3 * (bits / 10) + match bits % 10 {
+            0..=3 => 0,
+            4..=6 => 1,
+            _ => 2
+        },
+        Mode::Alphanumeric => 2 * (bits / 11) + (bits % 11 >= 6) as usize,
+        Mode::Kanji | Mode::Chinese => bits / 13,
+        _ => bits / 8,
+    };
+
+    // character count indicator can't hold more
+    Ok(chars.min((1 << indicator_bits(mode, version)) - 1))
+}
+
+// the smallest version(index from 1) which can hold these segments, `None` if none can
+pub fn min_version(segments: &[Segment], ec_level: &str) -> Result<Option<usize>, Error> {
+    let ec_level = parse_ec_level(ec_level)?;
+
+    Ok(fit(ec_level, 1..=40, |version| segments.iter().map(|segment| segment.bits(version)).sum()))
+}
+
+// bits left after encoding these segments in this version(index from 1) and ec level,
+// negative if they don't fit
+pub fn remaining_bits(segments: &[Segment], version: usize, ec_level: &str) -> Result<isize, Error> {
+    let version = check_version(version)? + 1;
+
+    Ok(capacity_bits(version, parse_ec_level(ec_level)?) as isize
+        - segments.iter().map(|segment| segment.bits(version) as isize).sum::<isize>())
+}
+
+#[test]
+fn test_max_chars() {
+    assert_eq!(max_chars(Mode::Numeric, 1, "L"), Ok(41));
+    assert_eq!(max_chars(Mode::Alphanumeric, 1, "L"), Ok(25));
+    assert_eq!(max_chars(Mode::Byte, 1, "L"), Ok(17));
+    assert_eq!(max_chars(Mode::Kanji, 1, "L"), Ok(10));
+    assert_eq!(max_chars(Mode::Numeric, 1, "H"), Ok(17));
+    assert_eq!(max_chars(Mode::Alphanumeric, 10, "Q"), Ok(221));
+    assert_eq!(max_chars(Mode::Byte, 27, "M"), Ok(1125));
+    assert_eq!(max_chars(Mode::Numeric, 40, "L"), Ok(7089));
+    assert_eq!(max_chars(Mode::Alphanumeric, 40, "L"), Ok(4296));
+    assert_eq!(max_chars(Mode::Byte, 40, "L"), Ok(2953));
+    assert_eq!(max_chars(Mode::Kanji, 40, "L"), Ok(1817));
+    assert_eq!(max_chars(Mode::Byte, 40, "H"), Ok(1273));
+    assert_eq!(max_chars(Mode::Unknown, 1, "L"), Ok(17));
+
+    assert_eq!(max_chars(Mode::Byte, 41, "L"), Err(Error::InvalidVersion(41)));
+    assert_eq!(max_chars(Mode::Byte, 1, "X"), Err(Error::InvalidEcLevel("X".to_string())));
+}
+
+#[test]
+fn test_min_version() {
+    assert_eq!(min_version(&[Segment::auto(&"1".repeat(41))], "L"), Ok(Some(1)));
+    assert_eq!(min_version(&[Segment::auto(&"1".repeat(42))], "L"), Ok(Some(2)));
+    assert_eq!(min_version(&[Segment::auto("HELLO WORLD")], "Q"), Ok(Some(1)));
+    assert_eq!(min_version(&[Segment::auto(&"a".repeat(2954))], "L"), Ok(None));
+    assert_eq!(
+        min_version(&[Segment::new(Mode::Byte, "https://"), Segment::new(Mode::Alphanumeric, "EXAMPLE.COM")], "M"),
+        Ok(Some(2))
+    );
+    // a segment in `Mode::Unknown` is counted in the detected mode, as `Encoder::encode_segments` encodes it
+    assert_eq!(min_version(&[Segment::new(Mode::Unknown, &"1".repeat(41))], "L"), Ok(Some(1)));
+    assert_eq!(min_version(&[], "X"), Err(Error::InvalidEcLevel("X".to_string())));
+
+    for version in 1..=40 {
+        for &ec_level in ["L", "M", "Q", "H"].iter() {
+            let segments = [Segment::new(Mode::Byte, &"a".repeat(max_chars(Mode::Byte, version, ec_level).unwrap()))];
+
+            assert!((0..8).contains(&remaining_bits(&segments, version, ec_level).unwrap()));
+            assert_eq!(min_version(&segments, ec_level), Ok(Some(version)));
+        }
+    }
+}
+
+#[test]
+fn test_remaining_bits() {
+    // 4 + 10 + 10 * 2 + 7 = 41 bits
+    assert_eq!(remaining_bits(&[Segment::auto("01234567")], 1, "M"), Ok(128 - 41));
+    // 4 + 10 + 10 * 14 = 154 bits
+    assert_eq!(remaining_bits(&[Segment::auto(&"1".repeat(42))], 1, "L"), Ok(-2));
+    assert_eq!(remaining_bits(&[Segment::new(Mode::Unknown, "01234567")], 1, "M"), Ok(128 - 41));
+    assert_eq!(remaining_bits(&[], 41, "M"), Err(Error::InvalidVersion(41)));
+}
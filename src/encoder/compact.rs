@@ -0,0 +1,149 @@
+use core::convert::TryFrom;
+
+use crate::prelude::*;
+
+use super::matrix::Matrix;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// standard alphabet with padding
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut base64 = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for i in 0..4 {
+            base64.push(if i <= chunk.len() { BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char } else { '=' });
+        }
+    }
+
+    base64
+}
+
+fn base64_decode(base64: &str) -> Option<Vec<u8>> {
+    let base64 = base64.as_bytes();
+    if !base64.len().is_multiple_of(4) { return None; }
+
+    let mut bytes = Vec::with_capacity(base64.len() / 4 * 3);
+    for (i, chunk) in base64.chunks(4).enumerate() {
+        // padding only at the end
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || padding > 0 && i + 1 != base64.len() / 4 { return None; }
+
+        let mut n = 0;
+        for &c in chunk[..4 - padding].iter() { n = n << 6 | BASE64.iter().position(|&b| b == c)? as u32; }
+        n <<= 6 * padding;
+
+        bytes.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..3 - padding]);
+    }
+
+    Some(bytes)
+}
+
+// modules row by row from the most significant bit, dark modules are 1, the last byte is padded with 0
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct CompactMatrix {
+    width: usize,
+    modules: String,
+}
+
+impl From<Matrix> for CompactMatrix {
+    fn from(matrix: Matrix) -> CompactMatrix {
+        let width = matrix.width();
+        let mut bytes = vec![0; (width * width).div_ceil(8)];
+        for (i, module) in matrix.0.iter().flatten().enumerate() { bytes[i / 8] |= (module & 1) << (7 - i % 8); }
+
+        CompactMatrix { width, modules: base64_encode(&bytes) }
+    }
+}
+
+// function modules are told apart from data modules by the function patterns of the version, read through the
+// orientation `decode` finds so that light on dark and mirror image symbols come back as they were
+impl TryFrom<CompactMatrix> for Matrix {
+    type Error = &'static str;
+
+    fn try_from(compact: CompactMatrix) -> Result<Matrix, &'static str> {
+        let width = compact.width;
+        if !(21..=177).contains(&width) || !(width - 17).is_multiple_of(4) { return Err("width is not 21 ~ 177 modules of a version"); }
+
+        let bytes = base64_decode(&compact.modules).ok_or("modules are not base64")?;
+        if bytes.len() != (width * width).div_ceil(8) { return Err("modules don't match the width"); }
+
+        let bits = Matrix(bytes.iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1))
+            .take(width * width)
+            .collect::<Vec<u8>>()
+            .chunks(width)
+            .map(|row| row.to_vec())
+            .collect());
+        let (upright, _, inverted, _) = crate::decoder::orient(&bits).map_err(|_| "format information can't be read")?;
+
+        let version = (width - 17) / 4;
+        let mut matrix = Matrix::blank(version);
+        matrix.with_function_patterns(version);
+        for (module, &bit) in matrix.0.iter_mut().flatten().zip(upright.0.iter().flatten()) {
+            // function patterns, reserved areas and version information -> 2 | 3, data modules -> 0 | 1
+            *module = if *module == 5 { bit } else { 2 + bit };
+        }
+        if inverted { matrix = matrix.inverted(); }
+
+        // `orient` doesn't tell a transposed symbol from a left and right swapped one, the one with the same modules
+        // is taken
+        let same = |matrix: &Matrix| {
+            matrix.0.iter().flatten().zip(bits.0.iter().flatten()).all(|(&module, &bit)| module & 1 == bit)
+        };
+        if same(&matrix) { return Ok(matrix); }
+
+        vec![matrix.transposed(), matrix.mirrored()].into_iter().find(same).ok_or("format information can't be read")
+    }
+}
+
+#[test]
+fn test_base64() {
+    for &(bytes, base64) in [
+        (&b""[..], ""),
+        (&b"f"[..], "Zg=="),
+        (&b"fo"[..], "Zm8="),
+        (&b"foo"[..], "Zm9v"),
+        (&b"foobar"[..], "Zm9vYmFy"),
+        (&[0xff, 0xfe, 0x00][..], "//4A"),
+    ].iter() {
+        assert_eq!(base64_encode(bytes), base64);
+        assert_eq!(base64_decode(base64).as_deref(), Some(bytes));
+    }
+
+    for base64 in ["Zg=", "Zg==Zg==", "Z===", "Zm9-"].iter() { assert_eq!(base64_decode(base64), None); }
+}
+
+#[test]
+fn test_compact_matrix() {
+    use super::Encoder;
+
+    for &(message, version) in [("01234567", 1), ("HELLO WORLD", 7), ("https://github.com/AurevoirXavier/qrustcode", 40)].iter() {
+        let matrix = Encoder::new().version(version).encode(message).unwrap().as_matrix();
+        let compact = CompactMatrix::from(matrix.clone());
+
+        assert_eq!(compact.width, version * 4 + 17);
+        assert_eq!(compact.modules.len(), (compact.width.pow(2).div_ceil(8)).div_ceil(3) * 4);
+        assert_eq!(Matrix::try_from(compact), Ok(matrix.clone()));
+
+        // light on dark and mirror images come back as they were
+        for matrix in [
+            matrix.inverted(),
+            matrix.transposed(),
+            matrix.mirrored(),
+            matrix.inverted().transposed(),
+            matrix.inverted().mirrored(),
+        ].iter() {
+            assert_eq!(Matrix::try_from(CompactMatrix::from(matrix.clone())).as_ref(), Ok(matrix));
+        }
+    }
+
+    assert!(Matrix::try_from(CompactMatrix { width: 22, modules: "AAAA".to_string() }).is_err());
+    assert!(Matrix::try_from(CompactMatrix { width: 21, modules: "AAAA".to_string() }).is_err());
+
+    // the right size but no format information
+    let modules = base64_encode(&[0; 56]);
+    assert_eq!(Matrix::try_from(CompactMatrix { width: 21, modules }), Err("format information can't be read"));
+}
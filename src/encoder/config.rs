@@ -0,0 +1,126 @@
+use crate::prelude::*;
+
+use super::{
+    Encoder,
+    Error,
+    matrix::Matrix,
+    mode::Mode,
+    segment::Segment,
+};
+
+// settings of a symbol and its message, `Encoder::config` fills in the ones it resolved to encode the same symbol again
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Config {
+    // mode of the segments in `Mode::Unknown`, detected if it is `Mode::Unknown` too
+    pub mode: Mode,
+    // index from 1, the smallest one which fits if `None`
+    pub version: Option<usize>,
+    // 'L', 'M', 'Q' or 'H'
+    pub ec_level: char,
+    // 0 ~ 7, the one with the lowest penalty if `None`
+    pub mask: Option<usize>,
+    pub segments: Vec<Segment>,
+}
+
+impl Config {
+    // an `Encoder` with these settings
+    pub fn encoder(&self) -> Encoder {
+        let mut encoder = Encoder::new().ec_level(self.ec_level.encode_utf8(&mut [0; 4]));
+        encoder.mode = self.mode;
+        encoder.requested.0 = self.mode;
+        if let Some(version) = self.version { encoder = encoder.version(version); }
+        if let Some(mask) = self.mask { encoder = encoder.mask(mask); }
+
+        encoder
+    }
+
+    pub fn encode(&self) -> Result<Matrix, Error> { Ok(self.encoder().encode_segments(&self.segments)?.as_matrix()) }
+}
+
+impl Encoder {
+    // encode `message` and record what this encoder resolved for it: the segments after `split`, and the version,
+    // ec level and mask after the version range, `boost_ec`, `prefer_ec` and `logo`, so `Config::encode` gives the
+    // same symbol, except for the pad codewords of a `halftone` encoder
+    pub fn config(&mut self, message: &str) -> Result<Config, Error> {
+        let split = self.detect_split(message)?;
+        let mask = self.encode(message)?.as_matrix().mask();
+        let segments = match split {
+            Some(split) => split.into_iter().map(|(mode, range)| Segment::new(mode, &message[range])).collect(),
+            None => vec![Segment::new(self.mode, message)],
+        };
+
+        Ok(Config {
+            mode: self.requested.0,
+            version: Some(self.version + 1),
+            ec_level: ['L', 'M', 'Q', 'H'][self.ec_level],
+            mask,
+            segments,
+        })
+    }
+}
+
+#[test]
+fn test_config() {
+    let mut encoder = Encoder::new().ec_level("Q").version(3).mask(5);
+    let config = encoder.config("HELLO WORLD").unwrap();
+    assert_eq!(
+        config,
+        Config {
+            mode: Mode::Unknown,
+            version: Some(3),
+            ec_level: 'Q',
+            mask: Some(5),
+            segments: vec![Segment::new(Mode::Alphanumeric, "HELLO WORLD")],
+        }
+    );
+    assert_eq!(config.encode(), Ok(encoder.as_matrix()));
+
+    let config = Encoder::new().mode("Byte").config("01234567").unwrap();
+    assert_eq!((config.mode, config.version, config.ec_level), (Mode::Byte, Some(1), 'L'));
+    assert_eq!(crate::decode(&config.encode().unwrap()).unwrap().segments, [Segment::new(Mode::Byte, "01234567")]);
+
+    assert_eq!(Config { ec_level: 'X', ..config }.encode(), Err(Error::InvalidEcLevel("X".to_string())));
+    assert!(Encoder::new().version(1).config(&"1".repeat(42)).is_err());
+}
+
+#[test]
+fn test_config_resolved() {
+    // the raised ec level and the split are recorded, not the settings which led to them
+    for encoder in [
+        Encoder::new().boost_ec(true),
+        Encoder::new().prefer_ec(true).max_version(2),
+        Encoder::new().split(true).ec_level("M"),
+        Encoder::new().min_version(4).mask(6),
+    ].iter() {
+        let mut encoder = encoder.clone();
+        let config = encoder.config("https://EXAMPLE.COM/0123456789").unwrap();
+        assert_eq!(config.encode(), Ok(encoder.as_matrix()));
+    }
+
+    let config = Encoder::new().boost_ec(true).config("HELLO WORLD").unwrap();
+    assert_eq!((config.version, config.ec_level), (Some(1), 'Q'));
+
+    let config = Encoder::new().split(true).config("https://EXAMPLE.COM").unwrap();
+    assert_eq!(config.segments, [Segment::new(Mode::Byte, "https"), Segment::new(Mode::Alphanumeric, "://EXAMPLE.COM")]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let config = Encoder::new().ec_level("M").mask(2).config("01234567").unwrap();
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(
+        json,
+        r#"{"mode":"Unknown","version":1,"ec_level":"M","mask":2,"segments":[{"mode":"Numeric","data":"01234567"}]}"#
+    );
+    assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+
+    // an archived symbol renders the same without the encoder
+    let matrix = config.encode().unwrap();
+    let json = serde_json::to_string(&matrix).unwrap();
+    assert!(json.starts_with(r#"{"width":21,"modules":"/"#));
+    assert_eq!(serde_json::from_str::<Matrix>(&json).unwrap(), matrix);
+
+    assert!(serde_json::from_str::<Matrix>(r#"{"width":21,"modules":"AAAA"}"#).is_err());
+}
@@ -0,0 +1,169 @@
+use crate::prelude::*;
+
+use crate::{
+    decoder::inflate::{DISTANCE_BASES, DISTANCE_EXTRA_BITS, LENGTH_BASES, LENGTH_EXTRA_BITS},
+    renderer::png::{adler32, zlib_stored},
+};
+
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// earlier positions with the same 3 bytes tried for every match, payloads are a few KB at most
+const MAX_CHAIN: usize = 128;
+
+// deflate packs bits from the least significant bit of every byte
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn push(&mut self, count: u32, value: u32) {
+        self.bits |= value << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes go from their most significant bit
+    fn push_code(&mut self, count: u32, code: u32) { self.push(count, code.reverse_bits() >> (32 - count)); }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 { self.bytes.push(self.bits as u8); }
+
+        self.bytes
+    }
+}
+
+// fixed literal/length codes of RFC 1951 3.2.6
+fn push_literal(writer: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => writer.push_code(8, 0x30 + symbol),
+        144..=255 => writer.push_code(9, 0x190 + symbol - 144),
+        256..=279 => writer.push_code(7, symbol - 256),
+        _ => writer.push_code(8, 0xc0 + symbol - 280),
+    }
+}
+
+// the last base which isn't larger than `value`, with its index
+fn base(bases: &[u16], value: usize) -> (usize, usize) {
+    let i = bases.iter().rposition(|&base| base as usize <= value).unwrap();
+
+    (i, value - bases[i] as usize)
+}
+
+fn push_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let (symbol, extra) = base(&LENGTH_BASES, length);
+    push_literal(writer, 257 + symbol as u32);
+    writer.push(LENGTH_EXTRA_BITS[symbol] as u32, extra as u32);
+
+    let (symbol, extra) = base(&DISTANCE_BASES, distance);
+    writer.push_code(5, symbol as u32);
+    writer.push(DISTANCE_EXTRA_BITS[symbol] as u32, extra as u32);
+}
+
+fn hash(bytes: &[u8]) -> usize { ((bytes[0] as usize) << 10 ^ (bytes[1] as usize) << 5 ^ bytes[2] as usize) & 0x7fff }
+
+// positions with the same first 3 bytes, from the last one
+struct Chains {
+    head: Vec<usize>,
+    previous: Vec<usize>,
+}
+
+impl Chains {
+    fn insert(&mut self, bytes: &[u8], i: usize) {
+        if i + MIN_MATCH > bytes.len() { return; }
+
+        let h = hash(&bytes[i..]);
+        self.previous[i] = self.head[h];
+        self.head[h] = i;
+    }
+}
+
+// a single block of fixed Huffman codes, greedy LZ77 matches over hash chains
+fn deflate_fixed(bytes: &[u8]) -> Vec<u8> {
+    let mut chains = Chains { head: vec![usize::MAX; 1 << 15], previous: vec![usize::MAX; bytes.len()] };
+
+    let mut writer = BitWriter { bytes: vec![], bits: 0, count: 0 };
+    // BFINAL, BTYPE = 01
+    writer.push(3, 0b011);
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let (mut length, mut distance) = (0, 0);
+        if i + MIN_MATCH <= bytes.len() {
+            let max = MAX_MATCH.min(bytes.len() - i);
+            let mut candidate = chains.head[hash(&bytes[i..])];
+
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || i - candidate > WINDOW { break; }
+
+                let len = bytes[candidate..].iter().zip(bytes[i..i + max].iter()).take_while(|(a, b)| a == b).count();
+                if len > length {
+                    (length, distance) = (len, i - candidate);
+                    if len == max { break; }
+                }
+                candidate = chains.previous[candidate];
+            }
+        }
+
+        if length >= MIN_MATCH {
+            push_match(&mut writer, length, distance);
+            for j in i..i + length { chains.insert(bytes, j); }
+            i += length;
+        } else {
+            push_literal(&mut writer, bytes[i] as u32);
+            chains.insert(bytes, i);
+            i += 1;
+        }
+    }
+    push_literal(&mut writer, 256);
+
+    writer.finish()
+}
+
+// zlib stream, RFC 1950, of fixed Huffman codes or stored blocks, whichever is shorter
+pub(crate) fn zlib_compress(bytes: &[u8]) -> Vec<u8> {
+    // 32K window, fastest compression level, 0x7801 % 31 == 0
+    let mut zlib = vec![0x78, 0x01];
+    zlib.extend_from_slice(&deflate_fixed(bytes));
+    zlib.extend_from_slice(&adler32(bytes).to_be_bytes());
+
+    let stored = zlib_stored(bytes);
+    if stored.len() < zlib.len() { stored } else { zlib }
+}
+
+#[test]
+fn test_zlib_compress() {
+    use crate::decoder::inflate::zlib_decompress;
+
+    let text = b"hello hello hello hello, the quick brown fox jumps over the lazy dog, the lazy dog".repeat(20);
+    // xorshift
+    let random = (0..5000)
+        .scan(0x2545_f491u32, |x, _| {
+            *x ^= *x << 13;
+            *x ^= *x >> 17;
+            *x ^= *x << 5;
+
+            Some(*x as u8)
+        })
+        .collect::<Vec<u8>>();
+    // matches of every length and of distances up to the window
+    let long = (0..100000u32).map(|i| (i % 7919 % 251) as u8).chain(core::iter::repeat_n(9, 600)).collect::<Vec<u8>>();
+
+    for bytes in [&b""[..], b"a", b"ab", b"aaaaaaaaaaaaaaaaaaaaaaaaaaa", &text, &random, &long] {
+        let zlib = zlib_compress(bytes);
+
+        assert_eq!(zlib_decompress(&zlib).unwrap(), bytes);
+        assert_eq!(zlib[zlib.len() - 4..], adler32(bytes).to_be_bytes());
+    }
+
+    assert!(zlib_compress(&text).len() < 150);
+    // incompressible bytes are stored
+    assert_eq!(zlib_compress(&random), zlib_stored(&random));
+}
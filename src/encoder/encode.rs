@@ -1,99 +1,359 @@
+use core::ops::Range;
+
+use crate::prelude::*;
+
 use super::{
     Encoder,
-    bits::push_binary,
+    Error,
+    bits::Bits,
+    mode::Mode,
+    resolve::split::Split,
+    segment::Segment,
 };
 
+fn numeric_encode<B: Bits>(bits: &mut B, bits_count: usize, message: &str) {
+    let len = message.len();
+    let edge = len / 3 * 3;
+
+    bits.push_bits(4, 0b0001);
+    bits.push_bits(bits_count, len as u16);
+
+    for i in (0..edge).step_by(3) { bits.push_bits(10, message[i..i + 3].parse().unwrap()); }
+    match len - edge {
+        rest @ 1..=2 => bits.push_bits(1 + 3 * rest, message[edge..len].parse().unwrap()),
+        0 => (),
+        _ => panic!()
+    }
+}
+
+fn alphanumeric_encode<B: Bits>(bits: &mut B, bits_count: usize, message: &str) {
+    use super::qrcode_info::alphanumeric_table;
+
+    let message = message.as_bytes();
+    let len = message.len();
+
+    bits.push_bits(4, 0b0010);
+    bits.push_bits(bits_count, len as u16);
+
+    for i in (0..len >> 1 << 1).step_by(2) {
+        bits.push_bits(
+            11,
+            45 *
+                alphanumeric_table(message[i]) as u16
+                +
+                alphanumeric_table(message[i + 1]) as u16,
+        );
+    }
+    if len & 1 == 1 { bits.push_bits(6, alphanumeric_table(message[len - 1]) as u16); }
+}
+
+fn byte_encode<B: Bits>(bits: &mut B, bits_count: usize, message: &[u8]) {
+    bits.push_bits(4, 0b0100);
+    bits.push_bits(bits_count, message.len() as u16);
+
+    for byte in message { bits.push_bits(8, *byte as u16); }
+}
+
+fn kanji_encode<B: Bits>(bits: &mut B, bits_count: usize, message: &str) {
+    use super::mode::shift_jis;
+
+    bits.push_bits(4, 0b1000);
+    bits.push_bits(bits_count, message.chars().count() as u16);
+
+    for c in message.chars() {
+        // "茗" -- Shift JIS value --> 0xe4aa
+        // 0xe4aa - 0xc140 = 0x236a => {
+        //     0x236a >> 8 = 0x23
+        //     0x236a & 0xff = 0x6a
+        // } => (0x23 * 0xc0 = 0x1a40) + 0x6a = 0x1aaa = 0b1101010101010
+        let shift_jis_value = shift_jis(c).unwrap();
+        let decimal = if shift_jis_value < 0xe040 { shift_jis_value - 0x8140 } else { shift_jis_value - 0xc140 };
+
+        bits.push_bits(13, (decimal >> 8) * 0xc0 + (decimal & 0xff));
+    }
+}
+
+//fn chinese_encode<B: Bits>(bits: &mut B, bits_count: usize, message: &str) {} // TODO
+
+// mode indicator + character count indicator + encoded message
+pub(crate) fn encode_segment<B: Bits>(bits: &mut B, mode: Mode, bits_count: usize, message: &str) {
+    use super::mode::Mode::*;
+
+    match mode {
+        Numeric => numeric_encode(bits, bits_count, message),
+        Alphanumeric => alphanumeric_encode(bits, bits_count, message),
+        Byte => byte_encode(bits, bits_count, message.as_bytes()),
+        Kanji => kanji_encode(bits, bits_count, message),
+//        Chinese => chinese_encode(bits, bits_count, message), // TODO
+        _ => panic!()
+    }
+}
+
+// segments of `split` one after another
+//
+// version: index from 1
+pub(crate) fn encode_split<B: Bits>(bits: &mut B, message: &str, split: &[(Mode, Range<usize>)], version: usize) {
+    use super::capacity::indicator_bits;
+
+    for (mode, range) in split.iter() { encode_segment(bits, *mode, indicator_bits(*mode, version), &message[range.clone()]); }
+}
+
 impl Encoder {
-    fn numeric_encode(&mut self, bits_count: usize, message: &str) -> &mut Encoder {
-        let len = message.len();
-        let edge = len / 3 * 3;
-
-        self.data = vec![0, 0, 0, 1];
-        push_binary(&mut self.data, bits_count, len as u16);
-
-        for i in (0..edge).step_by(3) { push_binary(&mut self.data, 10, message[i..i + 3].parse().unwrap()); }
-        match len - edge {
-            bits @ 1...2 => push_binary(&mut self.data, 1 + 3 * bits, message[edge..len].parse().unwrap()),
-            0 => (),
-            _ => panic!()
+    // detect mode and version, return the bits count of character count indicator
+    pub(crate) fn detect(&mut self, message: &str) -> Result<usize, Error> {
+        use super::mode::Mode::*;
+
+        // start from the settings, not the result of the last detection
+        let (mode, version, ec_level) = self.requested;
+        self.mode = mode;
+        self.version = version;
+        self.ec_level = ec_level;
+
+        self.mode_detect(message);
+
+        // Byte mode takes UTF-8 beyond ISO 8859-1, Chinese mode is not done yet
+        if mode != Unknown && mode != Byte {
+            if let Some(c) = message.chars().find(|&c| mode == Chinese || mode.not_support(c)) {
+                return Err(Error::UnsupportedCharacter { mode, c });
+            }
         }
 
+        self.version_detect(match self.mode {
+            Kanji | Chinese => message.chars().count(),
+            _ => message.len()
+        })
+    }
+
+    pub(crate) fn segment_encode(&mut self, bits_count: usize, message: &str) -> &mut Encoder {
+        self.data.clear();
+        encode_segment(&mut self.data, self.mode, bits_count, message);
+
         self
     }
 
-    fn alphanumeric_encode(&mut self, bits_count: usize, message: &str) -> &mut Encoder {
-        use super::qrcode_info::alphanumeric_table;
+    // the optimal split of the message if `split` is set and the mode is detected, versions 1 ~ 9, 10 ~ 26 and
+    // 27 ~ 40 are split on their own, then version and ec level are detected as `detect` does
+    pub(crate) fn detect_split(&mut self, message: &str) -> Result<Option<Split>, Error> {
+        use super::resolve::split::{split, split_bits};
+
+        let (mode, version, ec_level) = self.requested;
+        if !self.split || mode != Mode::Unknown { return Ok(None); }
+        self.version = version;
+        self.ec_level = ec_level;
 
-        let message = message.as_bytes();
-        let len = message.len();
+        let group = |version| match version {
+            1..=9 => 0,
+            10..=26 => 1,
+            _ => 2,
+        };
+        let mut splits = vec![split(message, 1), split(message, 10), split(message, 27)];
+        self.fit_version(|version| split_bits(message, &splits[group(version)], version))?;
 
-        self.data = vec![0, 0, 1, 0];
-        push_binary(&mut self.data, bits_count, len as u16);
+        let split = splits.swap_remove(group(self.version + 1));
+        self.mode = split[0].0;
+
+        Ok(Some(split))
+    }
 
-        for i in (0..len >> 1 << 1).step_by(2) {
-            push_binary(
-                &mut self.data,
-                11,
-                45 *
-                    alphanumeric_table(message[i]) as u16
-                    +
-                    alphanumeric_table(message[i + 1]) as u16,
-            );
+    pub fn encode(&mut self, message: &str) -> Result<&mut Encoder, Error> {
+        match self.detect_split(message)? {
+            Some(split) => {
+                self.data.clear();
+                encode_split(&mut self.data, message, &split, self.version + 1);
+            }
+            None => {
+                let bits_count = self.detect(message)?;
+                self.segment_encode(bits_count, message);
+            }
         }
-        if len & 1 == 1 { push_binary(&mut self.data, 6, alphanumeric_table(message[len]) as u16); }
 
-        self
+        Ok(self.terminate()
+            .decimal_data()
+            .interleave_with_ec()
+            .binary_data())
     }
 
-    fn byte_encode(&mut self, bits_count: usize, message: &str) -> &mut Encoder {
-        self.data = vec![0, 1, 0, 0];
-        push_binary(&mut self.data, bits_count, message.len() as u16);
+    // raw bytes which might not be UTF-8, always in Byte mode whatever the requested mode is
+    pub fn encode_bytes(&mut self, message: &[u8]) -> Result<&mut Encoder, Error> {
+        let (_, version, ec_level) = self.requested;
+        self.mode = Mode::Byte;
+        self.version = version;
+        self.ec_level = ec_level;
 
-        for byte in message.as_bytes() { push_binary(&mut self.data, 8, *byte as u16); }
+        let bits_count = self.version_detect(message.len())?;
 
-        self
+        self.data.clear();
+        byte_encode(&mut self.data, bits_count, message);
+
+        Ok(self.terminate()
+            .decimal_data()
+            .interleave_with_ec()
+            .binary_data())
     }
 
-    fn kanji_encode(&mut self, bits_count: usize, message: &str) -> &mut Encoder {
-        use encoding_rs::SHIFT_JIS;
+    // zlib, then Base45 in Alphanumeric mode whatever the requested mode is, as health certificates are encoded,
+    // `Decoded::decompress` is the inverse
+    pub fn encode_compressed(&mut self, bytes: &[u8]) -> Result<&mut Encoder, Error> {
+        use super::deflate::zlib_compress;
 
-        self.data = vec![1, 0, 0, 0];
-        push_binary(&mut self.data, bits_count, message.len() as u16);
+        let (_, version, ec_level) = self.requested;
+        self.mode = Mode::Alphanumeric;
+        self.version = version;
+        self.ec_level = ec_level;
 
-        let (message, _, _) = SHIFT_JIS.encode(message);
-        for kanji in message.chunks(2) {
-            // "茗" -- Shift JIS value --> 0xe4aa
-            // 0xe4aa - 0xc140 = 0x236a => {
-            //     0x236a >> 8 = 0x23
-            //     0x236a & 0xff = 0x6a
-            // } => (0x23 * 0xc0 = 0x1a40) + 0x6a = 0x1aaa = 0b1101010101010
-            let shift_jis_value = kanji[0] as u16 * 256 + kanji[1] as u16;
-            let decimal = if shift_jis_value < 0xe040 { shift_jis_value - 0x8140 } else { shift_jis_value - 0xc140 };
+        let text = crate::base45::encode(&zlib_compress(bytes));
+        let bits_count = self.version_detect(text.len())?;
 
-            push_binary(&mut self.data, 13, (decimal >> 8) * 0xc0 + (decimal & 0xff));
-        }
+        self.data.clear();
+        alphanumeric_encode(&mut self.data, bits_count, &text);
 
-        self
+        Ok(self.terminate()
+            .decimal_data()
+            .interleave_with_ec()
+            .binary_data())
     }
 
-//    fn chinese_encode(&mut self, bits_count: usize, message: &str) -> &mut Encoder { self } // TODO
+    // segments in their own modes one after another, a segment in `Mode::Unknown` takes the requested mode or the
+    // detected one
+    pub fn encode_segments(&mut self, segments: &[Segment]) -> Result<&mut Encoder, Error> {
+        use super::{capacity::indicator_bits, resolve::auto_detect::detect_mode};
 
-    pub fn encode(&mut self, message: &str) -> &mut Encoder {
-        use super::mode::Mode::*;
+        let (mode, version, ec_level) = self.requested;
+        self.version = version;
+        self.ec_level = ec_level;
 
-        self.mode_detect(message);
-        let bits_count = self.version_detect(message.len() as u16);
-
-        match self.mode {
-            Numeric => self.numeric_encode(bits_count, message),
-            Alphanumeric => self.alphanumeric_encode(bits_count, message),
-            Byte => self.byte_encode(bits_count, message),
-            Kanji => self.kanji_encode(bits_count, message),
-//            Chinese => self.chinese_encode(bits_count, message), // TODO
-            _ => panic!()
+        let segments = segments.iter()
+            .map(|segment| {
+                let mode = match (segment.mode, mode) {
+                    (Mode::Unknown, Mode::Unknown) => detect_mode(&segment.data),
+                    (Mode::Unknown, mode) => mode,
+                    (mode, _) => mode,
+                };
+
+                // Chinese mode is not done yet
+                if let Some(c) = segment.data.chars().find(|&c| mode == Mode::Chinese || mode != Mode::Byte && mode.not_support(c)) {
+                    return Err(Error::UnsupportedCharacter { mode, c });
+                }
+
+                Ok(Segment { mode, data: segment.data.clone() })
+            })
+            .collect::<Result<Vec<Segment>, Error>>()?;
+
+        self.fit_version(|version| segments.iter().map(|segment| segment.bits(version)).sum())?;
+        // the mode of the first segment, as if it were the only one
+        self.mode = segments.first().map_or(Mode::Byte, |segment| segment.mode);
+
+        self.data.clear();
+        for segment in segments.iter() {
+            encode_segment(&mut self.data, segment.mode, indicator_bits(segment.mode, self.version + 1), &segment.data);
         }
+
+        Ok(self.terminate()
             .decimal_data()
             .interleave_with_ec()
-            .binary_data()
+            .binary_data())
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_encode_segments() {
+    // a single segment is the same as `encode`
+    let mut encoder = Encoder::new().ec_level("Q");
+    let matrix = encoder.encode("HELLO WORLD").unwrap().as_matrix();
+    assert_eq!(encoder.encode_segments(&[Segment::new(Mode::Unknown, "HELLO WORLD")]).unwrap().as_matrix(), matrix);
+
+    // 4 + 8 + 8 * 8 + 4 + 9 + 61 = 150 bits fit in 1-L's 152 bits, the whole message in Byte mode needs 164 bits
+    let segments = [Segment::new(Mode::Byte, "https://"), Segment::new(Mode::Alphanumeric, "EXAMPLE.COM")];
+    let mut encoder = Encoder::new();
+    encoder.encode_segments(&segments).unwrap();
+    assert_eq!(encoder.version, 0);
+    assert_eq!(Encoder::new().encode("https://EXAMPLE.COM").unwrap().version, 1);
+
+    let decoded = crate::decode(&encoder.as_matrix()).unwrap();
+    assert_eq!(decoded.segments, segments);
+    assert_eq!(decoded.data, b"https://EXAMPLE.COM");
+
+    assert_eq!(
+        Encoder::new().encode_segments(&[Segment::new(Mode::Numeric, "1"), Segment::new(Mode::Alphanumeric, "a")]).err(),
+        Some(Error::UnsupportedCharacter { mode: Mode::Alphanumeric, c: 'a' })
+    );
+}
+
+#[test]
+fn test_encode_split() {
+    // 4 + 8 + 8 * 5 + 4 + 9 + 77 = 142 bits fit in 1-L's 152 bits
+    let mut encoder = Encoder::new().split(true);
+    let matrix = encoder.encode("https://EXAMPLE.COM").unwrap().as_matrix();
+    assert_eq!((encoder.mode, encoder.version), (Mode::Byte, 0));
+    assert_eq!(
+        crate::decode(&matrix).unwrap().segments,
+        [Segment::new(Mode::Byte, "https"), Segment::new(Mode::Alphanumeric, "://EXAMPLE.COM")]
+    );
+
+    // no split without a gain, or with a requested mode
+    let mut encoder = Encoder::new().ec_level("Q");
+    let matrix = encoder.encode("HELLO WORLD").unwrap().as_matrix();
+    assert_eq!(Encoder::new().ec_level("Q").split(true).encode("HELLO WORLD").unwrap().as_matrix(), matrix);
+    assert_eq!(Encoder::new().mode("Byte").split(true).encode("https://EXAMPLE.COM").unwrap().version, 1);
+
+    assert_eq!(
+        Encoder::new().version(1).split(true).encode(&"a".repeat(18)).err(),
+        Some(Error::DataTooLong { bits: 156, capacity: 152 })
+    );
+}
+
+#[test]
+fn test_encode_bytes() {
+    let mut encoder = Encoder::new().ec_level("M");
+    let matrix = encoder.encode("hello").unwrap().as_matrix();
+    assert_eq!(encoder.encode_bytes(b"hello").unwrap().as_matrix(), matrix);
+
+    let mut encoder = Encoder::new().mode("Numeric");
+    encoder.encode_bytes(&[0xff, 0xfe, 0x00]).unwrap();
+    assert_eq!((encoder.mode, encoder.version), (Mode::Byte, 0));
+
+    assert_eq!(
+        Encoder::new().mode("Numeric").encode("12a4").err(),
+        Some(Error::UnsupportedCharacter { mode: Mode::Numeric, c: 'a' })
+    );
+    // Chinese mode is not done yet, an error instead of a panic
+    assert_eq!(
+        Encoder::new().mode("Chinese").encode("中").err(),
+        Some(Error::UnsupportedCharacter { mode: Mode::Chinese, c: '中' })
+    );
+}
+
+#[test]
+fn test_encode_compressed() {
+    // a ticket of repeated JSON fields, compressed and in Alphanumeric mode it beats the raw bytes in Byte mode
+    let ticket = br#"{"ticket":"A-1024","seat":"12C","holder":"JANE DOE","valid":"2025-06-01"},"#.repeat(4);
+    let mut encoder = Encoder::new().ec_level("M").mode("Byte");
+    let matrix = encoder.encode_compressed(&ticket).unwrap().as_matrix();
+    assert_eq!(encoder.mode, Mode::Alphanumeric);
+    assert!(encoder.version < Encoder::new().ec_level("M").encode_bytes(&ticket).unwrap().version);
+
+    let decoded = crate::decode(&matrix).unwrap();
+    assert_eq!(decoded.segments[0].mode, Mode::Alphanumeric);
+    assert_eq!(decoded.decompress().unwrap(), ticket);
+
+    // 17 distinct bytes in 20 bytes of fixed codes, 2 + 20 + 4 bytes of zlib, 39 characters -> 4 + 9 + 215 bits
+    let bytes = (0..17u8).map(|i| i * 15).collect::<Vec<u8>>();
+    assert_eq!(Encoder::new().version(1).encode_compressed(&bytes).err(), Some(Error::DataTooLong { bits: 228, capacity: 152 }));
+}
+
+#[test]
+fn test_encode_reuse() {
+    let mut encoder = Encoder::new().ec_level("M");
+
+    // the version and mode detected for the first message don't stick
+    encoder.encode("01234567").unwrap();
+    assert_eq!((encoder.mode, encoder.version), (Mode::Numeric, 0));
+    encoder.encode(&"a".repeat(100)).unwrap();
+    assert_eq!((encoder.mode, encoder.version), (Mode::Byte, 5));
+
+    let mut encoder = Encoder::new().boost_ec(true);
+    encoder.encode("01234567").unwrap();
+    assert_eq!(encoder.ec_level, 3);
+    encoder.encode(&"1".repeat(25)).unwrap();
+    assert_eq!(encoder.ec_level, 2);
+}
@@ -0,0 +1,58 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::mode::Mode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // version is not in 1 ~ 40
+    InvalidVersion(usize),
+    // min version is greater than max version
+    InvalidVersionRange(usize, usize),
+    // symbol size is smaller than version 1's 21 x 21 modules
+    InvalidSize(usize),
+    // mask reference is not in 0 ~ 7
+    InvalidMask(usize),
+    // ec level is not "L", "M", "Q" or "H"
+    InvalidEcLevel(String),
+    // mode is not "Numeric", "Alphanumeric", "Byte", "Kanji" or "Chinese"
+    InvalidMode(String),
+    // bits of the data and the capacity of the largest allowed version in the requested ec level
+    DataTooLong { bits: usize, capacity: usize },
+    // the first character which the requested mode can't encode
+    UnsupportedCharacter { mode: Mode, c: char },
+    // codewords of the block which the logo covers most, and the codewords it can correct
+    LogoTooLarge { codewords: usize, budget: usize },
+    // the halftone image has no pixels
+    EmptyImage,
+    // widths of the first layer and of one which isn't the same version
+    LayersMismatch(usize, usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidVersion(version) => write!(f, "version {} is not in 1 ~ 40", version),
+            Error::InvalidVersionRange(min, max) => write!(f, "min version {} is greater than max version {}", min, max),
+            Error::InvalidSize(size) => write!(f, "{} x {} modules can't hold any version", size, size),
+            Error::InvalidMask(mask) => write!(f, "mask {} is not in 0 ~ 7", mask),
+            Error::InvalidEcLevel(ec_level) => write!(f, "ec level {:?} is not L, M, Q or H", ec_level),
+            Error::InvalidMode(mode) => {
+                write!(f, "mode {:?} is not Numeric, Alphanumeric, Byte, Kanji or Chinese", mode)
+            }
+            Error::DataTooLong { bits, capacity } => write!(f, "data needs {} bits but only {} bits fit", bits, capacity),
+            Error::UnsupportedCharacter { mode, c } => write!(f, "{:?} mode can't encode {:?}", mode, c),
+            Error::EmptyImage => write!(f, "halftone image has no pixels"),
+            Error::LayersMismatch(first, other) => {
+                write!(f, "layers of {} and {} modules are not the same version", first, other)
+            }
+            Error::LogoTooLarge { codewords, budget } => {
+                write!(f, "logo covers {} codewords of a block which can correct {}", codewords, budget)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,311 @@
+use core::{fmt, ops::Range};
+
+use crate::prelude::*;
+
+use super::{
+    Encoder,
+    Error,
+    matrix::Matrix,
+    mode::Mode,
+};
+
+const EC_LEVELS: [char; 4] = ['L', 'M', 'Q', 'H'];
+
+#[derive(Debug)]
+pub struct Block {
+    pub data: Vec<u8>,
+    pub ec: Vec<u8>,
+}
+
+// every stage of `Encoder::encode`
+//
+// bits are stored as one `u8` per bit, same as `Encoder.data`
+#[derive(Debug)]
+pub struct Explanation {
+    pub mode: Mode,
+    // index from 1
+    pub version: usize,
+    pub ec_level: char,
+    // (mode, byte range in the message) of every segment, more than one only if `split` took them
+    pub split: Vec<(Mode, Range<usize>)>,
+
+    // mode indicator + character count indicator of the first segment
+    pub header: Vec<u8>,
+    // every segment, header + encoded message
+    pub segment: Vec<u8>,
+    // segment + terminator + zero bits padding
+    pub terminated: Vec<u8>,
+    // terminated bits to codewords + pad codewords(236, 17)
+    pub data_codewords: Vec<u8>,
+    pub blocks: Vec<Block>,
+    pub interleaved: Vec<u8>,
+    pub remainder_bits: u8,
+    pub final_bits: Vec<u8>,
+    // penalties of the 4 conditions for every mask
+    pub penalties: [[u32; 4]; 8],
+    // mask reference 0 ~ 7
+    pub mask: usize,
+}
+
+fn bits_to_string(bits: &[u8]) -> String {
+    bits.chunks(8)
+        .map(|byte| byte.iter().map(|bit| (b'0' + bit) as char).collect::<String>())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn codewords_to_string(codewords: &[u8]) -> String {
+    codewords.iter()
+        .map(|codeword| codeword.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn codewords_to_json(codewords: &[u8]) -> String { format!("[{}]", codewords_to_string(codewords).replace(' ', ",")) }
+
+fn bits_to_json(bits: &[u8]) -> String { format!("\"{}\"", bits.iter().map(|bit| (b'0' + bit) as char).collect::<String>()) }
+
+impl Explanation {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"mode\":\"{:?}\",\"version\":{},\"ec_level\":\"{}\",\"split\":[{}],\"header\":{},\"segment\":{},\"terminated\":{},\"data_codewords\":{},\"blocks\":[{}],\"interleaved\":{},\"remainder_bits\":{},\"final_bits\":{},\"penalties\":[{}],\"mask\":{}}}",
+            self.mode,
+            self.version,
+            self.ec_level,
+            self.split.iter()
+                .map(|(mode, range)| format!("{{\"mode\":\"{:?}\",\"range\":[{},{}]}}", mode, range.start, range.end))
+                .collect::<Vec<String>>()
+                .join(","),
+            bits_to_json(&self.header),
+            bits_to_json(&self.segment),
+            bits_to_json(&self.terminated),
+            codewords_to_json(&self.data_codewords),
+            self.blocks.iter()
+                .map(|block| format!("{{\"data\":{},\"ec\":{}}}", codewords_to_json(&block.data), codewords_to_json(&block.ec)))
+                .collect::<Vec<String>>()
+                .join(","),
+            codewords_to_json(&self.interleaved),
+            self.remainder_bits,
+            bits_to_json(&self.final_bits),
+            self.penalties.iter()
+                .map(|penalties| format!("[{},{},{},{}]", penalties[0], penalties[1], penalties[2], penalties[3]))
+                .collect::<Vec<String>>()
+                .join(","),
+            self.mask,
+        )
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "mode: {:?}", self.mode)?;
+        writeln!(f, "version: {} ({}x{} modules)", self.version, self.version * 4 + 17, self.version * 4 + 17)?;
+        writeln!(f, "ec level: {}", self.ec_level)?;
+        if self.split.len() > 1 {
+            writeln!(f, "split: {} segments", self.split.len())?;
+            for (mode, range) in self.split.iter() {
+                writeln!(f, "    {:?}: bytes {} ~ {}", mode, range.start, range.end)?;
+            }
+        }
+        writeln!(f)?;
+
+        writeln!(f, "header: {} bits", self.header.len())?;
+        writeln!(f, "    mode indicator: {}", bits_to_string(&self.header[..4]))?;
+        writeln!(f, "    character count indicator: {}", bits_to_string(&self.header[4..]))?;
+        writeln!(f, "segment: {} bits", self.segment.len())?;
+        writeln!(f, "    {}", bits_to_string(&self.segment))?;
+        writeln!(f, "terminator + padding: {} bits", self.terminated.len() - self.segment.len())?;
+        writeln!(f, "    {}", bits_to_string(&self.terminated))?;
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "data codewords: {} ({} pad codewords)",
+            self.data_codewords.len(),
+            self.data_codewords.len() - self.terminated.len() / 8
+        )?;
+        writeln!(f, "    {}", codewords_to_string(&self.data_codewords))?;
+        writeln!(f, "blocks: {}", self.blocks.len())?;
+        for (i, block) in self.blocks.iter().enumerate() {
+            writeln!(f, "    block {}: {} data codewords, {} ec codewords", i + 1, block.data.len(), block.ec.len())?;
+            writeln!(f, "        data: {}", codewords_to_string(&block.data))?;
+            writeln!(f, "        ec: {}", codewords_to_string(&block.ec))?;
+        }
+        writeln!(f, "interleaved: {} codewords", self.interleaved.len())?;
+        writeln!(f, "    {}", codewords_to_string(&self.interleaved))?;
+        writeln!(f)?;
+
+        writeln!(f, "final bits: {} bits ({} remainder bits)", self.final_bits.len(), self.remainder_bits)?;
+        writeln!(f, "    {}", bits_to_string(&self.final_bits))?;
+        writeln!(f)?;
+
+        writeln!(f, "mask: {}", self.mask)?;
+        for (mask, penalties) in self.penalties.iter().enumerate() {
+            writeln!(
+                f,
+                "    mask {}: {} + {} + {} + {} = {}{}",
+                mask,
+                penalties[0],
+                penalties[1],
+                penalties[2],
+                penalties[3],
+                penalties.iter().sum::<u32>(),
+                if mask == self.mask { " *" } else { "" }
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Encoder {
+    // same as `encode`, but record every stage
+    pub fn explain(&mut self, message: &str) -> Result<Explanation, Error> {
+        use super::{
+            capacity::indicator_bits,
+            encode::encode_split,
+            qrcode_info::{EC_CW_PER_BLOCKS, remainder_bits},
+            resolve::error_correct::error_correct,
+        };
+
+        let (split, bits_count) = match self.detect_split(message)? {
+            Some(split) => {
+                self.data.clear();
+                encode_split(&mut self.data, message, &split, self.version + 1);
+
+                let bits_count = indicator_bits(split[0].0, self.version + 1);
+
+                (split, bits_count)
+            }
+            None => {
+                let bits_count = self.detect(message)?;
+                self.segment_encode(bits_count, message);
+
+                (vec![(self.mode, 0..message.len())], bits_count)
+            }
+        };
+
+        let segment = self.data.clone();
+        let terminated = self.terminate().data.clone();
+        let data_codewords = self.decimal_data().data.clone();
+        let blocks = self.data_blocks()
+            .into_iter()
+            .map(|block| {
+                let mut ec = vec![0; EC_CW_PER_BLOCKS[self.version][self.ec_level] as usize];
+                error_correct(block, &mut ec);
+
+                Block { data: block.to_vec(), ec }
+            })
+            .collect();
+        let interleaved = self.interleave_with_ec().data.clone();
+        let final_bits = self.binary_data().data.clone();
+
+        let version = self.version + 1;
+        let mut matrix = Matrix::blank(version);
+        let penalties = matrix.with_function_patterns(version).place_data(&final_bits).penalties(self.ec_level);
+
+        Ok(Explanation {
+            mode: self.mode,
+            version: self.version + 1,
+            ec_level: EC_LEVELS[self.ec_level],
+            split,
+            header: segment[..4 + bits_count].to_vec(),
+            segment,
+            terminated,
+            data_codewords,
+            blocks,
+            interleaved,
+            remainder_bits: remainder_bits(self.version),
+            final_bits,
+            penalties,
+            // the mask `as_matrix` takes
+            mask: self.mask.or(self.halftone_mask).unwrap_or_else(|| Matrix::best_mask(&penalties)),
+        })
+    }
+}
+
+#[test]
+fn test_explain() {
+    // ISO/IEC 18004 Annex I: "01234567", version 1-M
+    let explanation = Encoder::new()
+        .version(1)
+        .ec_level("M")
+        .explain("01234567")
+        .unwrap();
+
+    assert_eq!(explanation.mode, Mode::Numeric);
+    assert_eq!(bits_to_string(&explanation.header), "00010000 001000");
+    assert_eq!(explanation.segment.len(), 41);
+    assert_eq!(explanation.terminated.len(), 48);
+    assert_eq!(
+        explanation.data_codewords,
+        vec![0x10, 0x20, 0x0c, 0x56, 0x61, 0x80, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11]
+    );
+    assert_eq!(explanation.blocks.len(), 1);
+    assert_eq!(explanation.blocks[0].ec, vec![0xa5, 0x24, 0xd4, 0xc1, 0xed, 0x36, 0xc7, 0x87, 0x2c, 0x55]);
+    assert_eq!(explanation.interleaved.len(), 26);
+    assert_eq!(explanation.final_bits.len(), 26 * 8);
+
+    let json = explanation.to_json();
+    assert!(json.starts_with("{\"mode\":\"Numeric\",\"version\":1,\"ec_level\":\"M\",\"split\":[{\"mode\":\"Numeric\",\"range\":[0,8]}],\"header\":\"00010000001000\""));
+    assert!(json.contains("\"blocks\":[{\"data\":[16,32,12,86,97,128,236,17,236,17,236,17,236,17,236,17],\"ec\":[165,36,212,193,237,54,199,135,44,85]}]"));
+    assert!(json.ends_with(&format!("\"mask\":{}}}", explanation.mask)));
+
+    // same mask as the matrix
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    assert_eq!(encoder.encode("01234567").unwrap().as_matrix().mask(), Some(explanation.mask));
+}
+
+#[test]
+fn test_explain_interleave() {
+    // version 5-Q: 2 blocks of 15 codewords + 2 blocks of 16 codewords
+    let explanation = Encoder::new()
+        .version(5)
+        .ec_level("Q")
+        .explain("HELLO WORLD")
+        .unwrap();
+
+    assert_eq!(explanation.mode, Mode::Alphanumeric);
+    assert_eq!(
+        explanation.blocks.iter().map(|block| block.data.len()).collect::<Vec<usize>>(),
+        vec![15, 15, 16, 16]
+    );
+    assert_eq!(explanation.interleaved.len(), 62 + 4 * 18);
+    assert_eq!(explanation.interleaved[..4], [
+        explanation.blocks[0].data[0],
+        explanation.blocks[1].data[0],
+        explanation.blocks[2].data[0],
+        explanation.blocks[3].data[0],
+    ]);
+    // the last data codeword only exists in group 2's blocks
+    assert_eq!(explanation.interleaved[61], explanation.blocks[3].data[15]);
+    assert_eq!(explanation.interleaved[62], explanation.blocks[0].ec[0]);
+    assert_eq!(explanation.remainder_bits, 7);
+    assert_eq!(explanation.final_bits.len(), 134 * 8 + 7);
+}
+
+#[test]
+fn test_explain_resolved() {
+    use crate::decoder::Image;
+
+    // the split `encode` takes
+    let mut encoder = Encoder::new().split(true);
+    let explanation = encoder.explain("https://EXAMPLE.COM").unwrap();
+    assert_eq!(explanation.split, vec![(Mode::Byte, 0..5), (Mode::Alphanumeric, 5..19)]);
+    assert_eq!((explanation.mode, explanation.version), (Mode::Byte, 1));
+    assert_eq!(bits_to_string(&explanation.header), "01000000 0101");
+    assert_eq!(explanation.segment.len(), 142);
+    assert!(explanation.to_string().contains("split: 2 segments\n    Byte: bytes 0 ~ 5\n    Alphanumeric: bytes 5 ~ 19\n"));
+    let matrix = encoder.as_matrix();
+    assert_eq!(Encoder::new().split(true).encode("https://EXAMPLE.COM").unwrap().as_matrix(), matrix);
+    assert_eq!(matrix.mask(), Some(explanation.mask));
+
+    // the mask the halftone padding is solved for
+    let image = Image::new(2, 2, vec![0, 255, 255, 0]);
+    let halftone = || Encoder::new().version(6).ec_level("L").halftone(&image);
+    let mut encoder = halftone();
+    let explanation = encoder.explain("https://example.com").unwrap();
+    let matrix = encoder.as_matrix();
+    assert_eq!(halftone().encode("https://example.com").unwrap().as_matrix(), matrix);
+    assert_eq!(matrix.mask(), Some(explanation.mask));
+}
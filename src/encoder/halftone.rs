@@ -0,0 +1,283 @@
+use crate::prelude::*;
+
+use crate::decoder::Image;
+use super::{
+    Encoder,
+    logo::blocks,
+    matrix::MASKS,
+    qrcode_info::{DATA_DISTRIBUTIONS, EC_CW_PER_BLOCKS},
+    region::RegionMap,
+};
+
+// average luminance of the image over `cells` x `cells` cells which cover it
+pub(crate) fn sample(image: &Image, cells: usize) -> Vec<Vec<u8>> {
+    let span = |i: usize, len: usize| {
+        let start = i * len / cells;
+
+        start..((i + 1) * len / cells).max(start + 1)
+    };
+
+    (0..cells)
+        .map(|cy| (0..cells)
+            .map(|cx| {
+                let (xs, ys) = (span(cx, image.width()), span(cy, image.height()));
+                let sum = ys.clone()
+                    .flat_map(|y| xs.clone().map(move |x| image.pixel(x, y) as usize))
+                    .sum::<usize>();
+
+                (sum / (xs.len() * ys.len())) as u8
+            })
+            .collect())
+        .collect()
+}
+
+// `sample` in black and white by Floyd-Steinberg error diffusion, dark cells are true
+pub(crate) fn dither(image: &Image, cells: usize) -> Vec<Vec<bool>> {
+    let mut values = sample(image, cells).into_iter()
+        .map(|row| row.into_iter().map(|value| value as i16).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut dots = vec![vec![false; cells]; cells];
+
+    for y in 0..cells {
+        for x in 0..cells {
+            let dark = values[y][x] < 128;
+            let error = values[y][x] - if dark { 0 } else { 255 };
+            dots[y][x] = dark;
+
+            for &(dx, dy, weight) in [(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)].iter() {
+                let (x, y) = ((x as isize + dx) as usize, y + dy);
+                if x < cells && y < cells { values[y][x] += error * weight / 16; }
+            }
+        }
+    }
+
+    dots
+}
+
+// equations over GF(2) in echelon form, one which contradicts the earlier ones is dropped
+struct Equations {
+    // coefficients as a bitset, right hand side, pivot
+    rows: Vec<(Vec<u64>, bool, usize)>,
+    unknowns: usize,
+}
+
+impl Equations {
+    fn new(unknowns: usize) -> Equations { Equations { rows: vec![], unknowns } }
+
+    fn is_full(&self) -> bool { self.rows.len() == self.unknowns }
+
+    fn add(&mut self, mut coefficients: Vec<u64>, mut rhs: bool) {
+        for (row, row_rhs, pivot) in self.rows.iter() {
+            if coefficients[pivot / 64] >> (pivot % 64) & 1 == 1 {
+                for (coefficient, row) in coefficients.iter_mut().zip(row) { *coefficient ^= row; }
+                rhs ^= row_rhs;
+            }
+        }
+
+        if let Some(i) = coefficients.iter().position(|&word| word != 0) {
+            let pivot = i * 64 + coefficients[i].trailing_zeros() as usize;
+            self.rows.push((coefficients, rhs, pivot));
+        }
+    }
+
+    // unknowns without a pivot are 0, every row only has the pivots of later rows besides its own
+    fn solve(&self) -> Vec<bool> {
+        let mut unknowns = vec![false; self.unknowns];
+
+        for (row, rhs, pivot) in self.rows.iter().rev() {
+            let sum = (0..self.unknowns).filter(|&i| unknowns[i] && row[i / 64] >> (i % 64) & 1 == 1).count();
+            unknowns[*pivot] = rhs ^ (sum % 2 == 1);
+        }
+
+        unknowns
+    }
+}
+
+fn bit(codewords: &[u8], i: usize) -> bool { codewords[i / 8] >> (7 - i % 8) & 1 == 1 }
+
+impl Encoder {
+    // replace the pad codewords after the first `fixed` data codewords so that as many modules as possible take the
+    // color of the halftone image, the most contrasted modules first, and return the mask which matches it best,
+    // `None` without an image
+    //
+    // ec codewords are linear in the data bits over GF(2), so every pad bit flips a fixed set of its block's modules
+    pub(crate) fn halftone_padding(&mut self, fixed: usize) -> Option<usize> {
+        use crate::gf256::{reed_solomon, QR_CODE};
+
+        let (version, ec_level) = (self.version + 1, self.ec_level);
+        let region_map = RegionMap::new(version, ec_level);
+        let cells = match &self.halftone {
+            Some(image) => sample(image, region_map.width()),
+            None => return None,
+        };
+
+        let data_distribution = DATA_DISTRIBUTIONS[version - 1][ec_level];
+        let g1_blocks_num = data_distribution[0] as usize;
+        let g1_cw_per_block = data_distribution[1] as usize;
+        let lens = (0..g1_blocks_num + data_distribution[2] as usize)
+            .map(|block| g1_cw_per_block + (block >= g1_blocks_num) as usize)
+            .collect::<Vec<_>>();
+        let starts = lens.iter().scan(0, |start, len| { *start += len; Some(*start - len) }).collect::<Vec<_>>();
+        let ec_cws = EC_CW_PER_BLOCKS[version - 1][ec_level] as usize;
+        let reed_solomon = reed_solomon(&QR_CODE, ec_cws, 0);
+
+        // data codewords then ec codewords of a block
+        let encode = |data: &[u8]| {
+            let mut codewords = data.to_vec();
+            codewords.resize(data.len() + ec_cws, 0);
+            let (data, ec) = codewords.split_at_mut(data.len());
+            reed_solomon.encode(data, ec);
+
+            codewords
+        };
+
+        // module(x, y) of every bit of every block's codewords
+        let mut modules = lens.iter().map(|len| vec![(0, 0); (len + ec_cws) * 8]).collect::<Vec<_>>();
+        {
+            let (data_blocks, ec_blocks) = blocks(version, ec_level);
+            let mut counts = vec![0; lens.len()];
+
+            // all data codewords come before the ec codewords, which follow them in their blocks
+            for (i, &block) in data_blocks.iter().chain(ec_blocks.iter()).enumerate() {
+                let index = counts[block];
+                counts[block] += 1;
+
+                for bit in 0..8 { modules[block][index * 8 + bit] = region_map.placement()[i * 8 + bit]; }
+            }
+        }
+
+        // every module of a block as a sum of pad bits, on top of the block with zero pads
+        let systems = (0..lens.len())
+            .map(|block| {
+                let free = (starts[block]..starts[block] + lens[block]).filter(|&i| i >= fixed).count() * 8;
+                let offset = lens[block] * 8 - free;
+                let mut data = self.data[starts[block]..starts[block] + lens[block]].to_vec();
+                for codeword in data[offset / 8..].iter_mut() { *codeword = 0; }
+
+                let mut rows = vec![vec![0u64; free.div_ceil(64)]; modules[block].len()];
+                for j in 0..free {
+                    let mut unit = vec![0; lens[block]];
+                    unit[(offset + j) / 8] = 0x80 >> ((offset + j) % 8);
+
+                    let column = encode(&unit);
+                    for (r, row) in rows.iter_mut().enumerate() {
+                        if bit(&column, r) { row[j / 64] |= 1 << (j % 64); }
+                    }
+                }
+
+                (encode(&data), rows, free, offset)
+            })
+            .collect::<Vec<_>>();
+
+        let masks = self.mask.map_or(0..8, |mask| mask..mask + 1);
+        let mut best: Option<(i32, usize, Vec<u8>)> = None;
+        for mask in masks {
+            let mut data = self.data.clone();
+
+            for (block, (base, rows, free, offset)) in systems.iter().enumerate() {
+                if *free == 0 { continue; }
+
+                let mut order = (0..rows.len()).collect::<Vec<_>>();
+                let contrast = |r: usize| { let (x, y) = modules[block][r]; (cells[y][x] as i16 - 128).abs() };
+                order.sort_by_key(|&r| -contrast(r));
+
+                let mut equations = Equations::new(*free);
+                for r in order {
+                    if equations.is_full() { break; }
+
+                    let (x, y) = modules[block][r];
+                    // the module is drawn with the mask applied
+                    let target = (cells[y][x] < 128) ^ MASKS[mask](x as u8, y as u8);
+                    equations.add(rows[r].clone(), target ^ bit(base, r));
+                }
+
+                for (j, value) in equations.solve().into_iter().enumerate() {
+                    let (i, shift) = (starts[block] + (offset + j) / 8, 7 - (offset + j) % 8);
+                    data[i] = data[i] & !(1 << shift) | (value as u8) << shift;
+                }
+            }
+
+            // contrast of every module which takes its color
+            let score = (0..lens.len())
+                .map(|block| {
+                    let codewords = encode(&data[starts[block]..starts[block] + lens[block]]);
+
+                    modules[block].iter()
+                        .enumerate()
+                        .filter(|&(r, &(x, y))| bit(&codewords, r) ^ MASKS[mask](x as u8, y as u8) == (cells[y][x] < 128))
+                        .map(|(_, &(x, y))| (cells[y][x] as i32 - 128).abs())
+                        .sum::<i32>()
+                })
+                .sum::<i32>();
+
+            if best.as_ref().is_none_or(|&(best, _, _)| score > best) { best = Some((score, mask, data)); }
+        }
+
+        let (_, mask, data) = best.unwrap();
+        self.data = data;
+
+        Some(mask)
+    }
+}
+
+#[test]
+fn test_dither() {
+    let image = Image::new(4, 2, vec![0, 0, 255, 255, 0, 0, 255, 255]);
+    assert_eq!(sample(&image, 2), vec![vec![0, 255], vec![0, 255]]);
+    assert_eq!(sample(&image, 8)[0], vec![0, 0, 0, 0, 255, 255, 255, 255]);
+
+    // 50% gray is every other dot
+    let dots = dither(&Image::new(1, 1, vec![128]), 30);
+    assert_eq!(dots.iter().flatten().filter(|&&dark| dark).count(), 450);
+}
+
+#[test]
+fn test_equations() {
+    // x0 + x1 = 1, x1 = 1, then x0 = 1 contradicts them
+    let mut equations = Equations::new(3);
+    equations.add(vec![0b011], true);
+    equations.add(vec![0b010], true);
+    equations.add(vec![0b001], true);
+    assert_eq!(equations.rows.len(), 2);
+    assert_eq!(equations.solve(), vec![false, true, false]);
+}
+
+#[test]
+fn test_halftone() {
+    use crate::{decoder::decode, renderer::Renderer};
+
+    // a dark disc on white
+    let image = Image::new(90, 90, (0..90 * 90i32).map(|i| {
+        let (x, y) = (i % 90, i / 90);
+
+        if (x - 45).pow(2) + (y - 45).pow(2) < 30 * 30 { 0 } else { 255 }
+    }).collect());
+
+    let message = "https://example.com";
+    let plain = Encoder::new().version(6).ec_level("L").encode(message).unwrap().as_matrix();
+    let mut encoder = Encoder::new().version(6).ec_level("L").halftone(&image);
+    let matrix = encoder.encode(message).unwrap().as_matrix();
+    assert_eq!(decode(&matrix).unwrap().data, message.as_bytes());
+
+    // far more data modules follow the disc than with the standard padding
+    let cells = sample(&image, matrix.width());
+    let matches = |matrix: &crate::encoder::Matrix| (0..matrix.width())
+        .flat_map(|y| (0..matrix.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !matrix.is_function(x, y) && matrix.is_dark(x, y) == (cells[y][x] < 128))
+        .count();
+    assert!(matches(&matrix) > matches(&plain) + 300, "{} {}", matches(&matrix), matches(&plain));
+
+    // the center of every module is still read
+    let png = Renderer::new(&matrix).scale(6).halftone(&image).png();
+    assert_eq!(crate::decoder::decode_image(&png).unwrap().data, message.as_bytes());
+
+    // a mask set by the builder is kept
+    let matrix = Encoder::new().version(6).ec_level("L").mask(5).halftone(&image).encode(message).unwrap().as_matrix();
+    assert_eq!(matrix.mask(), Some(5));
+    assert_eq!(decode(&matrix).unwrap().data, message.as_bytes());
+
+    // an empty image is an error for the encoder and draws nothing
+    let empty = Image::new(0, 0, vec![]);
+    assert_eq!(Encoder::new().halftone(&empty).encode(message).err(), Some(super::Error::EmptyImage));
+    assert_eq!(Renderer::new(&plain).halftone(&empty).png(), Renderer::new(&plain).png());
+}
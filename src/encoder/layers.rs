@@ -0,0 +1,62 @@
+use super::{
+    Encoder,
+    Error,
+    matrix::Matrix,
+};
+
+impl Encoder {
+    // three symbols of the same version with the same settings, for the red, green and blue channels of one image as
+    // `Renderer::layers` draws them; their function patterns line up, so the finder patterns are black in the image
+    //
+    // the version is the smallest one which fits every message, ec levels and masks are chosen per symbol
+    pub fn encode_layers(&self, messages: [&str; 3]) -> Result<[Matrix; 3], Error> {
+        let mut encoders = [self.clone(), self.clone(), self.clone()];
+
+        loop {
+            for (encoder, message) in encoders.iter_mut().zip(messages.iter()) { encoder.encode(message)?; }
+
+            let version = encoders.iter().map(|encoder| encoder.version).max().unwrap();
+            if encoders.iter().all(|encoder| encoder.version == version) {
+                return Ok([encoders[0].as_matrix(), encoders[1].as_matrix(), encoders[2].as_matrix()]);
+            }
+
+            // the smaller symbols grow to the largest one, which a higher ec level might move again
+            for encoder in encoders.iter_mut() { *encoder = self.clone().min_version(version + 1); }
+        }
+    }
+}
+
+#[test]
+fn test_encode_layers() {
+    use crate::{decoder::{decode, decode_layers}, renderer::Renderer};
+
+    let messages = ["01234567", "HELLO WORLD", &"hello, world! ".repeat(5)[..]];
+    let layers = Encoder::new().ec_level("M").encode_layers(messages).unwrap();
+
+    // the longest message takes version 5
+    for (layer, message) in layers.iter().zip(messages.iter()) {
+        assert_eq!(layer.width(), 37);
+        assert_eq!(decode(layer).unwrap().data, message.as_bytes());
+    }
+
+    let renderer = Renderer::layers(&layers).unwrap().scale(2);
+    for image in [renderer.png(), renderer.ppm()].iter() {
+        let decoded = decode_layers(image).unwrap();
+        for (decoded, message) in decoded.iter().zip(messages.iter()) { assert_eq!(decoded.data, message.as_bytes()); }
+    }
+    // the top left corner of the finder pattern is black, the separator is white
+    let ppm = renderer.ppm();
+    let pixel = |x: usize, y: usize| &ppm[13 + 3 * (y * 90 + x)..][..3];
+    assert_eq!(pixel(8, 8), [0, 0, 0]);
+    assert_eq!(pixel(8 + 14, 8 + 14), [255, 255, 255]);
+
+    // symbols of different versions can't be drawn together
+    let version_1 = Encoder::new().encode("1").unwrap().as_matrix();
+    let mismatched = [layers[0].clone(), version_1, layers[2].clone()];
+    assert_eq!(Renderer::layers(&mismatched).unwrap_err(), Error::LayersMismatch(37, 21));
+
+    assert_eq!(
+        Encoder::new().version(1).encode_layers(["1", "2", &"3".repeat(42)[..]]).unwrap_err(),
+        Error::DataTooLong { bits: 154, capacity: 152 }
+    );
+}
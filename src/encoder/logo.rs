@@ -0,0 +1,219 @@
+use crate::prelude::*;
+
+use super::{
+    qrcode_info::{DATA_DISTRIBUTIONS, EC_CW_PER_BLOCKS},
+    region::{Region, RegionMap},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogoShape {
+    Rectangle,
+    Circle,
+}
+
+// a logo in the center of the symbol, its size is a fraction of the symbol's width so that it keeps its proportion
+// when a higher ec level takes a larger version
+//
+// a module is covered if the logo overlaps any part of it, function patterns are never covered
+#[derive(Debug, Clone, PartialEq)]
+pub struct Logo {
+    pub shape: LogoShape,
+    // 0 ~ 1 of the symbol's width, the diameter of a circle
+    pub width: f64,
+    pub height: f64,
+    // covered modules are drawn light, otherwise they are drawn and the logo goes over them
+    pub clear: bool,
+    // image drawn over the center by `Renderer::svg`
+    pub href: Option<String>,
+}
+
+impl Logo {
+    pub fn rectangle(width: f64, height: f64) -> Logo {
+        Logo {
+            shape: LogoShape::Rectangle,
+            width: width.clamp(0., 1.),
+            height: height.clamp(0., 1.),
+            clear: true,
+            href: None,
+        }
+    }
+
+    pub fn circle(diameter: f64) -> Logo {
+        Logo {
+            shape: LogoShape::Circle,
+            ..Logo::rectangle(diameter, diameter)
+        }
+    }
+
+    pub fn clear(mut self, clear: bool) -> Logo {
+        self.clear = clear;
+
+        self
+    }
+
+    pub fn href(mut self, href: &str) -> Logo {
+        self.href = Some(href.to_string());
+
+        self
+    }
+
+    // whether the logo overlaps module(x, y) of a symbol `len` modules wide, function patterns included
+    pub fn overlaps(&self, len: usize, x: usize, y: usize) -> bool {
+        if self.width <= 0. || self.height <= 0. { return false; }
+
+        let center = len as f64 / 2.;
+        // from the center to the nearest point of the module
+        let dx = ((x as f64 + 0.5 - center).abs() - 0.5).max(0.);
+        let dy = ((y as f64 + 0.5 - center).abs() - 0.5).max(0.);
+        let (rx, ry) = (self.width * len as f64 / 2., self.height * len as f64 / 2.);
+
+        match self.shape {
+            LogoShape::Rectangle => dx < rx && dy < ry,
+            LogoShape::Circle => dx * dx + dy * dy < rx * rx,
+        }
+    }
+
+    // codewords of every block with at least one covered module
+    //
+    // version: index from 1
+    // ec_level: same as `Encoder`
+    pub(crate) fn obscured(&self, version: usize, ec_level: usize) -> Vec<usize> {
+        let (data_blocks, ec_blocks) = blocks(version, ec_level);
+        let region_map = RegionMap::new(version, ec_level);
+        let len = region_map.width();
+
+        // a codeword counts once however many of its modules are covered
+        let mut codewords = vec![];
+        for y in 0..len {
+            for x in 0..len {
+                if !self.overlaps(len, x, y) { continue; }
+
+                match region_map.get(x, y) {
+                    Region::Data { codeword, .. } => codewords.push((data_blocks[codeword], codeword)),
+                    Region::Ec { codeword, .. } => codewords.push((ec_blocks[codeword], data_blocks.len() + codeword)),
+                    _ => (),
+                }
+            }
+        }
+        codewords.sort_unstable();
+        codewords.dedup();
+
+        let mut obscured = vec![0; *ec_blocks.iter().max().unwrap() + 1];
+        for (block, _) in codewords { obscured[block] += 1; }
+
+        obscured
+    }
+
+    // whether every block can correct its covered codewords, as if all of them were wrong
+    pub(crate) fn fits(&self, version: usize, ec_level: usize) -> Result<(), (usize, usize)> {
+        let budget = correctable(version, ec_level);
+
+        match self.obscured(version, ec_level).into_iter().max() {
+            Some(codewords) if codewords > budget => Err((codewords, budget)),
+            _ => Ok(()),
+        }
+    }
+}
+
+// block of every interleaved data codeword and ec codeword, the reverse of `interleave_with_ec`
+pub(super) fn blocks(version: usize, ec_level: usize) -> (Vec<usize>, Vec<usize>) {
+    let data_distribution = DATA_DISTRIBUTIONS[version - 1][ec_level];
+    let (g1_blocks_num, g1_cw_per_block) = (data_distribution[0] as usize, data_distribution[1] as usize);
+    let blocks_num = g1_blocks_num + data_distribution[2] as usize;
+
+    let mut data_blocks = vec![];
+    for i in 0..=g1_cw_per_block {
+        // group 2's blocks have one more codeword
+        let first = if i < g1_cw_per_block { 0 } else { g1_blocks_num };
+        data_blocks.extend(first..blocks_num);
+    }
+    let ec_blocks = (0..EC_CW_PER_BLOCKS[version - 1][ec_level]).flat_map(|_| 0..blocks_num).collect();
+
+    (data_blocks, ec_blocks)
+}
+
+// codewords a block can correct, the smallest symbols keep a few ec codewords for misdecode protection, ISO/IEC 18004
+// table 9
+fn correctable(version: usize, ec_level: usize) -> usize {
+    let protection = match (version, ec_level) {
+        (1, 0) => 3,
+        (1, 1) | (2, 0) => 2,
+        (1, _) | (3, 0) => 1,
+        _ => 0,
+    };
+
+    (EC_CW_PER_BLOCKS[version - 1][ec_level] as usize - protection) / 2
+}
+
+#[test]
+fn test_blocks() {
+    use super::qrcode_info::CAPACITIES;
+
+    for version in 1..=40 {
+        for ec_level in 0..4 {
+            let (data_blocks, ec_blocks) = blocks(version, ec_level);
+            let blocks_num = (DATA_DISTRIBUTIONS[version - 1][ec_level][0] + DATA_DISTRIBUTIONS[version - 1][ec_level][2]) as usize;
+
+            assert_eq!(data_blocks.len(), CAPACITIES[version - 1][ec_level] as usize / 8);
+            assert_eq!(ec_blocks.len(), EC_CW_PER_BLOCKS[version - 1][ec_level] as usize * blocks_num);
+        }
+    }
+
+    // 5-Q: 2 blocks of 15 and 2 blocks of 16 data codewords
+    let (data_blocks, ec_blocks) = blocks(5, 2);
+    assert_eq!(data_blocks[..6], [0, 1, 2, 3, 0, 1]);
+    assert_eq!(data_blocks[56..], [0, 1, 2, 3, 2, 3]);
+    assert_eq!(ec_blocks[..5], [0, 1, 2, 3, 0]);
+
+    assert_eq!((correctable(1, 0), correctable(1, 3), correctable(3, 0), correctable(5, 2)), (2, 8, 7, 9));
+}
+
+#[test]
+fn test_logo() {
+    // 21 modules, a square 5 modules wide from 8 to 13 overlaps modules 8 ~ 12
+    let logo = Logo::rectangle(5. / 21., 5. / 21.);
+    assert!(logo.overlaps(21, 8, 8) && logo.overlaps(21, 12, 12));
+    assert!(!logo.overlaps(21, 7, 10) && !logo.overlaps(21, 10, 13));
+
+    // corners of the bounding square are left out of a circle
+    let logo = Logo::circle(9. / 21.);
+    assert!(logo.overlaps(21, 10, 6) && logo.overlaps(21, 7, 8));
+    assert!(!logo.overlaps(21, 6, 6) && !logo.overlaps(21, 14, 14));
+
+    // 5 x 5 modules over 5 codewords, 1-H corrects 8 of them, 1-L only 2
+    assert_eq!(Logo::rectangle(0.2, 0.2).obscured(1, 0), [5]);
+    assert_eq!(Logo::rectangle(0.2, 0.2).fits(1, 3), Ok(()));
+    assert_eq!(Logo::rectangle(0.2, 0.2).fits(1, 0), Err((5, 2)));
+    assert_eq!(Logo::rectangle(0., 0.).obscured(1, 0), [0]);
+}
+
+#[test]
+fn test_encode_logo() {
+    use crate::{decode_image, encoder::{Encoder, Error}, renderer::Renderer};
+
+    let message = "https://example.com/menu";
+    let logo = Logo::circle(0.25);
+
+    assert!(matches!(
+        Encoder::new().ec_level("L").logo(&logo, false).encode(message).err(),
+        Some(Error::LogoTooLarge { .. })
+    ));
+
+    // raised to the first ec level whose blocks correct what the logo covers
+    let mut encoder = Encoder::new().ec_level("L").logo(&logo, true);
+    let matrix = encoder.encode(message).unwrap().as_matrix();
+    assert!(encoder.ec_level > 0);
+    assert_eq!(logo.fits(encoder.version + 1, encoder.ec_level), Ok(()));
+    assert!(logo.fits(Encoder::new().ec_level("L").detect_version(message).unwrap(), 0).is_err());
+
+    // the cleared modules are read back as errors
+    let decoded = decode_image(&Renderer::new(&matrix).logo(&logo).pbm()).unwrap();
+    assert_eq!(decoded.data, message.as_bytes());
+    assert!(decoded.corrected > 0);
+
+    // a fixed version can't grow
+    assert_eq!(
+        Encoder::new().version(1).logo(&Logo::rectangle(0.5, 0.5), true).encode("1").err(),
+        Some(Error::LogoTooLarge { codewords: 12, budget: 8 })
+    );
+}
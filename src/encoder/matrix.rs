@@ -1,3 +1,8 @@
+// coordinates read better than iterators in the pattern drawing
+#![allow(clippy::needless_range_loop)]
+
+use crate::prelude::*;
+
 // state: u8
 // 0 -> 0 normal module
 // 1 -> 1 normal module
@@ -5,8 +10,45 @@
 // 3 -> 1 function module
 // 4 -> 0 reserved module
 // 5 -> 0 unused module
-#[derive(Debug)]
-pub struct Matrix(Vec<Vec<u8>>);
+//
+// serialized as `super::compact::CompactMatrix`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "super::compact::CompactMatrix", try_from = "super::compact::CompactMatrix")
+)]
+pub struct Matrix(pub(crate) Vec<Vec<u8>>);
+
+// coordinates(x, y) of every module except the column 6, in the order of data placement
+//
+// columns are walked in pairs from the right, upward and downward in turn
+fn walk(len: usize) -> impl Iterator<Item = (usize, usize)> {
+    use core::iter::once;
+
+    // avoid timing pattern
+    (8..len).rev()
+        .step_by(2)
+        .chain([5, 3, 1].iter().cloned())
+        .enumerate()
+        .flat_map(move |(i, x)| (0..len).flat_map(move |j| {
+            let y = if i % 2 == 0 { len - 1 - j } else { j };
+
+            once((x, y)).chain(once((x - 1, y)))
+        }))
+}
+
+fn mask_1(x: u8, y: u8) -> bool { (x as u16 + y as u16).is_multiple_of(2) }
+fn mask_2(_: u8, y: u8) -> bool { y.is_multiple_of(2) }
+fn mask_3(x: u8, _: u8) -> bool { x.is_multiple_of(3) }
+fn mask_4(x: u8, y: u8) -> bool { (x as u16 + y as u16).is_multiple_of(3) }
+fn mask_5(x: u8, y: u8) -> bool { (y / 2 + x / 3).is_multiple_of(2) }
+fn mask_6(x: u8, y: u8) -> bool { ((x as u16 * y as u16) % 2) + ((x as u16 * y as u16) % 3) == 0 }
+fn mask_7(x: u8, y: u8) -> bool { (((x as u16 * y as u16) % 2) + ((x as u16 * y as u16) % 3)).is_multiple_of(2) }
+fn mask_8(x: u8, y: u8) -> bool { (((x as u16 + y as u16) % 2) + ((x as u16 * y as u16) % 3)).is_multiple_of(2) }
+
+// MASKS[mask reference], x -> column, y -> row
+pub(super) const MASKS: [fn(u8, u8) -> bool; 8] = [mask_1, mask_2, mask_3, mask_4, mask_5, mask_6, mask_7, mask_8];
 
 fn normalize_module(module: u8) -> u8 {
     match module {
@@ -15,8 +57,56 @@ fn normalize_module(module: u8) -> u8 {
     }
 }
 
+// coordinates(x, y) of format information bit 0 ~ 14
+//
+// the first copy skips the timing patterns around the top left finder pattern,
+// the second one is split between the top right and bottom left finder patterns
+fn format_coordinates(len: usize) -> [[(usize, usize); 15]; 2] {
+    let mut coordinates = [[(0, 0); 15]; 2];
+
+    for i in 0..15 {
+        coordinates[0][i] = match i {
+            0..=5 => (8, i),
+            6 => (8, 7),
+            7 => (8, 8),
+            8 => (7, 8),
+            _ => (14 - i, 8)
+        };
+        coordinates[1][i] = if i < 8 { (len - 1 - i, 8) } else { (8, len - 15 + i) };
+    }
+
+    coordinates
+}
+
+// (a, b) of version information bit 0 ~ 17, the top right copy is at (x: a, y: b), the bottom left one is transposed
+fn version_coordinates(len: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..18).map(move |i| (len - 11 + i % 3, i / 3))
+}
+
 impl Matrix {
-    fn add_finder_patterns(&mut self) -> &mut Matrix {
+    pub fn width(&self) -> usize { self.0.len() }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool { normalize_module(self.0[y][x]) == 1 }
+
+    // finder, separator, timing, alignment, format or version module, or the dark module
+    pub fn is_function(&self, x: usize, y: usize) -> bool { self.0[y][x] >= 2 }
+
+    // dark and light modules swapped, a reflectance reversed symbol of ISO/IEC 18004:2015
+    pub fn inverted(&self) -> Matrix {
+        Matrix(self.0.iter().map(|row| row.iter().map(|&module| if module < 4 { module ^ 1 } else { module }).collect()).collect())
+    }
+
+    // module(x, y) moved to (y, x), the mirror image which keeps the finder patterns in their corners
+    pub fn transposed(&self) -> Matrix {
+        Matrix((0..self.width()).map(|y| (0..self.width()).map(|x| self.0[x][y]).collect()).collect())
+    }
+
+    // left and right swapped, the mirror image of a symbol read from behind
+    pub fn mirrored(&self) -> Matrix {
+        Matrix(self.0.iter().map(|row| row.iter().rev().cloned().collect()).collect())
+    }
+
+    pub(crate) fn add_finder_patterns(&mut self) -> &mut Matrix {
         let Matrix(matrix) = self;
         let fix = matrix.len() - 7;
 
@@ -28,7 +118,7 @@ impl Matrix {
                             0 | 6 => 3,
                             _ => 2
                         }
-                        2...4 => match y {
+                        2..=4 => match y {
                             1 | 5 => 2,
                             _ => 3
                         }
@@ -41,7 +131,7 @@ impl Matrix {
         self
     }
 
-    fn add_separators(&mut self) -> &mut Matrix {
+    pub(crate) fn add_separators(&mut self) -> &mut Matrix {
         let Matrix(matrix) = self;
         let len = matrix.len();
         let fix = matrix.len() - 8;
@@ -66,7 +156,7 @@ impl Matrix {
         self
     }
 
-    fn add_alignment_patterns(&mut self, version: usize) -> &mut Matrix {
+    pub(crate) fn add_alignment_patterns(&mut self, version: usize) -> &mut Matrix {
         use crate::encoder::qrcode_info::ALIGNMENT_PATTERN_LOCATIONS;
 
         if version == 1 { return self; }
@@ -85,8 +175,8 @@ impl Matrix {
                 for y in 0..5 {
                     for x in 0..5 {
                         matrix[x + i][y + j] = match x {
-                            1...3 => match y {
-                                1...3 => 2,
+                            1..=3 => match y {
+                                1..=3 => 2,
                                 _ => 3
                             }
                             _ => 3
@@ -102,7 +192,7 @@ impl Matrix {
         self
     }
 
-    fn add_timing_patterns(&mut self) -> &mut Matrix {
+    pub(crate) fn add_timing_patterns(&mut self) -> &mut Matrix {
         let Matrix(matrix) = self;
         let fix = matrix.len() - 8;
         let mut timing_pattern = [3, 3, 2, 2].iter().cycle();
@@ -117,39 +207,40 @@ impl Matrix {
         self
     }
 
-    fn add_dark_module_and_reserved_areas(&mut self, version: usize) -> &mut Matrix {
+    pub(crate) fn add_dark_module_and_reserved_areas(&mut self, version: usize) -> &mut Matrix {
         let Matrix(matrix) = self;
         let len = matrix.len();
         let fix = len - 8;
 
+        // reserved areas
+        for i in 0..9 {
+            // horizontal
+            matrix[8][i] = 4;
+            // vertical
+            matrix[i][8] = 4;
+        }
+
+        for i in fix..len {
+            // horizontal
+            matrix[8][i] = 4;
+            // vertical
+            matrix[i][8] = 4;
+        }
+
+        // avoid timing pattern
+        matrix[6][8] = 3;
+        matrix[8][6] = 3;
+
+        // version information areas
         if version > 6 {
             for i in fix - 3..fix {
                 for j in 0..6 {
                     // top-right
-                    matrix[i][j] = 4;
-                    // bottom-left
                     matrix[j][i] = 4;
+                    // bottom-left
+                    matrix[i][j] = 4;
                 }
             }
-        } else {
-            // reserved areas
-            for i in 0..9 {
-                // horizontal
-                matrix[8][i] = 4;
-                // vertical
-                matrix[i][8] = 4;
-            }
-
-            for i in fix..len {
-                // horizontal
-                matrix[8][i] = 4;
-                // vertical
-                matrix[i][8] = 4;
-            }
-
-            // avoid timing pattern
-            matrix[6][8] = 3;
-            matrix[8][6] = 3;
         }
 
         // add dark module
@@ -158,51 +249,96 @@ impl Matrix {
         self
     }
 
-    fn place_data(&mut self, data: &Vec<u8>) -> &mut Matrix {
+    // format information around the top left finder pattern, and split between the other two
+    pub(crate) fn add_format_info(&mut self, ec_level: usize, mask: usize) -> &mut Matrix {
+        use crate::encoder::qrcode_info::format_bits;
+
         let Matrix(matrix) = self;
-        let len = matrix.len();
-        let mut data = data.iter();
-        let mut upward = true;
-        let mut x = len - 1;
-
-        'outer: loop {
-            if upward {
-                for y in (0..len).rev() {
-                    if matrix[y][x] == 5 { if let Some(&bit) = data.next() { matrix[y][x] = bit; } else { break 'outer; } }
-                    if matrix[y][x - 1] == 5 { if let Some(&bit) = data.next() { matrix[y][x - 1] = bit; } else { break 'outer; } }
-                }
-            } else {
-                for y in 0..len {
-                    if matrix[y][x] == 5 { if let Some(&bit) = data.next() { matrix[y][x] = bit; } else { break 'outer; } }
-                    if matrix[y][x - 1] == 5 { if let Some(&bit) = data.next() { matrix[y][x - 1] = bit; } else { break 'outer; } }
-                }
-            };
+        let bits = format_bits(ec_level, mask);
 
-            upward = !upward;
+        for copy in format_coordinates(matrix.len()).iter() {
+            for (i, &(x, y)) in copy.iter().enumerate() { matrix[y][x] = 2 + (bits >> i & 1) as u8; }
+        }
 
-            match x {
-                1 => break,
-                8 => x -= 3, // avoid timing pattern
-                _ => x -= 2
-            }
+        self
+    }
+
+    // both copies of the format information, dark modules are 1
+    pub(crate) fn format_info(&self) -> [u16; 2] {
+        let Matrix(matrix) = self;
+        let mut bits = [0; 2];
+
+        for (bits, copy) in bits.iter_mut().zip(format_coordinates(matrix.len()).iter()) {
+            for (i, &(x, y)) in copy.iter().enumerate() { *bits |= ((matrix[y][x] & 1) as u16) << i; }
+        }
+
+        bits
+    }
+
+    // version information above the bottom left finder pattern and left of the top right one, version 7 ~ 40
+    pub(crate) fn add_version_info(&mut self, version: usize) -> &mut Matrix {
+        use crate::encoder::qrcode_info::version_bits;
+
+        if version < 7 { return self; }
+
+        let Matrix(matrix) = self;
+        let bits = version_bits(version);
+
+        for (i, (a, b)) in version_coordinates(matrix.len()).enumerate() {
+            let module = 2 + (bits >> i & 1) as u8;
+
+            matrix[b][a] = module;
+            matrix[a][b] = module;
         }
 
         self
     }
 
-    fn eval_condition_1(matrix: &Vec<Vec<u8>>) -> u32 {
+    // coordinates(x, y) of unused modules, in the order of data placement
+    pub(crate) fn placement(&self) -> Vec<(usize, usize)> { self.placement_iter().collect() }
+
+    pub(crate) fn placement_iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let Matrix(matrix) = self;
+
+        walk(matrix.len()).filter(move |&(x, y)| matrix[y][x] == 5)
+    }
+
+    pub(crate) fn place_data(&mut self, data: &[u8]) -> &mut Matrix {
+        for (&bit, (x, y)) in data.iter().zip(self.placement()) { self.0[y][x] = bit; }
+
+        self
+    }
+
+    // place codewords from the most significant bit, remainder bits are 0
+    pub(crate) fn place_codewords(&mut self, codewords: &[u8]) -> &mut Matrix {
+        let Matrix(matrix) = self;
+        let mut i = 0;
+
+        for (x, y) in walk(matrix.len()) {
+            if matrix[y][x] != 5 { continue; }
+
+            matrix[y][x] = codewords.get(i / 8).map_or(0, |codeword| codeword >> (7 - i % 8) & 1);
+            i += 1;
+        }
+
+        self
+    }
+
+    // modules are compared by color, `state & 1` is 1 for dark modules once the matrix is filled
+
+    fn eval_condition_1(matrix: &[Vec<u8>]) -> u32 {
         let mut penalty = 0;
 
         let edge = matrix.len();
         for i in 0..edge {
             let mut count_x = 1u8;
-            let mut prev_module_x = &matrix[i][0];
+            let mut prev_module_x = matrix[i][0] & 1;
             let mut count_y = 1u8;
-            let mut prev_module_y = &matrix[0][i];
+            let mut prev_module_y = matrix[0][i] & 1;
 
             for j in 1..edge {
                 // horizontal
-                let module = &matrix[i][j];
+                let module = matrix[i][j] & 1;
                 if module == prev_module_x {
                     count_x += 1;
                     if count_x == 5 { penalty += 3; } else if count_x > 5 { penalty += 1; }
@@ -212,7 +348,7 @@ impl Matrix {
                 }
 
                 // vertical
-                let module = &matrix[j][i];
+                let module = matrix[j][i] & 1;
                 if module == prev_module_y {
                     count_y += 1;
                     if count_y == 5 { penalty += 3; } else if count_y > 5 { penalty += 1; }
@@ -226,21 +362,21 @@ impl Matrix {
         penalty
     }
 
-    fn eval_condition_2(matrix: &Vec<Vec<u8>>) -> u32 {
+    fn eval_condition_2(matrix: &[Vec<u8>]) -> u32 {
         let mut penalty = 0;
 
         let edge = matrix.len() - 1;
         for y in 0..edge {
             for x in 0..edge {
-                let top_left = &matrix[y][x];
+                let top_left = matrix[y][x] & 1;
 
-                let top_right = &matrix[y][x + 1];
+                let top_right = matrix[y][x + 1] & 1;
                 if top_left != top_right { continue; }
 
-                let bottom_left = &matrix[y + 1][x];
+                let bottom_left = matrix[y + 1][x] & 1;
                 if top_left != bottom_left { continue; }
 
-                let bottom_right = &matrix[y + 1][x + 1];
+                let bottom_right = matrix[y + 1][x + 1] & 1;
                 if top_left != bottom_right { continue; }
 
                 penalty += 3;
@@ -250,18 +386,19 @@ impl Matrix {
         penalty
     }
 
-    fn eval_condition_3(matrix: &Vec<Vec<u8>>) -> u32 {
+    fn eval_condition_3(matrix: &[Vec<u8>]) -> u32 {
         let mut penalty = 0;
 
         let edge = matrix.len();
         for i in 0..edge {
             for j in 0..edge - 10 {
                 // horizontal
-                let modules = &matrix[i][j..j + 11];
+                let mut modules = [0; 11];
+                for (module, &state) in modules.iter_mut().zip(matrix[i][j..j + 11].iter()) { *module = state & 1; }
                 if modules == [1, 0, 1, 1, 1, 0, 1, 0, 0, 0, 0] || modules == [0, 0, 0, 0, 1, 0, 1, 1, 1, 0, 1] { penalty += 40; }
 
                 // vertical
-                let modules: Vec<u8> = matrix[j..j + 11].iter().map(|y| y[i]).collect();
+                for (module, row) in modules.iter_mut().zip(matrix[j..j + 11].iter()) { *module = row[i] & 1; }
                 if modules == [1, 0, 1, 1, 1, 0, 1, 0, 0, 0, 0] || modules == [0, 0, 0, 0, 1, 0, 1, 1, 1, 0, 1] { penalty += 40; }
             }
         }
@@ -269,103 +406,123 @@ impl Matrix {
         penalty
     }
 
-    fn eval_condition_4(matrix: &Vec<Vec<u8>>) -> u32 {
-        let percent_of_dark_modules = {
-            let mut dark_modules = 0;
-            for row in matrix {
-                for module in row {
-                    if module == &0 { dark_modules += 1; }
-                }
+    fn eval_condition_4(matrix: &[Vec<u8>]) -> u32 {
+        let dark_modules = matrix.iter()
+            .flatten()
+            .filter(|&&module| module & 1 == 1)
+            .count();
+        let percent_of_dark_modules = (dark_modules * 100 / matrix.len().pow(2)) as i32;
+
+        // every 5% away from 50%
+        (50 - percent_of_dark_modules).unsigned_abs() / 5 * 10
+    }
+
+    // invert data modules where the mask is true, apply the same mask again to revert it
+    fn apply_mask(matrix: &mut [Vec<u8>], mask: fn(u8, u8) -> bool) {
+        for (y, row) in matrix.iter_mut().enumerate() {
+            for (x, module) in row.iter_mut().enumerate() {
+                if *module < 2 && mask(x as u8, y as u8) { *module ^= 1; }
             }
+        }
+    }
 
-            (dark_modules * 100) / (matrix.len() as i32).pow(2) * 5 / 5
-        };
+    // penalty of each condition with this mask and its format information, the data modules are restored
+    fn mask_penalties(&mut self, ec_level: usize, mask: usize) -> [u32; 4] {
+        Matrix::apply_mask(&mut self.0, MASKS[mask]);
+        self.add_format_info(ec_level, mask);
 
-        ((50 - percent_of_dark_modules).abs() as u32 / 5).min((55 - percent_of_dark_modules).abs() as u32 / 5) * 10
+        let Matrix(matrix) = self;
+        let penalties = [
+            Matrix::eval_condition_1(matrix),
+            Matrix::eval_condition_2(matrix),
+            Matrix::eval_condition_3(matrix),
+            Matrix::eval_condition_4(matrix),
+        ];
+
+        Matrix::apply_mask(matrix, MASKS[mask]);
+
+        penalties
     }
 
-    fn data_mask(&mut self) -> Matrix {
-        use std::{
-            sync::Arc,
-            thread,
-        };
+    // penalties of every mask, without threads or copies
+    pub(crate) fn penalties(&mut self, ec_level: usize) -> [[u32; 4]; 8] {
+        let mut penalties = [[0; 4]; 8];
+        for (mask, penalties) in penalties.iter_mut().enumerate() { *penalties = self.mask_penalties(ec_level, mask); }
 
-        fn mask_1(x: u8, y: u8) -> bool { (x as u16 + y as u16) % 2 == 0 }
-        fn mask_2(x: u8, _: u8) -> bool { x % 2 == 0 }
-        fn mask_3(_: u8, y: u8) -> bool { y % 3 == 0 }
-        fn mask_4(x: u8, y: u8) -> bool { (x as u16 + y as u16) % 3 == 0 }
-        fn mask_5(x: u8, y: u8) -> bool { ((x as f32 / 2.).floor() + (y as f32 / 2.).floor()) as u8 % 2 == 0 }
-        fn mask_6(x: u8, y: u8) -> bool { ((x as u16 * y as u16) % 2) + ((x as u16 * y as u16) % 3) == 0 }
-        fn mask_7(x: u8, y: u8) -> bool { (((x as u16 * y as u16) % 2) + ((x as u16 * y as u16) % 3)) % 2 == 0 }
-        fn mask_8(x: u8, y: u8) -> bool { (((x as u16 + y as u16) % 2) + ((x as u16 * y as u16) % 3)) % 2 == 0 }
+        penalties
+    }
 
-        let Matrix(matrix) = self;
+    // same as `penalties`, every mask is evaluated on its own copy in a thread
+    #[cfg(feature = "parallel")]
+    fn penalties_parallel(&self, ec_level: usize) -> [[u32; 4]; 8] {
+        use std::thread;
 
-        let mut handles = vec![];
-        for mask in [mask_1, mask_2, mask_3, mask_4, mask_5, mask_6, mask_7, mask_8].iter() {
-            let mut matrix = matrix.clone();
-            let handle = thread::spawn(move || {
-                let edge = matrix.len();
-                for y in 0..edge {
-                    for x in 0..edge {
-                        match matrix[y][x] {
-                            0 if mask(x as u8, y as u8) => matrix[y][x] = 1,
-                            1 if mask(x as u8, y as u8) => matrix[y][x] = 0,
-                            _ => continue,
-                        }
-                    }
-                }
+        let mut penalties = [[0; 4]; 8];
+        thread::scope(|scope| {
+            let handles = (0..8)
+                .map(|mask| scope.spawn(move || self.clone().mask_penalties(ec_level, mask)))
+                .collect::<Vec<_>>();
 
-                let matrix = Arc::new(matrix);
-                let mut handles = vec![];
-                for eval_condition in [
-                    Matrix::eval_condition_1,
-                    Matrix::eval_condition_2,
-                    Matrix::eval_condition_3,
-                    Matrix::eval_condition_4,
-                ].iter() {
-                    let matrix = matrix.clone();
-                    handles.push(thread::spawn(move || eval_condition(&matrix)))
-                }
+            for (penalties, handle) in penalties.iter_mut().zip(handles) { *penalties = handle.join().unwrap(); }
+        });
 
-                (
-                    handles.into_iter()
-                        .map(|handle| handle.join().unwrap())
-                        .sum::<u32>(),
-                    Matrix(Arc::try_unwrap(matrix).unwrap())
-                )
-            });
+        penalties
+    }
 
-            handles.push(handle);
-        }
+    // the mask with the lowest total penalty, the first one wins a tie
+    pub(crate) fn best_mask(penalties: &[[u32; 4]; 8]) -> usize {
+        (0..8).min_by_key(|&mask| penalties[mask].iter().sum::<u32>()).unwrap()
+    }
 
-        handles.into_iter()
-            .map(|handle| handle.join().unwrap())
-            .min_by_key(|&(penalty, _)| penalty)
-            .unwrap()
-            .1
+    pub(crate) fn data_mask(&mut self, ec_level: usize, mask: usize) -> &mut Matrix {
+        Matrix::apply_mask(&mut self.0, MASKS[mask]);
+
+        self.add_format_info(ec_level, mask)
     }
 
-    pub fn new(data: &Vec<u8>, version: usize) -> Matrix {
-        let mut matrix = Matrix({
-            let size = version * 4 + 17;
-            let mut matrix = vec![];
-            let mut row = vec![];
+    // mask reference read back from the format information as `decode` reads it, light on dark and mirror image
+    // symbols included, `None` if it can't be read
+    pub fn mask(&self) -> Option<usize> { crate::decoder::orient(self).ok().map(|(_, (_, mask), _, _)| mask) }
 
-            row.resize(size, 5);
-            matrix.resize(size, row);
+    // all modules are unused
+    pub(crate) fn blank(version: usize) -> Matrix {
+        let size = version * 4 + 17;
+        let mut matrix = vec![];
+        let mut row = vec![];
 
-            matrix
-        });
+        row.resize(size, 5);
+        matrix.resize(size, row);
 
-        matrix
-            .add_finder_patterns()
+        Matrix(matrix)
+    }
+
+    // function patterns, reserved areas and version information
+    //
+    // version: index from 1
+    pub(crate) fn with_function_patterns(&mut self, version: usize) -> &mut Matrix {
+        self.add_finder_patterns()
             .add_separators()
             .add_alignment_patterns(version)
             .add_timing_patterns()
             .add_dark_module_and_reserved_areas(version)
-            .place_data(data)
-            .data_mask()
+            .add_version_info(version)
+    }
+
+    // version: index from 1
+    // ec_level: same as `Encoder`
+    // mask: the one with the lowest penalty if `None`
+    pub(crate) fn new(data: &[u8], version: usize, ec_level: usize, mask: Option<usize>) -> Matrix {
+        let mut matrix = Matrix::blank(version);
+        matrix.with_function_patterns(version).place_data(data);
+
+        #[cfg(feature = "parallel")]
+        let mask = mask.unwrap_or_else(|| Matrix::best_mask(&matrix.penalties_parallel(ec_level)));
+        #[cfg(not(feature = "parallel"))]
+        let mask = mask.unwrap_or_else(|| Matrix::best_mask(&matrix.penalties(ec_level)));
+
+        matrix.data_mask(ec_level, mask);
+
+        matrix
     }
 }
 
@@ -672,15 +829,15 @@ fn test_add_dark_module_and_reserved_areas() {
             .join("\n"),
         [
             "\
-            ■■■■■■■□                          ○○○□■■■■■■■\n\
-            ■□□□□□■□                          ○○○□■□□□□□■\n\
-            ■□■■■□■□                          ○○○□■□■■■□■\n\
-            ■□■■■□■□                          ○○○□■□■■■□■\n\
-            ■□■■■□■□            ■■■■■         ○○○□■□■■■□■\n\
-            ■□□□□□■□            ■□□□■         ○○○□■□□□□□■\n\
+            ■■■■■■■□○                         ○○○□■■■■■■■\n\
+            ■□□□□□■□○                         ○○○□■□□□□□■\n\
+            ■□■■■□■□○                         ○○○□■□■■■□■\n\
+            ■□■■■□■□○                         ○○○□■□■■■□■\n\
+            ■□■■■□■□○           ■■■■■         ○○○□■□■■■□■\n\
+            ■□□□□□■□○           ■□□□■         ○○○□■□□□□□■\n\
             ■■■■■■■□■□■□■□■□■□■□■□■□■□■□■□■□■□■□■□■■■■■■■\n\
-            □□□□□□□□            ■□□□■            □□□□□□□□\n",
-            "      ■             ■■■■■                    \n",
+            □□□□□□□□○           ■□□□■            □□□□□□□□\n",
+            "○○○○○○■○○           ■■■■■            ○○○○○○○○\n",
             "      □                                      \n",
             "      ■                                      \n",
             "      □                                      \n",
@@ -710,13 +867,13 @@ fn test_add_dark_module_and_reserved_areas() {
             "○○○○○○□                                      \n",
             "○○○○○○■             ■■■■■           ■■■■■    \n\
             □□□□□□□□■           ■□□□■           ■□□□■    \n\
-            ■■■■■■■□            ■□■□■           ■□■□■    \n\
-            ■□□□□□■□            ■□□□■           ■□□□■    \n\
-            ■□■■■□■□            ■■■■■           ■■■■■    \n\
-            ■□■■■□■□                                     \n\
-            ■□■■■□■□                                     \n\
-            ■□□□□□■□                                     \n\
-            ■■■■■■■□                                     "
+            ■■■■■■■□○           ■□■□■           ■□■□■    \n\
+            ■□□□□□■□○           ■□□□■           ■□□□■    \n\
+            ■□■■■□■□○           ■■■■■           ■■■■■    \n\
+            ■□■■■□■□○                                    \n\
+            ■□■■■□■□○                                    \n\
+            ■□□□□□■□○                                    \n\
+            ■■■■■■■□○                                    "
         ].join("")
             .to_string()
     );
@@ -736,7 +893,7 @@ fn test_place_data() {
 
     let mut data = vec![];
     for _ in 0..18 { data.extend_from_slice(&[1, 0, 1, 0, 0, 0, 0, 1, 1, 1, 0, 1, 0, 1, 0, 1, 0, 1, 1]); }
-    for _ in 0..17 { data.push(0); }
+    data.extend_from_slice(&[0; 17]);
 
     matrix
         .add_finder_patterns()
@@ -822,3 +979,41 @@ fn test_eval_condition() {
     assert_eq!(Matrix::eval_condition_3(&matrix), 80);
     assert_eq!(Matrix::eval_condition_4(&matrix), 0);
 }
+
+#[test]
+fn test_format_and_version_info() {
+    use crate::encoder::qrcode_info::{format_bits, version_bits};
+
+    let mut matrix = Matrix::blank(7);
+    matrix.with_function_patterns(7).add_format_info(2, 5);
+
+    assert_eq!(matrix.format_info(), [format_bits(2, 5); 2]);
+    assert_eq!(matrix.mask(), Some(5));
+    // the dark module is kept
+    assert_eq!(matrix.0[45 - 8][8], 3);
+
+    // bit 0 ~ 2 in the first row of the top right block, bit 17 at the bottom right corner of the bottom left one
+    let bits = version_bits(7);
+    assert_eq!(matrix.0[0][34..37], [2 + (bits & 1) as u8, 2 + (bits >> 1 & 1) as u8, 2 + (bits >> 2 & 1) as u8]);
+    assert_eq!(matrix.0[36][5], 2 + (bits >> 17 & 1) as u8);
+    // nothing for version 1 ~ 6
+    assert!(Matrix::blank(6).with_function_patterns(6).0.iter().flatten().any(|&module| module == 4));
+}
+
+#[test]
+fn test_transforms() {
+    let matrix = Matrix(vec![vec![3, 0, 1], vec![2, 1, 0], vec![5, 0, 0]]);
+
+    assert_eq!(matrix.inverted().0, vec![vec![2, 1, 0], vec![3, 0, 1], vec![5, 1, 1]]);
+    assert_eq!(matrix.transposed().0, vec![vec![3, 2, 5], vec![0, 1, 0], vec![1, 0, 0]]);
+    assert_eq!(matrix.mirrored().0, vec![vec![1, 0, 3], vec![0, 1, 2], vec![0, 0, 5]]);
+    assert_eq!(matrix.transposed().transposed(), matrix);
+    assert!(matrix.inverted().is_function(0, 1));
+
+    // the mask reads back in every orientation `decode` reads, not from modules without a symbol
+    let matrix = crate::encoder::Encoder::new().mask(2).encode("HELLO WORLD").unwrap().as_matrix();
+    for symbol in [matrix.inverted(), matrix.mirrored(), matrix.transposed(), matrix.inverted().mirrored()].iter() {
+        assert_eq!(symbol.mask(), Some(2));
+    }
+    assert_eq!(Matrix(vec![vec![0; 21]; 21]).mask(), None);
+}
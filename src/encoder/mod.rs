@@ -1,13 +1,43 @@
+use crate::prelude::*;
+
+use crate::decoder::Image;
+
+#[doc(hidden)]
+pub mod bench;
+mod batch;
 mod bits;
+pub mod capacity;
+#[cfg(feature = "serde")]
+mod compact;
+mod config;
+mod deflate;
 mod matrix;
 mod mode;
 mod encode;
-mod qrcode_info;
-mod resolve;
+mod error;
+mod explain;
+pub(crate) mod halftone;
+mod layers;
+mod logo;
+pub(crate) mod qrcode_info;
+mod region;
+pub(crate) mod resolve;
+mod segment;
+mod workspace;
 
-use self::mode::Mode;
+pub use self::{
+    config::Config,
+    error::Error,
+    explain::{Block, Explanation},
+    logo::{Logo, LogoShape},
+    matrix::Matrix,
+    mode::Mode,
+    region::{Region, RegionMap},
+    segment::Segment,
+    workspace::Workspace,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Encoder {
     data: Vec<u8>,
 
@@ -28,6 +58,40 @@ pub struct Encoder {
     //         10 ~ 26 -> 1
     //         27 ~ 40 -> 2
     version: usize,
+
+    // mask which matched the `halftone` image best, picked on every encode like the mode and version
+    halftone_mask: Option<usize>,
+
+    // range of auto detected version, index from 0
+    min_version: usize,
+    max_version: usize,
+
+    // raise ec level as high as the data still fits after version is fixed
+    boost_ec: bool,
+    // choose the highest ec level which fits in the version range, then the smallest version for it
+    prefer_ec: bool,
+
+    // invalid setting, returned by `encode`
+    error: Option<Error>,
+
+    // (mode, version, ec_level) set by the builder, detection starts from them on every encode
+    requested: (Mode, usize, usize),
+
+    // threads of `encode_batch`, 0 -> one per available core
+    workers: usize,
+
+    // mask reference 0 ~ 7, the one with the lowest penalty if `None`
+    mask: Option<usize>,
+
+    // split the message into segments in different modes if it takes fewer bits, only if mode is detected
+    split: bool,
+
+    // logo which every block must be able to correct, and whether a higher ec level is taken if the requested one
+    // can't
+    logo: Option<(Logo, bool)>,
+
+    // grayscale image which pad codewords follow
+    halftone: Option<Image>,
 }
 
 impl Encoder {
@@ -37,37 +101,165 @@ impl Encoder {
             ec_level: 0,
             mode: Mode::Unknown,
             version: 255,
+            min_version: 0,
+            max_version: 39,
+            boost_ec: false,
+            prefer_ec: false,
+            error: None,
+            requested: (Mode::Unknown, 255, 0),
+            workers: 1,
+            mask: None,
+            split: false,
+            logo: None,
+            halftone: None,
+            halftone_mask: None,
         }
     }
 
     pub fn mode(mut self, mode: &str) -> Encoder {
-        self.mode = match mode {
+        let mode = match mode {
             "Numeric" => Mode::Numeric,
             "Alphanumeric" => Mode::Alphanumeric,
             "Byte" => Mode::Byte,
             "Kanji" => Mode::Kanji,
             "Chinese" => Mode::Chinese,
-            _ => panic!()
+            _ => {
+                self.error = Some(Error::InvalidMode(mode.to_string()));
+
+                return self;
+            }
         };
+        self.mode = mode;
+        self.requested.0 = mode;
 
         self
     }
 
     pub fn version(mut self, version: usize) -> Encoder {
-        self.version = version - 1; // index from 0
+        match check_version(version) {
+            Ok(version) => {
+                self.version = version;
+                self.requested.1 = version;
+            }
+            Err(e) => self.error = Some(e)
+        }
+
+        self
+    }
+
+    pub fn min_version(mut self, version: usize) -> Encoder {
+        match check_version(version) {
+            Ok(version) => self.min_version = version,
+            Err(e) => self.error = Some(e)
+        }
+
+        self
+    }
+
+    pub fn max_version(mut self, version: usize) -> Encoder {
+        match check_version(version) {
+            Ok(version) => self.max_version = version,
+            Err(e) => self.error = Some(e)
+        }
+
+        self
+    }
+
+    // the largest version whose size is no more than `modules` x `modules`
+    pub fn max_size(mut self, modules: usize) -> Encoder {
+        if modules < 21 {
+            self.error = Some(Error::InvalidSize(modules));
+
+            return self;
+        }
+
+        self.max_version(((modules - 17) / 4).min(40))
+    }
+
+    pub fn boost_ec(mut self, boost_ec: bool) -> Encoder {
+        self.boost_ec = boost_ec;
+
+        self
+    }
+
+    pub fn prefer_ec(mut self, prefer_ec: bool) -> Encoder {
+        self.prefer_ec = prefer_ec;
+
+        self
+    }
+
+    pub fn split(mut self, split: bool) -> Encoder {
+        self.split = split;
+
+        self
+    }
+
+    // `Error::LogoTooLarge` if a block can't correct the codewords under `logo` in the requested ec level, or in any
+    // higher one which fits if `raise_ec` is set
+    pub fn logo(mut self, logo: &Logo, raise_ec: bool) -> Encoder {
+        self.logo = Some((logo.clone(), raise_ec));
+
+        self
+    }
+
+    // pad codewords and the mask are picked to show the image, as drawn by `Renderer::halftone`, a mask set by `mask`
+    // is kept; `encode_into` keeps the standard padding
+    pub fn halftone(mut self, image: &Image) -> Encoder {
+        if image.width() == 0 || image.height() == 0 {
+            self.error = Some(Error::EmptyImage);
+        } else {
+            self.halftone = Some(image.clone());
+        }
+
+        self
+    }
+
+    // ignored without the `parallel` feature
+    pub fn workers(mut self, workers: usize) -> Encoder {
+        self.workers = workers;
+
+        self
+    }
+
+    pub fn mask(mut self, mask: usize) -> Encoder {
+        if mask < 8 { self.mask = Some(mask); } else { self.error = Some(Error::InvalidMask(mask)); }
 
         self
     }
 
     pub fn ec_level(mut self, ec_level: &str) -> Encoder {
-        self.ec_level = match ec_level {
-            "L" => 0,
-            "M" => 1,
-            "Q" => 2,
-            "H" => 3,
-            _ => panic!()
-        };
+        match parse_ec_level(ec_level) {
+            Ok(ec_level) => {
+                self.ec_level = ec_level;
+                self.requested.2 = ec_level;
+            }
+            Err(e) => self.error = Some(e)
+        }
 
         self
     }
-}
\ No newline at end of file
+}
+
+impl Default for Encoder {
+    fn default() -> Encoder { Encoder::new() }
+}
+
+// version: index from 1 -> index from 0
+fn check_version(version: usize) -> Result<usize, Error> {
+    if version == 0 || version > 40 { Err(Error::InvalidVersion(version)) } else { Ok(version - 1) }
+}
+
+// ec_levels:
+//     L -> 0
+//     M -> 1
+//     Q -> 2
+//     H -> 3
+fn parse_ec_level(ec_level: &str) -> Result<usize, Error> {
+    match ec_level {
+        "L" => Ok(0),
+        "M" => Ok(1),
+        "Q" => Ok(2),
+        "H" => Ok(3),
+        _ => Err(Error::InvalidEcLevel(ec_level.to_string()))
+    }
+}
@@ -1,4 +1,5 @@
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     Unknown,
     Numeric,
@@ -10,7 +11,7 @@ pub enum Mode {
 }
 
 impl Mode {
-    pub fn to_usize(&self) -> usize {
+    pub(crate) fn to_usize(self) -> usize {
         match self {
             Mode::Numeric => 0,
             Mode::Alphanumeric => 1,
@@ -21,20 +22,38 @@ impl Mode {
         }
     }
 
-    pub fn  not_support(&self, c: char) -> bool {
+    pub(crate) fn not_support(&self, c: char) -> bool {
         match self {
-            Mode::Numeric => match c {
-                '0'...'9' => false,
-                _ => true
-            }
-            Mode::Alphanumeric => if super::qrcode_info::alphanumeric_table(c as u8) == 0 { true } else { false }
-            Mode::Byte => match c as u8 {
-                255 => false,
-                _ => true
-            }
-            Mode::Kanji => c < '\u{0800}' || c > '\u{4e00}',   // TODO range might not accurate
-            Mode::Chinese => c < '\u{4e00}' || c > '\u{9fa5}', // TODO range might not accurate
+            Mode::Numeric => !c.is_ascii_digit(),
+            Mode::Alphanumeric => !matches!(c, '0'..='9' | 'A'..='Z' | ' ' | '$' | '%' | '*' | '+' | '-' | '.' | '/' | ':'),
+            // ISO 8859-1
+            Mode::Byte => c as u32 > 0xff,
+            // only double-byte characters in 0x8140 ~ 0x9ffc and 0xe040 ~ 0xebbf can be encoded
+            Mode::Kanji => !matches!(shift_jis(c), Some(0x8140..=0x9ffc) | Some(0xe040..=0xebbf)),
+            Mode::Chinese => !('\u{4e00}'..='\u{9fa5}').contains(&c), // TODO range might not accurate
             _ => unreachable!()
         }
     }
-}
\ No newline at end of file
+}
+// Shift JIS value of a double-byte character, without allocation
+pub(crate) fn shift_jis(c: char) -> Option<u16> {
+    use encoding_rs::{EncoderResult, SHIFT_JIS};
+
+    let mut utf8 = [0; 4];
+    let mut kanji = [0; 4];
+    let (result, _, written) = SHIFT_JIS.new_encoder()
+        .encode_from_utf8_without_replacement(c.encode_utf8(&mut utf8), &mut kanji, true);
+
+    match (result, written) {
+        (EncoderResult::InputEmpty, 2) => Some(kanji[0] as u16 * 256 + kanji[1] as u16),
+        _ => None
+    }
+}
+
+#[test]
+fn test_shift_jis() {
+    assert_eq!(shift_jis('茗'), Some(0xe4aa));
+    assert_eq!(shift_jis('点'), Some(0x935f));
+    assert_eq!(shift_jis('a'), None);
+    assert_eq!(shift_jis('Ḥ'), None);
+}
@@ -118,60 +118,64 @@ pub const DATA_DISTRIBUTIONS: [[[u8; 3]; 4]; 40] = [
     [[19, 118, 6], [18, 47, 31], [34, 24, 34], [20, 15, 61]]
 ];
 
-lazy_static! {
-    pub static ref ALIGNMENT_PATTERN_LOCATIONS: [&'static [u8]; 39] = [
-        // version 2 ~ 6
-        &[6, 18],
-        &[6, 22],
-        &[6, 26],
-        &[6, 30],
-        &[6, 34],
-        // version 7 ~ 13
-        &[6, 22, 38],
-        &[6, 24, 42],
-        &[6, 26, 46],
-        &[6, 28, 50],
-        &[6, 30, 54],
-        &[6, 32, 58],
-        &[6, 34, 62],
-        // version 14 ~ 20
-        &[6, 26, 46, 66],
-        &[6, 26, 48, 60],
-        &[6, 26, 50, 74],
-        &[6, 30, 54, 78],
-        &[6, 30, 56, 82],
-        &[6, 30, 58, 86],
-        &[6, 34, 62, 90],
-        // version 21 ~ 27
-        &[6, 28, 50, 72, 94],
-        &[6, 26, 50, 74, 98],
-        &[6, 30, 54, 78, 102],
-        &[6, 28, 54, 80, 106],
-        &[6, 32, 58, 84, 110],
-        &[6, 30, 58, 86, 114],
-        &[6, 34, 62, 90, 118],
-        // version 28 ~ 34
-        &[6, 26, 50, 74, 98, 122],
-        &[6, 30, 54, 78, 102, 126],
-        &[6, 26, 52, 78, 104, 130],
-        &[6, 30, 56, 82, 108, 134],
-        &[6, 34, 60, 86, 112, 138],
-        &[6, 30, 58, 86, 114, 142],
-        &[6, 34, 62, 90, 118, 146],
-        // version 35 ~ 40
-        &[6, 30, 54, 78, 102, 126, 150],
-        &[6, 24, 50, 76, 102, 128, 154],
-        &[6, 28, 54, 80, 106, 132, 158],
-        &[6, 32, 58, 84, 110, 136, 162],
-        &[6, 26, 54, 82, 110, 138, 166],
-        &[6, 30, 58, 86, 114, 142, 170],
-    ];  
-}
+// Alignment Pattern Locations, row and column coordinates of the centers
+//
+// ALIGNMENT_PATTERN_LOCATIONS[version - 2] -> coordinates
+pub const ALIGNMENT_PATTERN_LOCATIONS: [&[u8]; 39] = [
+    // version 2 ~ 6
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    // version 7 ~ 13
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+    &[6, 30, 54],
+    &[6, 32, 58],
+    &[6, 34, 62],
+    // version 14 ~ 20
+    &[6, 26, 46, 66],
+    &[6, 26, 48, 70],
+    &[6, 26, 50, 74],
+    &[6, 30, 54, 78],
+    &[6, 30, 56, 82],
+    &[6, 30, 58, 86],
+    &[6, 34, 62, 90],
+    // version 21 ~ 27
+    &[6, 28, 50, 72, 94],
+    &[6, 26, 50, 74, 98],
+    &[6, 30, 54, 78, 102],
+    &[6, 28, 54, 80, 106],
+    &[6, 32, 58, 84, 110],
+    &[6, 30, 58, 86, 114],
+    &[6, 34, 62, 90, 118],
+    // version 28 ~ 34
+    &[6, 26, 50, 74, 98, 122],
+    &[6, 30, 54, 78, 102, 126],
+    &[6, 26, 52, 78, 104, 130],
+    &[6, 30, 56, 82, 108, 134],
+    &[6, 34, 60, 86, 112, 138],
+    &[6, 30, 58, 86, 114, 142],
+    &[6, 34, 62, 90, 118, 146],
+    // version 35 ~ 40
+    &[6, 30, 54, 78, 102, 126, 150],
+    &[6, 24, 50, 76, 102, 128, 154],
+    &[6, 28, 54, 80, 106, 132, 158],
+    &[6, 32, 58, 84, 110, 136, 162],
+    &[6, 26, 54, 82, 110, 138, 166],
+    &[6, 30, 58, 86, 114, 142, 170],
+];
+
+// ALPHANUMERIC_CHARS[alphanumeric_table(b)] = b
+pub const ALPHANUMERIC_CHARS: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
 
 pub fn alphanumeric_table(b: u8) -> u8 {
     match b {
-        b'0'...b'9' => b - 48, // 48 = b'0'
-        b'A'...b'Z' => b - 55, // 55 = b'A' - 10
+        b'0'..=b'9' => b - 48, // 48 = b'0'
+        b'A'..=b'Z' => b - 55, // 55 = b'A' - 10
         b' ' => 36,
         b'$' => 37,
         b'%' => 38,
@@ -185,11 +189,50 @@ pub fn alphanumeric_table(b: u8) -> u8 {
     }
 }
 
+// version: index from 0
 pub fn remainder_bits(version: usize) -> u8 {
-    match version {
-        2...6 => 7,
-        14...20 | 28...34 => 3,
-        21...27 => 4,
+    match version + 1 {
+        2..=6 => 7,
+        14..=20 | 28..=34 => 3,
+        21..=27 => 4,
         _ => 0
     }
 }
+
+// 15 bits format information: 2 bits ec level + 3 bits mask reference + 10 bits BCH code, masked by 0x5412
+//
+// ec_level: same as above
+pub fn format_bits(ec_level: usize, mask: usize) -> u16 {
+    // L -> 01, M -> 00, Q -> 11, H -> 10
+    let data = ([1, 0, 3, 2][ec_level] << 3 | mask) as u32;
+    // generator x^10 + x^8 + x^5 + x^4 + x^2 + x + 1
+    let remainder = (0..10).fold(data, |remainder, _| (remainder << 1) ^ ((remainder >> 9) * 0x537));
+
+    ((data << 10 | remainder) ^ 0x5412) as u16
+}
+
+// 18 bits version information: 6 bits version + 12 bits BCH code
+//
+// version: index from 1, 7 ~ 40
+pub fn version_bits(version: usize) -> u32 {
+    let data = version as u32;
+    // generator x^12 + x^11 + x^10 + x^9 + x^8 + x^5 + x^2 + 1
+    let remainder = (0..12).fold(data, |remainder, _| (remainder << 1) ^ ((remainder >> 11) * 0x1f25));
+
+    data << 12 | remainder
+}
+
+#[test]
+fn test_format_bits() {
+    assert_eq!(format_bits(0, 0), 0b111011111000100);
+    assert_eq!(format_bits(1, 0), 0b101010000010010);
+    assert_eq!(format_bits(2, 7), 0b010101111101101);
+    assert_eq!(format_bits(3, 4), 0b000011101100010);
+}
+
+#[test]
+fn test_version_bits() {
+    assert_eq!(version_bits(7), 0b000111110010010100);
+    assert_eq!(version_bits(21), 0b010101011010000011);
+    assert_eq!(version_bits(40), 0b101000110001101001);
+}
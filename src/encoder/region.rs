@@ -0,0 +1,142 @@
+use crate::prelude::*;
+
+use super::{
+    Encoder,
+    matrix::Matrix,
+};
+
+// role of a module
+//
+// codeword: index in the interleaved sequence of data codewords or ec codewords
+// bit: 0 ~ 7, from the most significant bit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+    Finder,
+    Separator,
+    Timing,
+    Alignment,
+    Format,
+    Version,
+    DarkModule,
+    Data { codeword: usize, bit: u8 },
+    Ec { codeword: usize, bit: u8 },
+    Remainder,
+}
+
+#[derive(Debug)]
+pub struct RegionMap {
+    regions: Vec<Vec<Region>>,
+    // coordinates(x, y) of data modules, in the order of data placement
+    placement: Vec<(usize, usize)>,
+}
+
+impl RegionMap {
+    // version: index from 1
+    // ec_level: same as `Encoder`
+    pub(crate) fn new(version: usize, ec_level: usize) -> RegionMap {
+        use super::qrcode_info::{CAPACITIES, DATA_DISTRIBUTIONS, EC_CW_PER_BLOCKS};
+
+        let mut matrix = Matrix::blank(version);
+        let len = matrix.width();
+        let mut regions = vec![vec![None; len]; len];
+
+        // replay `Matrix::new`, the first pattern which takes a module owns it
+        {
+            let mut mark = |matrix: &Matrix, region: &dyn Fn(usize, usize) -> Region| {
+                for (y, row) in regions.iter_mut().enumerate() {
+                    for (x, module) in row.iter_mut().enumerate() {
+                        if module.is_none() && matrix.0[y][x] != 5 { *module = Some(region(x, y)); }
+                    }
+                }
+            };
+
+            mark(matrix.add_finder_patterns(), &|_, _| Region::Finder);
+            mark(matrix.add_separators(), &|_, _| Region::Separator);
+            mark(matrix.add_alignment_patterns(version), &|_, _| Region::Alignment);
+            mark(matrix.add_timing_patterns(), &|_, _| Region::Timing);
+            mark(matrix.add_dark_module_and_reserved_areas(version), &|x, y| {
+                let fix = len - 8;
+
+                if (x, y) == (8, fix) {
+                    Region::DarkModule
+                } else if version > 6 && (x < 6 || y < 6) && (fix - 3..fix).contains(&x.max(y)) {
+                    Region::Version
+                } else {
+                    Region::Format
+                }
+            });
+        }
+
+        let data_distribution = DATA_DISTRIBUTIONS[version - 1][ec_level];
+        let data_cws = CAPACITIES[version - 1][ec_level] as usize / 8;
+        let ec_cws = EC_CW_PER_BLOCKS[version - 1][ec_level] as usize
+            * (data_distribution[0] + data_distribution[2]) as usize;
+
+        let placement = matrix.placement();
+        for (i, &(x, y)) in placement.iter().enumerate() {
+            let (codeword, bit) = (i / 8, (i % 8) as u8);
+
+            regions[y][x] = Some(if codeword < data_cws {
+                Region::Data { codeword, bit }
+            } else if codeword < data_cws + ec_cws {
+                Region::Ec { codeword: codeword - data_cws, bit }
+            } else {
+                Region::Remainder
+            });
+        }
+
+        RegionMap {
+            regions: regions.into_iter()
+                .map(|row| row.into_iter().map(Option::unwrap).collect())
+                .collect(),
+            placement,
+        }
+    }
+
+    pub fn width(&self) -> usize { self.regions.len() }
+
+    pub fn get(&self, x: usize, y: usize) -> Region { self.regions[y][x] }
+
+    pub fn placement(&self) -> &[(usize, usize)] { &self.placement }
+}
+
+impl Encoder {
+    // regions of the last encoded symbol, `None` before the first encode
+    pub fn region_map(&self) -> Option<RegionMap> {
+        if self.version > 39 { return None; }
+
+        Some(RegionMap::new(self.version + 1, self.ec_level))
+    }
+}
+
+#[test]
+fn test_region_map() {
+    use super::qrcode_info::remainder_bits;
+
+    for version in 1..=40 {
+        let region_map = RegionMap::new(version, 0);
+        let len = version * 4 + 17;
+
+        // every module which is left for data is covered by codewords or remainder bits
+        let remainders = region_map.regions.iter()
+            .flatten()
+            .filter(|&&region| region == Region::Remainder)
+            .count();
+        assert_eq!(remainders, remainder_bits(version - 1) as usize);
+
+        assert_eq!(region_map.get(8, len - 8), Region::DarkModule);
+        assert_eq!(region_map.get(6, 10), Region::Timing);
+        assert_eq!(region_map.get(8, 0), Region::Format);
+        assert_eq!(region_map.get(len - 1, 8), Region::Format);
+        assert_eq!(region_map.get(len - 9, 0) == Region::Version, version > 6);
+        assert_eq!(region_map.get(0, len - 9) == Region::Version, version > 6);
+        assert_eq!(region_map.get(len - 1, len - 1), Region::Data { codeword: 0, bit: 0 });
+        assert_eq!(region_map.get(len - 2, len - 1), Region::Data { codeword: 0, bit: 1 });
+    }
+
+    // no version is resolved before an encode
+    let mut encoder = Encoder::new();
+    assert!(encoder.region_map().is_none());
+    encoder.encode("01234567").unwrap();
+    assert_eq!(encoder.region_map().unwrap().width(), 21);
+}
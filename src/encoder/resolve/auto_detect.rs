@@ -1,86 +1,202 @@
 use crate::encoder::{
     Encoder,
-    mode::Mode::*,
+    Error,
+    Segment,
+    mode::{Mode, Mode::*},
 };
 
-impl Encoder {
-    pub fn mode_detect(&mut self, message: &str) {
-        if let Unknown = self.mode { () } else { return; }
-
-        // reverse the order of Mode, we can pop() the most suitable mode at the end of the loop
-        // modes[2] -> Byte mode(ISO 8859-1)
-        // Byte mode(UTF-8) should be modes[0], but ignore it
-        let mut modes = vec![Chinese, Kanji, Byte, Alphanumeric, Numeric];
-
-        // check every char
-        for c in message.chars() {
-            let mut fix = 0;
-            // check every mode(remained) except Byte(UTF-8)
-            for i in 0..modes.len() {
-                let i = i - fix;
-                if modes[i].not_support(c) {
-                    modes.remove(i);
-                    fix += 1;
-
-                    // use Byte(UTF-8) mode
-                    if fix == modes.len() {
-                        self.mode = Byte;
-                        return;
-                    }
-                }
-            }
+// the most suitable mode for the whole message
+pub fn detect_mode(message: &str) -> Mode {
+    // reverse the order of Mode, the last supported mode is the most suitable one
+    // modes[1] -> Byte mode(ISO 8859-1)
+    // Byte mode(UTF-8) is the fallback if none of them remained
+    // Chinese mode is skipped until `chinese_encode` is done
+    let modes = [Kanji, Byte, Alphanumeric, Numeric];
+    let mut supported = [true; 4];
+
+    // check every char
+    for c in message.chars() {
+        for (mode, supported) in modes.iter().zip(supported.iter_mut()) {
+            if *supported && mode.not_support(c) { *supported = false; }
         }
 
-        self.mode = modes.pop().unwrap();
+        // use Byte(UTF-8) mode
+        if !supported.contains(&true) { return Byte; }
+    }
+
+    modes.iter()
+        .zip(supported.iter())
+        .rev()
+        .find(|&(_, &supported)| supported)
+        .map_or(Byte, |(&mode, _)| mode)
+}
+
+impl Encoder {
+    pub(crate) fn mode_detect(&mut self, message: &str) {
+        if self.mode != Unknown { return; }
+
+        self.mode = detect_mode(message);
     }
 
-    pub fn version_detect(&mut self, len: u16) -> usize {
-        use crate::encoder::qrcode_info::INDICATORS;
+    pub(crate) fn version_detect(&mut self, len: usize) -> Result<usize, Error> {
+        use crate::encoder::capacity::{data_bits, indicator_bits};
 
-        if self.version == 255 {
-            use crate::encoder::qrcode_info::CAPACITIES;
+        let mode = self.mode;
+        let data_bits = data_bits(mode, len);
+        self.fit_version(|version| 4 + indicator_bits(mode, version) + data_bits)?;
 
-            let mut total_bits = 4 + match self.mode {
-                Numeric => {
-                    10 * (len / 3) + match len % 3 {
-                        0 => 0,
-                        1 => 4,
-                        2 => 7,
-                        _ => panic!()
-                    }
-                }
-                Alphanumeric => 11 * (len >> 1) + match len & 1 {
-                    0 => 0,
-                    1 => 6,
-                    _ => panic!()
-                },
-                Byte => 8 * len,
-                Kanji | Chinese => 13 * len,
-                _ => panic!() // TODO
-            };
-
-            // 1 - 9, 10 - 26, 27 - 40 versions have same indicator
-            for (&(start, end), indicators) in [(0usize, 8usize), (9, 25), (26, 39)].iter().zip(INDICATORS.iter()) {
-                total_bits += indicators[self.mode.to_usize()] as u16;
-
-                if total_bits < CAPACITIES[end][self.ec_level] {
-                    for version in start..=end {
-                        if total_bits < CAPACITIES[version][self.ec_level] {
-                            self.version = version;
-                            return indicators[self.mode.to_usize()] as usize;
-                        }
-                    }
-                }
+        Ok(indicator_bits(mode, self.version + 1))
+    }
+
+    // version(index from 1) which `encode` would choose for `message`, without encoding it
+    pub(crate) fn detect_version(&self, message: &str) -> Result<usize, Error> {
+        let mut encoder = self.clone();
+        if encoder.detect_split(message)?.is_none() { encoder.detect(message)?; }
+
+        Ok(encoder.version + 1)
+    }
+
+    // version(index from 1) which `encode_segments` would choose for `segments`
+    pub(crate) fn segments_version(&self, segments: &[Segment]) -> Result<usize, Error> {
+        let mut encoder = self.clone();
+        encoder.encode_segments(segments)?;
+
+        Ok(encoder.version + 1)
+    }
+
+    // fix version and ec level for data of `bits(version)` bits, version index from 1
+    pub(crate) fn fit_version<F: Fn(usize) -> usize>(&mut self, bits: F) -> Result<(), Error> {
+        use crate::encoder::capacity::{capacity_bits, fit};
+
+        if let Some(e) = &self.error { return Err(e.clone()); }
+        if self.min_version > self.max_version {
+            return Err(Error::InvalidVersionRange(self.min_version + 1, self.max_version + 1));
+        }
+
+        // the largest version which is allowed
+        let version = if self.version == 255 { self.max_version + 1 } else { self.version + 1 };
+        if bits(version) > capacity_bits(version, self.ec_level) {
+            return Err(Error::DataTooLong {
+                bits: bits(version),
+                capacity: capacity_bits(version, self.ec_level),
+            });
+        }
+
+        let versions = self.min_version + 1..=self.max_version + 1;
+        let detected = self.version == 255;
+        if detected {
+            if self.prefer_ec {
+                // the requested ec level fits at least
+                self.ec_level = (self.ec_level..4)
+                    .rev()
+                    .find(|&ec_level| fit(ec_level, versions.clone(), &bits).is_some())
+                    .unwrap();
             }
 
-            unreachable!()
-        } else {
-            INDICATORS[match self.version {
-                0...8 => 0,
-                9...25 => 1,
-                26...39 => 2,
-                _ => panic!()
-            }][self.mode.to_usize()] as usize
+            self.version = fit(self.ec_level, versions.clone(), &bits).unwrap() - 1;
+        }
+
+        if self.boost_ec {
+            let bits = bits(self.version + 1);
+            while self.ec_level < 3 && bits <= capacity_bits(self.version + 1, self.ec_level + 1) { self.ec_level += 1; }
+        }
+
+        if let Some((logo, raise_ec)) = &self.logo {
+            while let Err((codewords, budget)) = logo.fits(self.version + 1, self.ec_level) {
+                // the next ec level which the data fits in, in a larger version if it is detected
+                let version = self.version + 1;
+                let fits = |ec_level| if detected {
+                    fit(ec_level, versions.clone(), &bits)
+                } else {
+                    Some(version).filter(|&version| bits(version) <= capacity_bits(version, ec_level))
+                };
+                let next = (self.ec_level + 1..4)
+                    .filter(|_| *raise_ec)
+                    .find_map(|ec_level| fits(ec_level).map(|version| (version, ec_level)));
+
+                match next {
+                    Some((version, ec_level)) => (self.version, self.ec_level) = (version - 1, ec_level),
+                    None => return Err(Error::LogoTooLarge { codewords, budget }),
+                }
+            }
         }
+
+        Ok(())
     }
 }
+
+#[test]
+fn test_mode_detect() {
+    for &(message, mode) in [
+        ("01234567", Numeric),
+        ("HELLO WORLD", Alphanumeric),
+        ("hello, world", Byte),
+        ("ハロー・ワールド", Kanji),
+        ("Ḥello", Byte),
+    ].iter() {
+        let mut encoder = Encoder::new();
+        encoder.mode_detect(message);
+
+        assert_eq!(encoder.mode, mode);
+    }
+}
+
+#[test]
+fn test_version_detect() {
+    // 4 + 10 + 10 * 2 + 7 = 41 bits
+    let mut encoder = Encoder::new().ec_level("M");
+    encoder.detect("01234567").unwrap();
+    assert_eq!((encoder.version, encoder.ec_level), (0, 1));
+
+    let mut encoder = Encoder::new().ec_level("M").min_version(5);
+    encoder.detect("01234567").unwrap();
+    assert_eq!((encoder.version, encoder.ec_level), (4, 1));
+    assert_eq!(encoder.detect_version("01234567"), Ok(5));
+    assert_eq!(Encoder::new().split(true).detect_version("https://EXAMPLE.COM"), Ok(1));
+    assert_eq!(Encoder::new().detect_version("https://EXAMPLE.COM"), Ok(2));
+
+    // 1-Q holds 104 bits, 1-H holds 72 bits
+    let mut encoder = Encoder::new().ec_level("L").boost_ec(true);
+    encoder.detect("01234567").unwrap();
+    assert_eq!((encoder.version, encoder.ec_level), (0, 3));
+
+    let mut encoder = Encoder::new().ec_level("L").boost_ec(true);
+    encoder.detect(&"1".repeat(25)).unwrap();
+    assert_eq!((encoder.version, encoder.ec_level), (0, 2));
+
+    // pin the size and let ec level float, 4 + 8 + 8 * 40 = 332 bits, 3-M holds 352 bits, 3-Q holds 272 bits
+    let mut encoder = Encoder::new().min_version(3).max_version(3).boost_ec(true);
+    encoder.detect(&"a".repeat(40)).unwrap();
+    assert_eq!((encoder.version, encoder.ec_level), (2, 1));
+}
+
+#[test]
+fn test_version_constraints() {
+    // 33 x 33 modules -> version 4, 4 + 8 + 8 * 50 = 412 bits, 4-Q holds 384 bits, 4-M holds 512 bits
+    let mut encoder = Encoder::new().max_size(33).prefer_ec(true);
+    encoder.detect(&"a".repeat(50)).unwrap();
+    assert_eq!((encoder.version, encoder.ec_level), (3, 1));
+
+    // the smallest version for the highest ec level
+    let mut encoder = Encoder::new().max_size(33).prefer_ec(true);
+    encoder.detect("01234567").unwrap();
+    assert_eq!((encoder.version, encoder.ec_level), (0, 3));
+
+    assert_eq!(
+        Encoder::new().max_size(33).detect(&"a".repeat(100)),
+        Err(Error::DataTooLong { bits: 812, capacity: 640 })
+    );
+    assert_eq!(
+        Encoder::new().version(1).ec_level("H").detect(&"1".repeat(18)),
+        Err(Error::DataTooLong { bits: 74, capacity: 72 })
+    );
+    assert_eq!(Encoder::new().version(0).detect("1"), Err(Error::InvalidVersion(0)));
+    assert_eq!(Encoder::new().max_version(41).detect("1"), Err(Error::InvalidVersion(41)));
+    assert_eq!(Encoder::new().max_size(20).detect("1"), Err(Error::InvalidSize(20)));
+    assert_eq!(Encoder::new().ec_level("X").detect("1"), Err(Error::InvalidEcLevel("X".to_string())));
+    assert_eq!(Encoder::new().mode("Latin").detect("1"), Err(Error::InvalidMode("Latin".to_string())));
+    assert_eq!(
+        Encoder::new().min_version(10).max_version(5).detect("1"),
+        Err(Error::InvalidVersionRange(10, 5))
+    );
+}
@@ -1,133 +1,101 @@
+use crate::prelude::*;
+
 use crate::encoder::{
     Encoder,
     matrix::Matrix
 };
 
-fn gen_indexes(from: &mut usize, step: usize, count: u8) -> Vec<usize> {
-    let mut indexes = vec![];
-    for _ in 0..count {
-        indexes.push(*from);
-        *from += step;
-    }
+// split data codewords into blocks, group 2's blocks have one more codeword than group 1's
+fn split_blocks(data: &[u8], g1_blocks_num: usize, g1_cw_per_block: usize) -> impl Iterator<Item = &[u8]> {
+    let (g1, g2) = data.split_at(g1_blocks_num * g1_cw_per_block);
 
-    indexes
+    g1.chunks(g1_cw_per_block).chain(g2.chunks(g1_cw_per_block + 1))
 }
 
 #[test]
-fn test_gen_indexes() {
-    let g1_blocks_num = 2;
-    let g2_blocks_num = 2;
-    let g1_cw_per_block = 15;
-    let g2_cw_per_block = 16;
-
-    let indexes = {
-        let mut i = 0;
-        let mut indexes = gen_indexes(&mut i, g1_cw_per_block, g1_blocks_num);
-        indexes.extend_from_slice(gen_indexes(
-            &mut i,
-            g2_cw_per_block,
-            g2_blocks_num,
-        ).as_slice());
-
-        indexes
-    };
+fn test_split_blocks() {
+    let data = (0..62).collect::<Vec<u8>>();
+    let blocks = split_blocks(&data, 2, 15).collect::<Vec<&[u8]>>();
 
-    assert_eq!(indexes, vec![0, 15, 30, 46]);
+    assert_eq!(blocks.iter().map(|block| block.len()).collect::<Vec<usize>>(), vec![15, 15, 16, 16]);
+    assert_eq!(blocks.iter().map(|block| block[0]).collect::<Vec<u8>>(), vec![0, 15, 30, 46]);
 }
 
-impl Encoder {
-    pub fn interleave_with_ec(&mut self) -> &mut Encoder {
-        use std::thread;
-        use super::error_correct::error_correct;
-        use crate::encoder::qrcode_info::{DATA_DISTRIBUTIONS, EC_CW_PER_BLOCKS};
+// append ec codewords of every block to `codewords`, then write data codewords and ec codewords into `interleaved`
+// block by block in turn
+//
+// version: index from 0
+// ec_level: same as `Encoder`
+pub(crate) fn interleave_with_ec(
+    codewords: &mut Vec<u8>,
+    data_cws: usize,
+    version: usize,
+    ec_level: usize,
+    interleaved: &mut Vec<u8>,
+) {
+    use crate::{
+        encoder::qrcode_info::{DATA_DISTRIBUTIONS, EC_CW_PER_BLOCKS},
+        gf256::{reed_solomon, QR_CODE},
+    };
 
-        let ec_cw_per_blocks = EC_CW_PER_BLOCKS[self.version][self.ec_level];
-        let data_distribution = DATA_DISTRIBUTIONS[self.version][self.ec_level];
+    let ec_cw_per_block = EC_CW_PER_BLOCKS[version][ec_level] as usize;
+    let data_distribution = DATA_DISTRIBUTIONS[version][ec_level];
 
-        let g1_blocks_num = data_distribution[0];
-        let g2_blocks_num = data_distribution[2];
+    let g1_blocks_num = data_distribution[0] as usize;
+    let g1_cw_per_block = data_distribution[1] as usize;
+    let blocks_num = g1_blocks_num + data_distribution[2] as usize;
 
-        // smaller QR codes only consist of one block of data codewords
-        if g1_blocks_num + g2_blocks_num == 1 {
-            self.data.extend_from_slice(error_correct(
-                self.data.clone(),
-                ec_cw_per_blocks
-            ).as_slice());
+    let reed_solomon = reed_solomon(&QR_CODE, ec_cw_per_block, 0);
 
-            return self;
-        }
+    // ec codewords are appended block by block
+    codewords.resize(data_cws + blocks_num * ec_cw_per_block, 0);
+    let (data, ec) = codewords.split_at_mut(data_cws);
+    for (block, ec) in split_blocks(data, g1_blocks_num, g1_cw_per_block).zip(ec.chunks_mut(ec_cw_per_block)) {
+        reed_solomon.encode(block, ec);
+    }
+
+    interleaved.clear();
+
+    // smaller QR codes only consist of one block of data codewords
+    if blocks_num == 1 {
+        interleaved.extend_from_slice(codewords);
+
+        return;
+    }
 
-        let g1_cw_per_block = data_distribution[1] as usize;
-        let g2_cw_per_block = g1_cw_per_block + 1;
-
-        let data = self.data.clone();
-        let final_data = thread::spawn(move || {
-            let mut final_data = vec![];
-            let mut indexes = {
-                let mut i = 0;
-                let mut indexes = gen_indexes(&mut i, g1_cw_per_block, g1_blocks_num);
-
-                indexes.extend_from_slice(gen_indexes(
-                    &mut i,
-                    g2_cw_per_block,
-                    g2_blocks_num,
-                ).as_slice());
-
-                indexes
-            };
-
-            for _ in 0..g1_blocks_num {
-                for i in indexes.iter_mut() {
-                    final_data.push(data[*i]);
-                    *i += 1;
-                }
-            }
-
-            // g2_cw_per_block is always `1` greater than g1_cw_per_block
-            for i in indexes[g1_blocks_num as usize..].iter() { final_data.push(data[*i]); }
-
-            final_data
-        });
-
-        let mut final_ec_data = vec![];
-        {
-            let mut ec_data = vec![];
-            {
-                let g1_cw = g1_blocks_num as usize * g1_cw_per_block;
-                for chunk in self.data[..g1_cw].chunks(g1_cw_per_block) {
-                    ec_data.extend_from_slice(error_correct(
-                        chunk.to_vec(),
-                        ec_cw_per_blocks,
-                    ).as_slice())
-                }
-
-                for chunk in self.data[g1_cw..].chunks(g2_cw_per_block) {
-                    ec_data.extend_from_slice(error_correct(
-                        chunk.to_vec(),
-                        ec_cw_per_blocks,
-                    ).as_slice())
-                }
-            }
-
-            let mut indexes = gen_indexes(
-                &mut 0,
-                ec_cw_per_blocks as usize,
-                g1_blocks_num + g2_blocks_num,
-            );
-
-            for _ in 0..ec_cw_per_blocks {
-                for i in indexes.iter_mut() {
-                    final_ec_data.push(ec_data[*i]);
-                    *i += 1;
-                }
-            }
+    // g2_cw_per_block is always `1` greater than g1_cw_per_block
+    for i in 0..=g1_cw_per_block {
+        for block in split_blocks(data, g1_blocks_num, g1_cw_per_block) {
+            if let Some(&codeword) = block.get(i) { interleaved.push(codeword); }
         }
+    }
+
+    for i in 0..ec_cw_per_block {
+        for block in ec.chunks(ec_cw_per_block) { interleaved.push(block[i]); }
+    }
+}
+
+impl Encoder {
+    pub(crate) fn data_blocks(&self) -> Vec<&[u8]> {
+        use crate::encoder::qrcode_info::DATA_DISTRIBUTIONS;
 
-        self.data = final_data.join().unwrap();
-        self.data.extend_from_slice(final_ec_data.as_slice());
+        let data_distribution = DATA_DISTRIBUTIONS[self.version][self.ec_level];
+
+        split_blocks(&self.data, data_distribution[0] as usize, data_distribution[1] as usize).collect()
+    }
+
+    pub(crate) fn interleave_with_ec(&mut self) -> &mut Encoder {
+        let data_cws = self.data.len();
+        let mut final_data = Vec::with_capacity(data_cws);
+
+        interleave_with_ec(&mut self.data, data_cws, self.version, self.ec_level, &mut final_data);
+        self.data = final_data;
 
         self
     }
 
-    pub fn as_matrix(&self) -> Matrix { Matrix::new(&self.data, self.version) }
+    // `Matrix` index version from 1
+    pub fn as_matrix(&self) -> Matrix {
+        Matrix::new(&self.data, self.version + 1, self.ec_level, self.mask.or(self.halftone_mask))
+    }
 }
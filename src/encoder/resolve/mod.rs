@@ -1,3 +1,4 @@
-mod auto_detect;
-mod final_structure;
-mod error_correct;
\ No newline at end of file
+pub mod auto_detect;
+pub(crate) mod final_structure;
+pub mod error_correct;
+pub(crate) mod split;
\ No newline at end of file
@@ -0,0 +1,162 @@
+use core::ops::Range;
+
+use crate::prelude::*;
+
+use crate::encoder::{
+    capacity::indicator_bits,
+    mode::{Mode, Mode::*},
+};
+
+// states of the last segment:
+//     0 ~ 2 -> Numeric, characters % 3
+//     3 ~ 4 -> Alphanumeric, characters % 2
+//     5     -> Byte
+//     6     -> Kanji
+const STATES: [Mode; 7] = [Numeric, Numeric, Numeric, Alphanumeric, Alphanumeric, Byte, Kanji];
+const FIRST_STATES: [usize; 4] = [0, 3, 5, 6];
+
+// (state after `c`, bits of `c`) if `c` can follow `state` in the same segment
+fn next(state: usize, c: char) -> Option<(usize, usize)> {
+    if STATES[state] != Byte && STATES[state].not_support(c) { return None; }
+
+    // 10 bits per 3 digits -> 4 + 3 + 3, 11 bits per 2 characters -> 6 + 5
+    Some(match state {
+        0 => (1, 4),
+        1 => (2, 3),
+        2 => (0, 3),
+        3 => (4, 6),
+        4 => (3, 5),
+        5 => (5, 8 * c.len_utf8()),
+        _ => (6, 13),
+    })
+}
+
+// (mode, byte range in the message) of every segment
+pub(crate) type Split = Vec<(Mode, Range<usize>)>;
+
+// segments of `message` in the fewest bits for the versions whose character count indicators are as long as
+// `version`'s
+//
+// version: index from 1
+pub(crate) fn split(message: &str, version: usize) -> Split {
+    use super::auto_detect::detect_mode;
+
+    let chars = message.char_indices().collect::<Vec<(usize, char)>>();
+    if chars.is_empty() { return vec![(detect_mode(message), 0..0)]; }
+
+    // bits of the best prefix ending in each state, and (previous state, starts a segment) of every character
+    let mut bits = [usize::MAX; 7];
+    let mut from = vec![[(0, false); 7]; chars.len()];
+
+    for (i, &(_, c)) in chars.iter().enumerate() {
+        let (best, &best_bits) = bits.iter().enumerate().min_by_key(|&(_, &bits)| bits).unwrap();
+        let mut next_bits = [usize::MAX; 7];
+
+        // stay in the segment, or start one after the best prefix, staying wins a tie
+        for (state, &state_bits) in bits.iter().enumerate() {
+            if state_bits == usize::MAX { continue; }
+
+            if let Some((next, c_bits)) = next(state, c) {
+                if state_bits + c_bits < next_bits[next] {
+                    next_bits[next] = state_bits + c_bits;
+                    from[i][next] = (state, false);
+                }
+            }
+        }
+        for &state in FIRST_STATES.iter() {
+            if let Some((next, c_bits)) = next(state, c) {
+                let start_bits = if i == 0 { 0 } else { best_bits } + 4 + indicator_bits(STATES[state], version) + c_bits;
+
+                if start_bits < next_bits[next] {
+                    next_bits[next] = start_bits;
+                    from[i][next] = (best, true);
+                }
+            }
+        }
+
+        bits = next_bits;
+    }
+
+    // walk back from the best last state
+    let mut state = (0..7).min_by_key(|&state| bits[state]).unwrap();
+    let mut segments = vec![];
+    let mut end = message.len();
+    for (i, &(start, _)) in chars.iter().enumerate().rev() {
+        let (previous, starts) = from[i][state];
+
+        if starts {
+            segments.push((STATES[state], start..end));
+            end = start;
+        }
+        state = previous;
+    }
+    segments.reverse();
+
+    segments
+}
+
+// bits of every segment of `split` with its mode indicator and character count indicator
+//
+// version: index from 1
+pub(crate) fn split_bits(message: &str, split: &[(Mode, Range<usize>)], version: usize) -> usize {
+    use crate::encoder::capacity::data_bits;
+
+    split.iter()
+        .map(|(mode, range)| {
+            let len = match mode {
+                Kanji | Chinese => message[range.clone()].chars().count(),
+                _ => range.len(),
+            };
+
+            4 + indicator_bits(*mode, version) + data_bits(*mode, len)
+        })
+        .sum()
+}
+
+#[test]
+fn test_split() {
+    use crate::encoder::segment::Segment;
+
+    let modes = |message, version| split(message, version)
+        .into_iter()
+        .map(|(mode, range)| Segment::new(mode, &message[range]))
+        .collect::<Vec<Segment>>();
+
+    // a single mode wins when switching doesn't pay off
+    assert_eq!(modes("01234567", 1), [Segment::new(Numeric, "01234567")]);
+    assert_eq!(modes("HELLO WORLD", 1), [Segment::new(Alphanumeric, "HELLO WORLD")]);
+    assert_eq!(modes("a1b2c3", 1), [Segment::new(Byte, "a1b2c3")]);
+    assert_eq!(modes("", 1), [Segment::new(Numeric, "")]);
+
+    assert_eq!(
+        modes("https://EXAMPLE.COM", 1),
+        [Segment::new(Byte, "https"), Segment::new(Alphanumeric, "://EXAMPLE.COM")]
+    );
+    assert_eq!(
+        modes("ABCDEFGHIJ0123456789012345", 1),
+        [Segment::new(Alphanumeric, "ABCDEFGHIJ"), Segment::new(Numeric, "0123456789012345")]
+    );
+    assert_eq!(
+        modes("点茗ハロー12345678901234567890", 1),
+        [Segment::new(Kanji, "点茗ハロー"), Segment::new(Numeric, "12345678901234567890")]
+    );
+
+    // longer character count indicators of larger versions make switching cost more,
+    // 4 + 8 + 8 * 4 + 4 + 10 + 24 + 4 + 8 + 8 * 3 = 118 bits < 4 + 8 + 8 * 14 = 124 bits in version 1,
+    // 4 + 16 + 8 * 4 + 4 + 14 + 24 + 4 + 16 + 8 * 3 = 138 bits > 4 + 16 + 8 * 14 = 132 bits in version 27
+    assert_eq!(
+        modes("abc.0123456xyz", 1),
+        [Segment::new(Byte, "abc."), Segment::new(Numeric, "0123456"), Segment::new(Byte, "xyz")]
+    );
+    assert_eq!(modes("abc.0123456xyz", 27), [Segment::new(Byte, "abc.0123456xyz")]);
+
+    // the split is never longer than a single segment
+    for message in ["hello, world!", "WIFI:T:WPA;S:HOME 5G;P:12345678;;", "ハロー・ワールド 2024", "Ḥello 0123"].iter() {
+        for &version in [1, 10, 27].iter() {
+            let single = Segment::auto(message).bits(version);
+            let split = modes(message, version).iter().map(|segment| segment.bits(version)).sum::<usize>();
+
+            assert!(split <= single, "{} {}", message, version);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::prelude::*;
+
+use super::mode::Mode;
+
+// a run of characters encoded in one mode, with its own mode indicator and character count indicator
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    pub mode: Mode,
+    pub data: String,
+}
+
+impl Segment {
+    pub fn new(mode: Mode, data: &str) -> Segment {
+        Segment {
+            mode,
+            data: data.to_string(),
+        }
+    }
+
+    // a single segment in the most suitable mode for the whole message
+    pub fn auto(message: &str) -> Segment {
+        use super::resolve::auto_detect::detect_mode;
+
+        Segment::new(detect_mode(message), message)
+    }
+
+    // value of character count indicator
+    pub fn char_count(&self) -> usize {
+        match self.mode {
+            Mode::Kanji | Mode::Chinese => self.data.chars().count(),
+            _ => self.data.len()
+        }
+    }
+
+    // version: index from 1, a segment in `Mode::Unknown` counts in the mode detected for its data
+    pub fn bits(&self, version: usize) -> usize {
+        use super::capacity::{data_bits, indicator_bits};
+
+        if self.mode == Mode::Unknown { return Segment::auto(&self.data).bits(version); }
+
+        4 + indicator_bits(self.mode, version) + data_bits(self.mode, self.char_count())
+    }
+}
@@ -0,0 +1,145 @@
+use crate::prelude::*;
+
+use super::{
+    Encoder,
+    Error,
+    matrix::Matrix,
+};
+
+// total codewords of version 40
+const MAX_CODEWORDS: usize = 3706;
+// width of version 40
+const MAX_WIDTH: usize = 177;
+
+// buffers reused by `Encoder::encode_into`, sized for version 40 at creation
+#[derive(Debug)]
+pub struct Workspace {
+    // data codewords followed by ec codewords of every block
+    codewords: Vec<u8>,
+    interleaved: Vec<u8>,
+    matrix: Matrix,
+    // rows which are not used by the current version
+    spare_rows: Vec<Vec<u8>>,
+}
+
+impl Workspace {
+    pub fn new() -> Workspace {
+        Workspace {
+            codewords: Vec::with_capacity(MAX_CODEWORDS),
+            interleaved: Vec::with_capacity(MAX_CODEWORDS),
+            matrix: Matrix(Vec::with_capacity(MAX_WIDTH)),
+            spare_rows: (0..MAX_WIDTH).map(|_| Vec::with_capacity(MAX_WIDTH)).collect(),
+        }
+    }
+
+    // result of the last `Encoder::encode_into`
+    pub fn matrix(&self) -> &Matrix { &self.matrix }
+}
+
+impl Default for Workspace {
+    fn default() -> Workspace { Workspace::new() }
+}
+
+// same as `Matrix::blank`, rows are moved between `matrix` and `spare_rows` instead of allocated
+fn reset_matrix<'a>(matrix: &'a mut Matrix, spare_rows: &mut Vec<Vec<u8>>, version: usize) -> &'a mut Matrix {
+    let Matrix(rows) = matrix;
+    let width = version * 4 + 17;
+
+    while rows.len() > width { spare_rows.push(rows.pop().unwrap()); }
+    while rows.len() < width { rows.push(spare_rows.pop().unwrap()); }
+
+    for row in rows.iter_mut() {
+        row.clear();
+        row.resize(width, 5);
+    }
+
+    matrix
+}
+
+impl Encoder {
+    // same as `encode` then `as_matrix`, the steady state doesn't allocate unless `halftone` is set
+    pub fn encode_into<'a>(&mut self, message: &str, workspace: &'a mut Workspace) -> Result<&'a Matrix, Error> {
+        use super::{
+            bits::Codewords,
+            encode::{encode_segment, encode_split},
+            qrcode_info::CAPACITIES,
+            resolve::final_structure::interleave_with_ec,
+        };
+
+        // the halftone padding and its mask are solved by `encode`
+        if self.halftone.is_some() {
+            let encoded = self.encode(message)?.as_matrix();
+            let matrix = reset_matrix(&mut workspace.matrix, &mut workspace.spare_rows, self.version + 1);
+            for (row, encoded) in matrix.0.iter_mut().zip(encoded.0.iter()) { row.copy_from_slice(encoded); }
+
+            return Ok(matrix);
+        }
+
+        // the split allocates
+        let split = self.detect_split(message)?;
+        let bits_count = if split.is_none() { self.detect(message)? } else { 0 };
+        let capacity = CAPACITIES[self.version][self.ec_level] as usize;
+        let Workspace { codewords, interleaved, matrix, spare_rows } = workspace;
+
+        {
+            let mut bits = Codewords::new(codewords);
+            match split {
+                Some(split) => encode_split(&mut bits, message, &split, self.version + 1),
+                None => encode_segment(&mut bits, self.mode, bits_count, message),
+            }
+
+            // terminator is 4 zero bits at most, then pad zero bits until the length is a multiple of 8
+            let len = bits.len + (capacity - bits.len).min(4);
+            bits.codewords.resize(len.div_ceil(8), 0);
+        }
+
+        let mut paddings = [236u8, 17].iter().cycle();
+        while codewords.len() < capacity / 8 { codewords.push(*paddings.next().unwrap()); }
+
+        interleave_with_ec(codewords, capacity / 8, self.version, self.ec_level, interleaved);
+
+        let version = self.version + 1;
+
+        let matrix = reset_matrix(matrix, spare_rows, version);
+        matrix.with_function_patterns(version).place_codewords(interleaved);
+
+        let mask = self.mask.unwrap_or_else(|| Matrix::best_mask(&matrix.penalties(self.ec_level)));
+
+        Ok(matrix.data_mask(self.ec_level, mask))
+    }
+}
+
+#[test]
+fn test_encode_into() {
+    let mut workspace = Workspace::new();
+
+    for &(message, ec_level) in [
+        ("01234567", "M"),
+        ("HELLO WORLD", "Q"),
+        ("ハロー・ワールド", "H"),
+        (&"hello, world! ".repeat(50)[..], "L"),
+        ("https://github.com/AurevoirXavier/qrustcode", "M"),
+        (&"1".repeat(7089)[..], "L"),
+        ("0", "H"),
+        ("https://EXAMPLE.COM/0123456789", "L"),
+    ].iter() {
+        for &split in [false, true].iter() {
+            let mut encoder = Encoder::new().ec_level(ec_level).split(split);
+            let expect = encoder.encode(message).unwrap().as_matrix();
+
+            assert_eq!(encoder.encode_into(message, &mut workspace).unwrap().0, expect.0);
+        }
+    }
+
+    // the halftone padding isn't skipped
+    let image = crate::decoder::Image::new(2, 2, vec![0, 255, 255, 0]);
+    let mut encoder = Encoder::new().version(6).halftone(&image);
+    let expect = encoder.encode("https://example.com").unwrap().as_matrix();
+    assert_eq!(encoder.encode_into("https://example.com", &mut workspace).unwrap().0, expect.0);
+    assert_eq!(encoder.encode_into("01234567", &mut workspace).unwrap().width(), 41);
+
+    assert_eq!(
+        Encoder::new().version(1).encode_into(&"1".repeat(42), &mut workspace).unwrap_err(),
+        Error::DataTooLong { bits: 154, capacity: 152 }
+    );
+}
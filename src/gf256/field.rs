@@ -0,0 +1,86 @@
+// Galois Field GF(2^8) defined by a primitive polynomial
+//
+// store double `exp` table to guarantee `exp[log[x] + log[y]]` not out of index
+#[derive(Debug, PartialEq)]
+pub struct Field {
+    primitive: u16,
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+// x^8 + x^4 + x^3 + x^2 + 1
+pub static QR_CODE: Field = Field::new(0x11d);
+
+// x^8 + x^5 + x^3 + x^2 + 1
+pub static DATA_MATRIX: Field = Field::new(0x12d);
+
+impl Field {
+    pub const fn new(primitive: u16) -> Field {
+        let mut exp = [0; 512];
+        let mut log = [0; 256];
+
+        let mut x = 1u16;
+        let mut i = 0;
+        while i < 255 {
+            exp[i] = x as u8;
+            exp[i + 255] = x as u8;
+            log[x as usize] = i as u8;
+
+            x <<= 1;
+            if x & 0x100 != 0 { x ^= primitive; }
+
+            i += 1;
+        }
+        exp[510] = exp[0];
+        exp[511] = exp[1];
+
+        Field { primitive, exp, log }
+    }
+
+    pub fn primitive(&self) -> u16 { self.primitive }
+
+    // α^power
+    pub const fn exp(&self, power: usize) -> u8 { self.exp[power % 255] }
+
+    // x = α^log(x), x can't be 0
+    pub fn log(&self, x: u8) -> usize {
+        assert_ne!(x, 0);
+
+        self.log[x as usize] as usize
+    }
+
+    pub const fn mul(&self, x: u8, y: u8) -> u8 {
+        if x == 0 || y == 0 { 0 } else { self.exp[self.log[x as usize] as usize + self.log[y as usize] as usize] }
+    }
+
+    // y can't be 0
+    pub fn div(&self, x: u8, y: u8) -> u8 {
+        assert_ne!(y, 0);
+
+        if x == 0 { 0 } else { self.exp[self.log[x as usize] as usize + 255 - self.log[y as usize] as usize] }
+    }
+
+    // x can't be 0
+    pub fn inv(&self, x: u8) -> u8 { self.div(1, x) }
+
+    pub fn pow(&self, x: u8, power: usize) -> u8 {
+        if power == 0 { 1 } else if x == 0 { 0 } else { self.exp(self.log(x) * power) }
+    }
+}
+
+#[test]
+fn test_field() {
+    assert_eq!(QR_CODE.exp[..10], [1, 2, 4, 8, 16, 32, 64, 128, 29, 58]);
+    assert_eq!(QR_CODE.log[..10], [0, 0, 1, 25, 2, 50, 26, 198, 3, 223]);
+    assert_eq!(DATA_MATRIX.exp[..10], [1, 2, 4, 8, 16, 32, 64, 128, 45, 90]);
+
+    for field in [&QR_CODE, &DATA_MATRIX].iter() {
+        for x in 1..=255u8 {
+            assert_eq!(field.mul(x, field.inv(x)), 1);
+            assert_eq!(field.div(field.mul(x, 7), 7), x);
+            assert_eq!(field.exp(field.log(x)), x);
+        }
+        assert_eq!(field.pow(2, 255), 1);
+        assert_eq!(field.mul(0, 9), 0);
+    }
+}
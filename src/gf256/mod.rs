@@ -0,0 +1,64 @@
+mod field;
+mod polynomial;
+mod reed_solomon;
+
+#[cfg(feature = "std")]
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+pub use self::{
+    field::{Field, DATA_MATRIX, QR_CODE},
+    polynomial::Polynomial,
+    reed_solomon::ReedSolomon,
+};
+
+// (primitive, first root, degree) -> generator polynomial
+#[cfg(feature = "std")]
+static GENERATORS: Mutex<BTreeMap<(u16, usize, usize), Arc<Polynomial>>> = Mutex::new(BTreeMap::new());
+
+// `Polynomial::generator`, computed once and cached, `ReedSolomon::new` takes it from here
+#[cfg(feature = "std")]
+pub fn generator(field: &'static Field, degree: usize, first_root: usize) -> Arc<Polynomial> {
+    GENERATORS.lock()
+        .unwrap()
+        .entry((field.primitive(), first_root, degree))
+        .or_insert_with(|| Arc::new(Polynomial::generator(field, degree, first_root)))
+        .clone()
+}
+
+// `ReedSolomon::new`, QR Code's ec lengths take the tables built at compile time and don't allocate
+pub fn reed_solomon(field: &'static Field, degree: usize, first_root: usize) -> ReedSolomon {
+    if field.primitive() == QR_CODE.primitive() && first_root == 0 {
+        if let Some(reed_solomon) = ReedSolomon::qr_code(field, degree) { return reed_solomon; }
+    }
+
+    ReedSolomon::new(field, degree, first_root)
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_generator_cache() {
+    let generator = generator(&QR_CODE, 13, 0);
+
+    assert!(Arc::ptr_eq(&generator, &self::generator(&QR_CODE, 13, 0)));
+    assert_eq!(*generator, Polynomial::generator(&QR_CODE, 13, 0));
+    assert_ne!(*generator, *self::generator(&DATA_MATRIX, 13, 0));
+
+    // `ReedSolomon::new` fills the cache
+    ReedSolomon::new(&DATA_MATRIX, 9, 1);
+    assert!(GENERATORS.lock().unwrap().contains_key(&(DATA_MATRIX.primitive(), 1, 9)));
+}
+
+#[test]
+fn test_reed_solomon() {
+    let (mut ec, mut expect) = ([0; 13], [0; 13]);
+    reed_solomon(&QR_CODE, 13, 0).encode(&[1, 2, 3], &mut ec);
+    ReedSolomon::new(&QR_CODE, 13, 0).encode(&[1, 2, 3], &mut expect);
+    assert_eq!(ec, expect);
+
+    // built on the call
+    assert_eq!(reed_solomon(&QR_CODE, 11, 0).degree(), 11);
+    assert_eq!(reed_solomon(&DATA_MATRIX, 13, 1).degree(), 13);
+}
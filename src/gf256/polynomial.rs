@@ -0,0 +1,163 @@
+use crate::prelude::*;
+
+use super::field::Field;
+
+// polynomial over GF(2^8), coefficients are stored from the highest degree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    field: &'static Field,
+    coefficients: Vec<u8>,
+}
+
+impl Polynomial {
+    pub fn new(field: &'static Field, coefficients: &[u8]) -> Polynomial {
+        // trim leading zeros, keep one zero for the zero polynomial
+        let leading_zeros = coefficients.iter().take_while(|&&coefficient| coefficient == 0).count();
+
+        Polynomial {
+            field,
+            coefficients: if leading_zeros == coefficients.len() { vec![0] } else { coefficients[leading_zeros..].to_vec() },
+        }
+    }
+
+    // coefficient * x^degree
+    pub fn monomial(field: &'static Field, degree: usize, coefficient: u8) -> Polynomial {
+        let mut coefficients = vec![0; degree + 1];
+        coefficients[0] = coefficient;
+
+        Polynomial::new(field, &coefficients)
+    }
+
+    // (x - α^first_root)(x - α^(first_root + 1)) ... (x - α^(first_root + degree - 1))
+    //
+    // QR Code uses first_root 0, Data Matrix uses first_root 1
+    pub fn generator(field: &'static Field, degree: usize, first_root: usize) -> Polynomial {
+        (first_root..first_root + degree).fold(Polynomial::new(field, &[1]), |generator, root| {
+            // subtraction is addition in GF(2^8)
+            generator.mul(&Polynomial::new(field, &[1, field.exp(root)]))
+        })
+    }
+
+    pub fn field(&self) -> &'static Field { self.field }
+
+    pub fn coefficients(&self) -> &[u8] { &self.coefficients }
+
+    pub fn degree(&self) -> usize { self.coefficients.len() - 1 }
+
+    pub fn is_zero(&self) -> bool { self.coefficients[0] == 0 }
+
+    // coefficient of x^degree
+    pub fn coefficient(&self, degree: usize) -> u8 {
+        if degree > self.degree() { 0 } else { self.coefficients[self.degree() - degree] }
+    }
+
+    // addition and subtraction are both XOR
+    pub fn add(&self, other: &Polynomial) -> Polynomial {
+        let (long, short) = if self.coefficients.len() >= other.coefficients.len() { (self, other) } else { (other, self) };
+        let mut coefficients = long.coefficients.clone();
+        let fix = long.coefficients.len() - short.coefficients.len();
+
+        for (x, &y) in coefficients[fix..].iter_mut().zip(short.coefficients.iter()) { *x ^= y; }
+
+        Polynomial::new(self.field, &coefficients)
+    }
+
+    pub fn mul(&self, other: &Polynomial) -> Polynomial {
+        let mut coefficients = vec![0; self.coefficients.len() + other.coefficients.len() - 1];
+
+        for (i, &x) in self.coefficients.iter().enumerate() {
+            for (j, &y) in other.coefficients.iter().enumerate() { coefficients[i + j] ^= self.field.mul(x, y); }
+        }
+
+        Polynomial::new(self.field, &coefficients)
+    }
+
+    pub fn scale(&self, coefficient: u8) -> Polynomial {
+        Polynomial::new(
+            self.field,
+            &self.coefficients.iter().map(|&x| self.field.mul(x, coefficient)).collect::<Vec<u8>>(),
+        )
+    }
+
+    // (quotient, remainder), divisor can't be zero
+    pub fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        assert!(!divisor.is_zero());
+
+        if self.degree() < divisor.degree() { return (Polynomial::new(self.field, &[0]), self.clone()); }
+
+        let mut remainder = self.coefficients.clone();
+        let len = remainder.len() - divisor.degree();
+        let inverse = self.field.inv(divisor.coefficients[0]);
+
+        // synthetic division, the first `len` coefficients become the quotient
+        for i in 0..len {
+            let coefficient = self.field.mul(remainder[i], inverse);
+            remainder[i] = coefficient;
+
+            if coefficient != 0 {
+                for (x, &y) in remainder[i + 1..].iter_mut().zip(divisor.coefficients[1..].iter()) {
+                    *x ^= self.field.mul(y, coefficient);
+                }
+            }
+        }
+
+        (
+            Polynomial::new(self.field, &remainder[..len]),
+            Polynomial::new(self.field, &remainder[len..]),
+        )
+    }
+
+    // Horner's method
+    pub fn eval(&self, x: u8) -> u8 {
+        self.coefficients.iter().fold(0, |result, &coefficient| self.field.mul(result, x) ^ coefficient)
+    }
+}
+
+#[test]
+fn test_polynomial() {
+    use super::field::QR_CODE;
+
+    let p = Polynomial::new(&QR_CODE, &[0, 0, 3, 0, 1]);
+    assert_eq!(p.coefficients(), [3, 0, 1]);
+    assert_eq!(p.degree(), 2);
+    assert_eq!(p.coefficient(2), 3);
+    assert_eq!(p.coefficient(5), 0);
+    assert_eq!(p.add(&p), Polynomial::new(&QR_CODE, &[0]));
+    assert_eq!(p.eval(0), 1);
+    assert_eq!(p.eval(1), 2);
+
+    let q = Polynomial::new(&QR_CODE, &[7, 9]);
+    let (quotient, remainder) = p.mul(&q).add(&Polynomial::new(&QR_CODE, &[5])).div_rem(&q);
+    assert_eq!(quotient, p);
+    assert_eq!(remainder, Polynomial::new(&QR_CODE, &[5]));
+    assert_eq!(Polynomial::monomial(&QR_CODE, 3, 2).scale(3).coefficients(), [6, 0, 0, 0]);
+}
+
+#[test]
+fn test_generator() {
+    use super::field::{DATA_MATRIX, QR_CODE};
+
+    // α exponents of the coefficients, without the leading 1
+    for &(degree, exponents) in [
+        (2, &[25, 1][..]),
+        (7, &[87, 229, 146, 149, 238, 102, 21][..]),
+        (10, &[251, 67, 46, 61, 118, 70, 64, 94, 32, 45][..]),
+        (30, &[
+            41, 173, 145, 152, 216, 31, 179, 182, 50, 48, 110, 86, 239, 96, 222, 125, 42, 173, 226, 193, 224, 130, 156,
+            37, 251, 216, 238, 40, 192, 180,
+        ][..]),
+    ].iter() {
+        let generator = Polynomial::generator(&QR_CODE, degree, 0);
+
+        assert_eq!(generator.degree(), degree);
+        assert_eq!(
+            generator.coefficients()[1..].iter().map(|&x| QR_CODE.log(x)).collect::<Vec<usize>>(),
+            exponents.iter().map(|&x| x as usize).collect::<Vec<usize>>()
+        );
+        // α^0 ~ α^(degree - 1) are the roots
+        for root in 0..degree { assert_eq!(generator.eval(QR_CODE.exp(root)), 0); }
+    }
+
+    // Data Matrix, 5 ec codewords: 62, 111, 15, 48, 228
+    assert_eq!(Polynomial::generator(&DATA_MATRIX, 5, 1).coefficients(), [1, 62, 111, 15, 48, 228]);
+}
@@ -0,0 +1,290 @@
+use alloc::borrow::Cow;
+
+use crate::prelude::*;
+
+use super::field::Field;
+
+// every number of ec codewords per block of QR Code
+const QR_CODE_DEGREES: [usize; 13] = [7, 10, 13, 15, 16, 17, 18, 20, 22, 24, 26, 28, 30];
+
+// tables of `QR_CODE_DEGREES` one after another, built at compile time so that encoding QR Code never allocates them
+static QR_CODE_TABLES: [u8; qr_code_tables_len()] = qr_code_tables();
+
+const fn qr_code_tables_len() -> usize {
+    let (mut len, mut i) = (0, 0);
+    while i < QR_CODE_DEGREES.len() {
+        len += 256 * QR_CODE_DEGREES[i];
+        i += 1;
+    }
+
+    len
+}
+
+// same tables as `ReedSolomon::new` in `QR_CODE` with first root 0
+const fn qr_code_tables() -> [u8; qr_code_tables_len()] {
+    let field = Field::new(0x11d);
+    let mut tables = [0; qr_code_tables_len()];
+    let (mut start, mut i) = (0, 0);
+
+    while i < QR_CODE_DEGREES.len() {
+        let degree = QR_CODE_DEGREES[i];
+
+        // `Polynomial::generator`, multiplied by (x - α^root) from the highest degree
+        let mut generator = [0; 31];
+        generator[0] = 1;
+        let mut root = 0;
+        while root < degree {
+            let mut j = root + 1;
+            while j > 0 {
+                generator[j] ^= field.mul(generator[j - 1], field.exp(root));
+                j -= 1;
+            }
+            root += 1;
+        }
+
+        let mut coefficient = 0;
+        while coefficient < 256 {
+            let mut j = 0;
+            while j < degree {
+                tables[start + coefficient * degree + j] = field.mul(generator[j + 1], coefficient as u8);
+                j += 1;
+            }
+            coefficient += 1;
+        }
+
+        start += 256 * degree;
+        i += 1;
+    }
+
+    tables
+}
+
+// systematic Reed–Solomon encoder, a LFSR dividing data(x) * x^degree by the generator polynomial
+//
+// table[coefficient * degree + i] = generator[i + 1] * coefficient, so encoding needs no field arithmetic
+#[derive(Debug)]
+pub struct ReedSolomon {
+    field: &'static Field,
+    first_root: usize,
+    degree: usize,
+    table: Cow<'static, [u8]>,
+}
+
+impl ReedSolomon {
+    pub fn new(field: &'static Field, degree: usize, first_root: usize) -> ReedSolomon {
+        #[cfg(feature = "std")]
+        let generator = super::generator(field, degree, first_root);
+        #[cfg(not(feature = "std"))]
+        let generator = super::Polynomial::generator(field, degree, first_root);
+        let mut table = Vec::with_capacity(256 * degree);
+
+        for coefficient in 0..=255 {
+            table.extend(generator.coefficients()[1..].iter().map(|&x| field.mul(x, coefficient)));
+        }
+
+        ReedSolomon { field, first_root, degree, table: Cow::Owned(table) }
+    }
+
+    // the table built at compile time, `None` if `degree` isn't a number of ec codewords of QR Code
+    pub(crate) fn qr_code(field: &'static Field, degree: usize) -> Option<ReedSolomon> {
+        let i = QR_CODE_DEGREES.iter().position(|&x| x == degree)?;
+        let start = QR_CODE_DEGREES[..i].iter().map(|&x| 256 * x).sum::<usize>();
+
+        Some(ReedSolomon {
+            field,
+            first_root: 0,
+            degree,
+            table: Cow::Borrowed(&QR_CODE_TABLES[start..start + 256 * degree]),
+        })
+    }
+
+    // number of ec codewords
+    pub fn degree(&self) -> usize { self.degree }
+
+    // write `degree` ec codewords of `data` into `ec`
+    pub fn encode(&self, data: &[u8], ec: &mut [u8]) {
+        assert_eq!(ec.len(), self.degree);
+
+        for x in ec.iter_mut() { *x = 0; }
+        if self.degree == 0 { return; }
+
+        for &codeword in data {
+            let feedback = (codeword ^ ec[0]) as usize;
+
+            ec.copy_within(1.., 0);
+            ec[self.degree - 1] = 0;
+
+            if feedback != 0 {
+                for (x, &y) in ec.iter_mut().zip(self.table[feedback * self.degree..].iter()) { *x ^= y; }
+            }
+        }
+    }
+
+    // S[j] = block(α^(first_root + j)), all zero if the block is a codeword
+    fn syndromes(&self, block: &[u8]) -> Vec<u8> {
+        let field = self.field;
+
+        (0..self.degree)
+            .map(|j| {
+                let x = field.exp(self.first_root + j);
+
+                block.iter().fold(0, |y, &codeword| field.mul(y, x) ^ codeword)
+            })
+            .collect()
+    }
+
+    // correct up to `degree / 2` wrong codewords of data + ec in place, return how many were corrected,
+    // `None` if there are too many errors
+    //
+    // Berlekamp–Massey finds the error locator, Chien search finds its roots, Forney gives the error values
+    pub fn correct(&self, block: &mut [u8]) -> Option<usize> {
+        let field = self.field;
+        let len = block.len();
+        if len > 255 || len < self.degree { return None; }
+
+        let syndromes = self.syndromes(block);
+        if syndromes.iter().all(|&s| s == 0) { return Some(0); }
+
+        // polynomials are stored from the lowest degree here
+        let mut locator = vec![1];
+        let mut prev = vec![1];
+        let mut errors = 0;
+        let mut shift = 1;
+        let mut prev_discrepancy = 1;
+        for n in 0..self.degree {
+            let discrepancy = (1..=errors.min(locator.len() - 1))
+                .fold(syndromes[n], |d, i| d ^ field.mul(locator[i], syndromes[n - i]));
+
+            if discrepancy == 0 {
+                shift += 1;
+
+                continue;
+            }
+
+            let scale = field.div(discrepancy, prev_discrepancy);
+            let mut next = locator.clone();
+            if next.len() < prev.len() + shift { next.resize(prev.len() + shift, 0); }
+            for (i, &x) in prev.iter().enumerate() { next[i + shift] ^= field.mul(scale, x); }
+
+            if 2 * errors <= n {
+                prev = core::mem::replace(&mut locator, next);
+                errors = n + 1 - errors;
+                prev_discrepancy = discrepancy;
+                shift = 1;
+            } else {
+                locator = next;
+                shift += 1;
+            }
+        }
+        locator.truncate(errors + 1);
+        if 2 * errors > self.degree { return None; }
+
+        // evaluator = syndromes(x) * locator(x) mod x^degree
+        let mut evaluator = vec![0; self.degree];
+        for (i, &s) in syndromes.iter().enumerate() {
+            for (j, &l) in locator.iter().enumerate() {
+                if i + j < self.degree { evaluator[i + j] ^= field.mul(s, l); }
+            }
+        }
+        let eval = |poly: &[u8], x: u8| poly.iter().rev().fold(0, |y, &c| field.mul(y, x) ^ c);
+
+        let mut corrected = 0;
+        for (i, codeword) in block.iter_mut().enumerate() {
+            // the codeword at `i` is the coefficient of x^power
+            let power = len - 1 - i;
+            let x_inv = field.exp(255 - power % 255);
+            if eval(&locator, x_inv) != 0 { continue; }
+
+            // formal derivative keeps the odd terms
+            let derivative = locator.iter()
+                .enumerate()
+                .skip(1)
+                .step_by(2)
+                .fold(0, |y, (j, &c)| y ^ field.mul(c, field.pow(x_inv, j - 1)));
+            if derivative == 0 { return None; }
+
+            let magnitude = field.div(
+                field.mul(field.pow(field.exp(power), 1 + 255 - self.first_root % 255), eval(&evaluator, x_inv)),
+                derivative,
+            );
+            *codeword ^= magnitude;
+            corrected += 1;
+        }
+
+        if corrected != errors || self.syndromes(block).iter().any(|&s| s != 0) { return None; }
+
+        Some(corrected)
+    }
+}
+
+#[test]
+fn test_reed_solomon() {
+    use super::{
+        field::{DATA_MATRIX, QR_CODE},
+        polynomial::Polynomial,
+    };
+
+    let data = [0x10, 0x20, 0x0c, 0x56, 0x61, 0x80, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11];
+    let mut ec = [0; 10];
+    ReedSolomon::new(&QR_CODE, 10, 0).encode(&data, &mut ec);
+    assert_eq!(ec, [0xa5, 0x24, 0xd4, 0xc1, 0xed, 0x36, 0xc7, 0x87, 0x2c, 0x55]);
+
+    // same as the remainder of the polynomial division
+    for &(field, degree, first_root) in [(&QR_CODE, 30, 0), (&DATA_MATRIX, 5, 1), (&DATA_MATRIX, 28, 1)].iter() {
+        let mut ec = vec![0; degree];
+        ReedSolomon::new(field, degree, first_root).encode(&data, &mut ec);
+
+        let (_, remainder) = Polynomial::new(field, &data)
+            .mul(&Polynomial::monomial(field, degree, 1))
+            .div_rem(&Polynomial::generator(field, degree, first_root));
+        let mut expect = vec![0; degree - remainder.degree() - 1];
+        expect.extend_from_slice(remainder.coefficients());
+
+        assert_eq!(ec, expect);
+    }
+}
+
+#[test]
+fn test_qr_code_tables() {
+    use super::field::QR_CODE;
+    use crate::encoder::qrcode_info::EC_CW_PER_BLOCKS;
+
+    for &degree in EC_CW_PER_BLOCKS.iter().flatten() {
+        let reed_solomon = ReedSolomon::qr_code(&QR_CODE, degree as usize).unwrap();
+
+        assert!(matches!(reed_solomon.table, Cow::Borrowed(_)));
+        assert_eq!(reed_solomon.table, ReedSolomon::new(&QR_CODE, degree as usize, 0).table);
+    }
+    assert!(ReedSolomon::qr_code(&QR_CODE, 11).is_none());
+}
+
+#[test]
+fn test_correct() {
+    use super::field::{DATA_MATRIX, QR_CODE};
+
+    let data = (0..40).map(|i| (i * 37 + 11) as u8).collect::<Vec<u8>>();
+
+    for &(field, degree, first_root) in [(&QR_CODE, 10, 0), (&QR_CODE, 30, 0), (&DATA_MATRIX, 28, 1)].iter() {
+        let reed_solomon = ReedSolomon::new(field, degree, first_root);
+        let mut block = data.clone();
+        block.resize(data.len() + degree, 0);
+        let (data, ec) = block.split_at_mut(data.len());
+        reed_solomon.encode(data, ec);
+
+        assert_eq!(reed_solomon.correct(&mut block.clone()), Some(0));
+
+        // every number of errors up to the capacity, spread over data and ec
+        for errors in 1..=degree / 2 {
+            let mut received = block.clone();
+            for i in 0..errors { received[i * 3] ^= (i * 29 + 1) as u8; }
+
+            assert_eq!(reed_solomon.correct(&mut received), Some(errors));
+            assert_eq!(received, block);
+        }
+
+        // one error more is detected, not miscorrected into the sent block
+        let mut received = block.clone();
+        for i in 0..=degree / 2 { received[i * 3] ^= 0x5a; }
+        assert_ne!(reed_solomon.correct(&mut received).map(|_| received), Some(block.clone()));
+    }
+}
@@ -1,38 +1,54 @@
-#![feature(test)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
 extern crate encoding_rs;
-#[macro_use]
-extern crate lazy_static;
-extern crate test;
 
+mod decoder;
 mod encoder;
+mod prelude;
+pub mod base45;
+pub mod gf256;
+pub mod payload;
+pub mod renderer;
+
+pub use self::encoder::{
+    capacity,
+    Block,
+    Config,
+    Encoder,
+    Error,
+    Explanation,
+    Logo,
+    LogoShape,
+    Matrix,
+    Mode,
+    Region,
+    RegionMap,
+    Segment,
+    Workspace,
+};
+pub use self::decoder::{
+    decode,
+    decode_image,
+    decode_layers,
+    DecodeError,
+    Decoded,
+    Image,
+};
+pub use self::renderer::{EyeShape, Fill, ModuleShape, Renderer, Style};
+
+// entry points of the pipeline stages for `benches/`, not a stable API
+#[doc(hidden)]
+pub use self::encoder::bench;
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use self::test::Bencher;
-
-    #[bench]
-    fn encoder_test(b: &mut Bencher) {
-        use self::encoder::Encoder;
-        let mut encoder = Encoder::new()
-            .mode("Kanji")
-            .version(3)
-            .ec_level("M");
-
-        b.iter(|| (0..100).fold((), |_, _| {
-            encoder
-                .encode("ハロー・ワールド")
-                .as_matrix();
-        }));
-    }
 
     #[test]
     fn test() {
-        use self::encoder::Encoder;
-
         let mut encoder = Encoder::new().ec_level("M");
-        let matrix = encoder.encode("ハロー・ワールド").as_matrix();
+        let matrix = encoder.encode("ハロー・ワールド").unwrap().as_matrix();
 
         println!("{:?}", matrix);
     }
@@ -0,0 +1,275 @@
+extern crate qrustcode;
+
+use std::{
+    env,
+    fs,
+    io::{self, Read, Write},
+    process,
+};
+
+use qrustcode::{capacity::max_chars, decode, decode_image, Decoded, Encoder, Mode, Renderer};
+
+const USAGE: &str = "\
+usage:
+    qrustcode encode [TEXT] [options]     encode TEXT, or the bytes of stdin
+    qrustcode decode [FILE]               decode a PBM, PGM or PNG image, or stdin
+    qrustcode inspect [TEXT] [options]    report version, ec level, mask, segments and penalties
+    qrustcode inspect --image FILE        the same for the symbol in an image
+    qrustcode capacity [VERSION]          max characters per mode and ec level, of every version by default
+
+options:
+    --mode MODE          numeric, alphanumeric, byte or kanji, detected by default
+    --version N          1 ~ 40, the smallest one which fits by default
+    --ec LEVEL           L, M, Q or H, default L
+    --mask N             0 ~ 7, the one with the lowest penalty by default
+    --format FORMAT      svg, png, pbm, term or eps, default svg
+    --scale N            pixels per module, default 4
+    --quiet-zone N       modules of margin, default 4
+    -o, --output FILE    write to FILE instead of stdout
+";
+
+// usage errors exit with 2, failures of encoding, decoding or io with 1
+enum Failure {
+    Usage(String),
+    Error(String),
+}
+
+impl<E: std::fmt::Display> From<E> for Failure {
+    fn from(e: E) -> Failure { Failure::Error(e.to_string()) }
+}
+
+#[derive(Default)]
+struct Options {
+    input: Option<String>,
+    image: Option<String>,
+    mode: Option<&'static str>,
+    version: Option<usize>,
+    ec_level: Option<&'static str>,
+    mask: Option<usize>,
+    format: Option<&'static str>,
+    scale: Option<usize>,
+    quiet_zone: Option<usize>,
+    output: Option<String>,
+}
+
+fn number(flag: &str, value: &str) -> Result<usize, Failure> {
+    value.parse().map_err(|_| Failure::Usage(format!("{} expects a number, got {:?}", flag, value)))
+}
+
+fn choice(flag: &str, value: &str, choices: &[&'static str]) -> Result<&'static str, Failure> {
+    choices.iter()
+        .find(|choice| choice.eq_ignore_ascii_case(value))
+        .cloned()
+        .ok_or_else(|| Failure::Usage(format!("{} expects one of {}, got {:?}", flag, choices.join(", "), value)))
+}
+
+// `--flag value` or `--flag=value`, at most one positional argument
+fn parse_options(args: &[String]) -> Result<Options, Failure> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "-" {
+            if options.input.is_some() { return Err(Failure::Usage(format!("unexpected argument {:?}", arg))); }
+            options.input = Some(arg.clone());
+
+            continue;
+        }
+
+        let (flag, inline) = match arg.find('=') {
+            Some(i) => (&arg[..i], Some(arg[i + 1..].to_string())),
+            None => (&arg[..], None),
+        };
+        let value = match inline.or_else(|| args.next().cloned()) {
+            Some(value) => value,
+            None => return Err(Failure::Usage(format!("{} expects a value", flag))),
+        };
+
+        match flag {
+            "--mode" => options.mode = Some(choice(flag, &value, &["Numeric", "Alphanumeric", "Byte", "Kanji"])?),
+            "--version" => options.version = Some(number(flag, &value)?),
+            "--ec" => options.ec_level = Some(choice(flag, &value, &["L", "M", "Q", "H"])?),
+            "--mask" => options.mask = Some(number(flag, &value)?),
+            "--format" => options.format = Some(choice(flag, &value, &["svg", "png", "pbm", "term", "eps"])?),
+            "--scale" => options.scale = Some(number(flag, &value)?),
+            "--quiet-zone" => options.quiet_zone = Some(number(flag, &value)?),
+            "-o" | "--output" => options.output = Some(value),
+            "--image" => options.image = Some(value),
+            _ => return Err(Failure::Usage(format!("unknown option {:?}", flag))),
+        }
+    }
+
+    Ok(options)
+}
+
+// the argument, or every byte of stdin
+fn read_input(input: &Option<String>) -> Result<Vec<u8>, Failure> {
+    match input {
+        Some(input) if input != "-" => Ok(input.clone().into_bytes()),
+        _ => {
+            let mut bytes = vec![];
+            io::stdin().read_to_end(&mut bytes)?;
+
+            Ok(bytes)
+        }
+    }
+}
+
+fn read_file(path: &Option<String>) -> Result<Vec<u8>, Failure> {
+    match path {
+        Some(path) if path != "-" => fs::read(path).map_err(|e| Failure::Error(format!("{}: {}", path, e))),
+        _ => read_input(&None),
+    }
+}
+
+fn write_output(output: &Option<String>, bytes: &[u8]) -> Result<(), Failure> {
+    match output {
+        Some(path) => fs::write(path, bytes).map_err(|e| Failure::Error(format!("{}: {}", path, e))),
+        None => Ok(io::stdout().write_all(bytes)?),
+    }
+}
+
+fn encode(options: &Options) -> Result<qrustcode::Matrix, Failure> {
+    let mut encoder = Encoder::new().ec_level(options.ec_level.unwrap_or("L"));
+    if let Some(mode) = options.mode { encoder = encoder.mode(mode); }
+    if let Some(version) = options.version { encoder = encoder.version(version); }
+    if let Some(mask) = options.mask { encoder = encoder.mask(mask); }
+
+    let input = read_input(&options.input)?;
+    let encoder = match String::from_utf8(input) {
+        Ok(message) => encoder.encode(&message)?,
+        // only Byte mode takes bytes which aren't UTF-8
+        Err(_) if options.mode.is_some_and(|mode| mode != "Byte") => {
+            return Err(Failure::Error(format!("{} mode needs UTF-8 input", options.mode.unwrap())));
+        }
+        Err(e) => encoder.encode_bytes(e.as_bytes())?,
+    };
+
+    Ok(encoder.as_matrix())
+}
+
+fn report(decoded: &Decoded) -> String {
+    let width = decoded.version * 4 + 17;
+    let mut report = format!(
+        "version: {} ({}x{} modules)\nec level: {}\nmask: {}\n",
+        decoded.version,
+        width,
+        width,
+        decoded.ec_level,
+        decoded.mask
+    );
+
+    report.push_str(&format!("segments: {}\n", decoded.segments.len()));
+    for segment in decoded.segments.iter() {
+        report.push_str(&format!("    {:?}: {} characters {:?}\n", segment.mode, segment.char_count(), segment.data));
+    }
+    report.push_str(&format!("corrected: {} codewords\n", decoded.corrected));
+
+    report.push_str("penalties:\n");
+    for (mask, penalties) in decoded.penalties.iter().enumerate() {
+        report.push_str(&format!(
+            "    mask {}: {} + {} + {} + {} = {}{}\n",
+            mask,
+            penalties[0],
+            penalties[1],
+            penalties[2],
+            penalties[3],
+            penalties.iter().sum::<u32>(),
+            if mask == decoded.mask { " *" } else { "" }
+        ));
+    }
+
+    report
+}
+
+// max characters per mode for every ec level of the version, or of every version
+fn capacity(version: &Option<String>) -> Result<String, Failure> {
+    let versions = match version {
+        Some(version) => {
+            let version = number("VERSION", version)?;
+            version..=version
+        }
+        None => 1..=40,
+    };
+
+    let row = |columns: [&dyn std::fmt::Display; 6]| {
+        format!("{:>7} {:>8} {:>7} {:>12} {:>6} {:>6}\n", columns[0], columns[1], columns[2], columns[3], columns[4], columns[5])
+    };
+
+    let mut table = row([&"version", &"ec level", &"numeric", &"alphanumeric", &"byte", &"kanji"]);
+    for version in versions {
+        for &ec_level in ["L", "M", "Q", "H"].iter() {
+            table.push_str(&row([
+                &version,
+                &ec_level,
+                &max_chars(Mode::Numeric, version, ec_level)?,
+                &max_chars(Mode::Alphanumeric, version, ec_level)?,
+                &max_chars(Mode::Byte, version, ec_level)?,
+                &max_chars(Mode::Kanji, version, ec_level)?,
+            ]));
+        }
+    }
+
+    Ok(table)
+}
+
+fn run(args: &[String]) -> Result<(), Failure> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Err(Failure::Usage("missing command".to_string())),
+    };
+    // before the options, which would take it as a flag without a value
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") { return write_output(&None, USAGE.as_bytes()); }
+    let options = parse_options(args)?;
+
+    match command {
+        "encode" => {
+            let matrix = encode(&options)?;
+            let mut renderer = Renderer::new(&matrix);
+            if let Some(scale) = options.scale { renderer = renderer.scale(scale); }
+            if let Some(quiet_zone) = options.quiet_zone { renderer = renderer.quiet_zone(quiet_zone); }
+
+            let image = match options.format.unwrap_or("svg") {
+                "png" => renderer.png(),
+                "pbm" => renderer.pbm(),
+                "term" => renderer.term().into_bytes(),
+                "eps" => renderer.eps().into_bytes(),
+                _ => renderer.svg().into_bytes(),
+            };
+
+            write_output(&options.output, &image)
+        }
+        "decode" => {
+            let decoded = decode_image(&read_file(&options.input)?)?;
+
+            write_output(&options.output, &decoded.data)
+        }
+        "inspect" => {
+            let decoded = match options.image {
+                Some(_) => decode_image(&read_file(&options.image)?)?,
+                None => decode(&encode(&options)?)?,
+            };
+
+            write_output(&options.output, report(&decoded).as_bytes())
+        }
+        "capacity" => write_output(&options.output, capacity(&options.input)?.as_bytes()),
+        "help" | "-h" | "--help" => write_output(&None, USAGE.as_bytes()),
+        _ => Err(Failure::Usage(format!("unknown command {:?}", command))),
+    }
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+
+    match run(&args) {
+        Ok(()) => (),
+        Err(Failure::Usage(message)) => {
+            eprintln!("qrustcode: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+        Err(Failure::Error(message)) => {
+            eprintln!("qrustcode: {}", message);
+            process::exit(1);
+        }
+    }
+}
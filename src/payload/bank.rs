@@ -0,0 +1,109 @@
+// checks shared by the payment payloads
+
+use crate::prelude::*;
+
+use super::error::PaymentError;
+
+// ISO 7064 MOD 97-10 of letters and digits, letters count as 10 ~ 35
+fn mod97(s: &str) -> Option<u32> {
+    let mut remainder = 0;
+    for c in s.chars() {
+        let n = c.to_digit(36)?;
+        remainder = if n < 10 { (remainder * 10 + n) % 97 } else { (remainder * 100 + n) % 97 };
+    }
+
+    Some(remainder)
+}
+
+// the IBAN without spaces and in upper case
+pub(crate) fn iban(iban: &str) -> Result<String, PaymentError> {
+    let iban = iban.chars().filter(|&c| c != ' ').collect::<String>().to_ascii_uppercase();
+    let bytes = iban.as_bytes();
+
+    if !(15..=34).contains(&bytes.len())
+        || !bytes[..2].iter().all(u8::is_ascii_uppercase)
+        || !bytes[2..4].iter().all(u8::is_ascii_digit)
+        || !bytes.iter().all(u8::is_ascii_alphanumeric)
+    {
+        return Err(PaymentError::InvalidIban);
+    }
+
+    // the first 4 characters are moved to the end
+    match mod97(&format!("{}{}", &iban[4..], &iban[..4])) {
+        Some(1) => Ok(iban),
+        _ => Err(PaymentError::InvalidIban),
+    }
+}
+
+// ISO 11649 creditor reference, `RF` + 2 check digits + up to 21 letters or digits
+pub(crate) fn creditor_reference(reference: &str) -> Result<String, PaymentError> {
+    let reference = reference.chars().filter(|&c| c != ' ').collect::<String>().to_ascii_uppercase();
+    let bytes = reference.as_bytes();
+
+    if !(5..=25).contains(&bytes.len())
+        || !reference.starts_with("RF")
+        || !bytes[2..4].iter().all(u8::is_ascii_digit)
+        || !bytes.iter().all(u8::is_ascii_alphanumeric)
+    {
+        return Err(PaymentError::InvalidReference);
+    }
+
+    match mod97(&format!("{}{}", &reference[4..], &reference[..4])) {
+        Some(1) => Ok(reference),
+        _ => Err(PaymentError::InvalidReference),
+    }
+}
+
+// cents of `1234`, `1234.5` or `1234.50`, no sign, leading zeros or thousands separators
+pub(crate) fn amount(amount: &str) -> Result<u64, PaymentError> {
+    let (units, cents) = match amount.find('.') {
+        Some(i) => (&amount[..i], &amount[i + 1..]),
+        None => (amount, "00"),
+    };
+
+    if units.is_empty()
+        || units.len() > 9
+        || units.len() > 1 && units.starts_with('0')
+        || !(1..=2).contains(&cents.len())
+        || !units.bytes().chain(cents.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return Err(PaymentError::InvalidAmount);
+    }
+
+    let cents = units.parse::<u64>().unwrap() * 100 + cents.parse::<u64>().unwrap() * if cents.len() == 1 { 10 } else { 1 };
+    if cents == 0 { Err(PaymentError::InvalidAmount) } else { Ok(cents) }
+}
+
+pub(crate) fn format_amount(cents: u64) -> String { format!("{}.{:02}", cents / 100, cents % 100) }
+
+// `value` in characters is no longer than `max`
+pub(crate) fn check_len(field: &'static str, value: &str, max: usize) -> Result<(), PaymentError> {
+    if value.chars().count() > max { Err(PaymentError::TooLong { field, max }) } else { Ok(()) }
+}
+
+#[test]
+fn test_iban() {
+    assert_eq!(iban("DE89 3704 0044 0532 0130 00"), Ok("DE89370400440532013000".to_string()));
+    assert_eq!(iban("ch9300762011623852957"), Ok("CH9300762011623852957".to_string()));
+    assert_eq!(iban("DE88370400440532013000"), Err(PaymentError::InvalidIban));
+    assert_eq!(iban("DE89"), Err(PaymentError::InvalidIban));
+    assert_eq!(iban("DE89-3704-0044-0532-0130-00"), Err(PaymentError::InvalidIban));
+}
+
+#[test]
+fn test_creditor_reference() {
+    assert_eq!(creditor_reference("RF18 5390 0754 7034"), Ok("RF18539007547034".to_string()));
+    assert_eq!(creditor_reference("RF19539007547034"), Err(PaymentError::InvalidReference));
+    assert_eq!(creditor_reference("XX18539007547034"), Err(PaymentError::InvalidReference));
+}
+
+#[test]
+fn test_amount() {
+    for &(amount_str, cents) in [("1", 100), ("0.5", 50), ("12.34", 1234), ("999999999.99", 99999999999)].iter() {
+        assert_eq!(amount(amount_str), Ok(cents));
+    }
+    for amount_str in ["", "0", "0.00", "01.00", "1.", "1.234", "-1", "+1", "1,00", "1000000000", "1 000"].iter() {
+        assert_eq!(amount(amount_str), Err(PaymentError::InvalidAmount), "{}", amount_str);
+    }
+    assert_eq!(format_amount(1230), "12.30");
+}
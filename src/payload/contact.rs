@@ -0,0 +1,289 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use crate::encoder::{Encoder, Error};
+
+use super::{escape, fold, Payload};
+
+// escaped in vCard text values, newlines become `\n`
+const VCARD_SPECIAL: [char; 3] = ['\\', ';', ','];
+// escaped in MECARD values
+const MECARD_SPECIAL: [char; 4] = ['\\', ';', ',', ':'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContactFormat {
+    VCard3,
+    VCard4,
+    // NTT DoCoMo's single line format, without title and photo
+    Mecard,
+}
+
+// components of vCard `ADR`, empty ones are left out
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Address {
+    pub street: String,
+    pub city: String,
+    pub region: String,
+    pub postal_code: String,
+    pub country: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    given_name: String,
+    family_name: String,
+    org: Option<String>,
+    title: Option<String>,
+    phones: Vec<String>,
+    emails: Vec<String>,
+    address: Option<Address>,
+    url: Option<String>,
+    photo_url: Option<String>,
+
+    format: ContactFormat,
+    // MECARD if it makes a smaller symbol and holds every field
+    compact: bool,
+}
+
+impl Contact {
+    pub fn new(given_name: &str, family_name: &str) -> Contact {
+        Contact {
+            given_name: given_name.to_string(),
+            family_name: family_name.to_string(),
+            org: None,
+            title: None,
+            phones: vec![],
+            emails: vec![],
+            address: None,
+            url: None,
+            photo_url: None,
+            format: ContactFormat::VCard3,
+            compact: false,
+        }
+    }
+
+    pub fn org(mut self, org: &str) -> Contact {
+        self.org = Some(org.to_string());
+
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Contact {
+        self.title = Some(title.to_string());
+
+        self
+    }
+
+    // every call adds one
+    pub fn phone(mut self, phone: &str) -> Contact {
+        self.phones.push(phone.to_string());
+
+        self
+    }
+
+    // every call adds one
+    pub fn email(mut self, email: &str) -> Contact {
+        self.emails.push(email.to_string());
+
+        self
+    }
+
+    pub fn address(mut self, address: Address) -> Contact {
+        self.address = Some(address);
+
+        self
+    }
+
+    pub fn url(mut self, url: &str) -> Contact {
+        self.url = Some(url.to_string());
+
+        self
+    }
+
+    pub fn photo_url(mut self, photo_url: &str) -> Contact {
+        self.photo_url = Some(photo_url.to_string());
+
+        self
+    }
+
+    // vCard 3.0 by default
+    pub fn format(mut self, format: ContactFormat) -> Contact {
+        self.format = format;
+
+        self
+    }
+
+    pub fn compact(mut self, compact: bool) -> Contact {
+        self.compact = compact;
+
+        self
+    }
+
+    fn full_name(&self) -> String {
+        match (self.given_name.is_empty(), self.family_name.is_empty()) {
+            (false, false) => format!("{} {}", self.given_name, self.family_name),
+            (true, _) => self.family_name.clone(),
+            (false, true) => self.given_name.clone(),
+        }
+    }
+
+    fn vcard(&self, version_4: bool) -> String {
+        let text = |value: &str| escape(value, &VCARD_SPECIAL).replace("\r\n", "\\n").replace('\n', "\\n");
+        let mut lines = vec![
+            "BEGIN:VCARD".to_string(),
+            format!("VERSION:{}", if version_4 { "4.0" } else { "3.0" }),
+            format!("N:{};{};;;", text(&self.family_name), text(&self.given_name)),
+            format!("FN:{}", text(&self.full_name())),
+        ];
+
+        if let Some(org) = &self.org { lines.push(format!("ORG:{}", text(org))); }
+        if let Some(title) = &self.title { lines.push(format!("TITLE:{}", text(title))); }
+        for phone in self.phones.iter() { lines.push(format!("TEL:{}", text(phone))); }
+        for email in self.emails.iter() {
+            lines.push(format!("EMAIL{}:{}", if version_4 { "" } else { ";TYPE=INTERNET" }, text(email)));
+        }
+        if let Some(address) = &self.address {
+            lines.push(format!(
+                "ADR:;;{};{};{};{};{}",
+                text(&address.street),
+                text(&address.city),
+                text(&address.region),
+                text(&address.postal_code),
+                text(&address.country)
+            ));
+        }
+        // URIs are not text, they aren't escaped
+        if let Some(url) = &self.url { lines.push(format!("URL:{}", url)); }
+        if let Some(photo_url) = &self.photo_url {
+            lines.push(format!("PHOTO{}:{}", if version_4 { "" } else { ";VALUE=uri" }, photo_url));
+        }
+        lines.push("END:VCARD".to_string());
+
+        lines.iter().map(|line| fold(line)).collect()
+    }
+
+    fn mecard(&self) -> String {
+        let value = |value: &str| escape(value, &MECARD_SPECIAL);
+        let mut mecard = format!("MECARD:N:{},{};", value(&self.family_name), value(&self.given_name));
+
+        for phone in self.phones.iter() { mecard.push_str(&format!("TEL:{};", value(phone))); }
+        for email in self.emails.iter() { mecard.push_str(&format!("EMAIL:{};", value(email))); }
+        if let Some(address) = &self.address {
+            // PO box, room number, street, city, region, postal code, country
+            mecard.push_str(&format!(
+                "ADR:,,{},{},{},{},{};",
+                value(&address.street),
+                value(&address.city),
+                value(&address.region),
+                value(&address.postal_code),
+                value(&address.country)
+            ));
+        }
+        if let Some(url) = &self.url { mecard.push_str(&format!("URL:{};", value(url))); }
+        if let Some(org) = &self.org { mecard.push_str(&format!("ORG:{};", value(org))); }
+        mecard.push(';');
+
+        mecard
+    }
+
+    // the payload in `format`, or MECARD if `compact` is set and it fits in a smaller version with `encoder`'s
+    // settings
+    pub fn payload(&self, encoder: &Encoder) -> Result<String, Error> {
+        let payload = self.to_string();
+        if !self.compact || self.format == ContactFormat::Mecard || self.title.is_some() || self.photo_url.is_some() {
+            return Ok(payload);
+        }
+
+        let mecard = self.mecard();
+        match (encoder.detect_version(&payload), encoder.detect_version(&mecard)) {
+            (Ok(version), Ok(mecard_version)) if mecard_version < version => Ok(mecard),
+            (Err(_), Ok(_)) => Ok(mecard),
+            (result, _) => result.map(|_| payload),
+        }
+    }
+}
+
+// long enough that M keeps the symbol small, badges are handled with care
+impl Payload for Contact {
+    fn ec_level(&self) -> &'static str { "M" }
+}
+
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.format {
+            ContactFormat::VCard3 => write!(f, "{}", self.vcard(false)),
+            ContactFormat::VCard4 => write!(f, "{}", self.vcard(true)),
+            ContactFormat::Mecard => write!(f, "{}", self.mecard()),
+        }
+    }
+}
+
+#[cfg(test)]
+fn jane() -> Contact {
+    Contact::new("Jane", "Doe")
+        .org("Example, Inc.")
+        .phone("+1 555 0100")
+        .email("jane@example.com")
+        .address(Address {
+            street: "1 Main St".to_string(),
+            city: "Springfield".to_string(),
+            country: "USA".to_string(),
+            ..Address::default()
+        })
+        .url("https://example.com")
+}
+
+#[test]
+fn test_vcard() {
+    assert_eq!(
+        jane().title("Engineer; R&D").to_string(),
+        "BEGIN:VCARD\r\n\
+        VERSION:3.0\r\n\
+        N:Doe;Jane;;;\r\n\
+        FN:Jane Doe\r\n\
+        ORG:Example\\, Inc.\r\n\
+        TITLE:Engineer\\; R&D\r\n\
+        TEL:+1 555 0100\r\n\
+        EMAIL;TYPE=INTERNET:jane@example.com\r\n\
+        ADR:;;1 Main St;Springfield;;;USA\r\n\
+        URL:https://example.com\r\n\
+        END:VCARD\r\n"
+    );
+    assert_eq!(
+        Contact::new("", "Doe").phone("1").email("a@b.c").photo_url("https://example.com/a.png").format(ContactFormat::VCard4).to_string(),
+        "BEGIN:VCARD\r\nVERSION:4.0\r\nN:Doe;;;;\r\nFN:Doe\r\nTEL:1\r\nEMAIL:a@b.c\r\nPHOTO:https://example.com/a.png\r\nEND:VCARD\r\n"
+    );
+    assert!(Contact::new("A", "B").photo_url("x").to_string().contains("\r\nPHOTO;VALUE=uri:x\r\n"));
+    assert!(Contact::new("A", "B").title("line 1\nline 2").to_string().contains("\r\nTITLE:line 1\\nline 2\r\n"));
+}
+
+#[test]
+fn test_mecard() {
+    assert_eq!(
+        jane().title("dropped").format(ContactFormat::Mecard).to_string(),
+        "MECARD:N:Doe,Jane;TEL:+1 555 0100;EMAIL:jane@example.com;ADR:,,1 Main St,Springfield,,,USA;\
+        URL:https\\://example.com;ORG:Example\\, Inc.;;"
+    );
+}
+
+#[test]
+fn test_compact() {
+    let encoder = Encoder::new().ec_level("M");
+
+    // 200 bytes of vCard need version 10, 136 bytes of MECARD fit in version 8
+    assert_eq!(encoder.detect_version(&jane().to_string()), Ok(10));
+    assert_eq!(jane().compact(true).payload(&encoder).unwrap(), jane().format(ContactFormat::Mecard).to_string());
+    assert_eq!(jane().payload(&encoder).unwrap(), jane().to_string());
+
+    // MECARD can't hold the title
+    assert_eq!(jane().title("Engineer").compact(true).payload(&encoder).unwrap(), jane().title("Engineer").to_string());
+
+    // same version, the vCard stays
+    assert!(jane().compact(true).payload(&Encoder::new().version(10)).unwrap().starts_with("BEGIN:VCARD"));
+
+    // only MECARD fits
+    let encoder = Encoder::new().ec_level("M").max_version(9);
+    assert!(jane().compact(true).payload(&encoder).unwrap().starts_with("MECARD:"));
+    assert!(jane().url(&"x".repeat(200)).compact(true).payload(&encoder).is_err());
+}
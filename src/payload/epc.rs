@@ -0,0 +1,218 @@
+use crate::prelude::*;
+
+use crate::encoder::{Encoder, Matrix};
+
+use super::{
+    bank::{amount, check_len, creditor_reference, format_amount, iban},
+    error::PaymentError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EpcCharset {
+    Utf8,
+    // ISO 8859-1
+    Latin1,
+}
+
+// EPC069-12 SEPA credit transfer, known as GiroCode
+#[derive(Debug, Clone, PartialEq)]
+pub struct Epc {
+    name: String,
+    iban: String,
+    bic: Option<String>,
+    amount: Option<String>,
+    // ISO 20022 purpose code, e.g. CHAR, GDDS
+    purpose: Option<String>,
+    // structured ISO 11649 creditor reference, or unstructured text
+    reference: Option<String>,
+    text: Option<String>,
+    information: Option<String>,
+    charset: EpcCharset,
+}
+
+impl Epc {
+    pub fn new(name: &str, iban: &str) -> Epc {
+        Epc {
+            name: name.to_string(),
+            iban: iban.to_string(),
+            bic: None,
+            amount: None,
+            purpose: None,
+            reference: None,
+            text: None,
+            information: None,
+            charset: EpcCharset::Utf8,
+        }
+    }
+
+    pub fn bic(mut self, bic: &str) -> Epc {
+        self.bic = Some(bic.to_string());
+
+        self
+    }
+
+    // in euros, e.g. `12.5`
+    pub fn amount(mut self, amount: &str) -> Epc {
+        self.amount = Some(amount.to_string());
+
+        self
+    }
+
+    pub fn purpose(mut self, purpose: &str) -> Epc {
+        self.purpose = Some(purpose.to_string());
+
+        self
+    }
+
+    // `RF...`, takes the place of `text`
+    pub fn reference(mut self, reference: &str) -> Epc {
+        self.reference = Some(reference.to_string());
+
+        self
+    }
+
+    pub fn text(mut self, text: &str) -> Epc {
+        self.text = Some(text.to_string());
+
+        self
+    }
+
+    // beneficiary to originator information
+    pub fn information(mut self, information: &str) -> Epc {
+        self.information = Some(information.to_string());
+
+        self
+    }
+
+    pub fn charset(mut self, charset: EpcCharset) -> Epc {
+        self.charset = charset;
+
+        self
+    }
+
+    // lines of the payload, the empty ones at the end are left out
+    fn lines(&self) -> Result<Vec<String>, PaymentError> {
+        if self.name.trim().is_empty() { return Err(PaymentError::InvalidField("name")); }
+        check_len("name", &self.name, 70)?;
+
+        let bic = match &self.bic {
+            Some(bic) => {
+                let bic = bic.to_ascii_uppercase();
+                let bytes = bic.as_bytes();
+                if !(bytes.len() == 8 || bytes.len() == 11)
+                    || !bytes[..6].iter().all(u8::is_ascii_uppercase)
+                    || !bytes.iter().all(u8::is_ascii_alphanumeric)
+                {
+                    return Err(PaymentError::InvalidBic);
+                }
+
+                bic
+            }
+            None => String::new(),
+        };
+        let amount = match &self.amount {
+            Some(euros) => format!("EUR{}", format_amount(amount(euros)?)),
+            None => String::new(),
+        };
+        let purpose = self.purpose.clone().unwrap_or_default();
+        let is_code = purpose.len() == 4 && purpose.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+        if !(purpose.is_empty() || is_code) {
+            return Err(PaymentError::InvalidField("purpose"));
+        }
+        let (reference, text) = match (&self.reference, &self.text) {
+            (Some(_), Some(_)) => return Err(PaymentError::InvalidReference),
+            (Some(reference), None) => (creditor_reference(reference)?, String::new()),
+            (None, text) => (String::new(), text.clone().unwrap_or_default()),
+        };
+        check_len("text", &text, 140)?;
+        let information = self.information.clone().unwrap_or_default();
+        check_len("information", &information, 70)?;
+
+        let mut lines = vec![
+            "BCD".to_string(),
+            "002".to_string(),
+            match self.charset {
+                EpcCharset::Utf8 => "1",
+                EpcCharset::Latin1 => "2",
+            }.to_string(),
+            "SCT".to_string(),
+            bic,
+            self.name.clone(),
+            iban(&self.iban)?,
+            amount,
+            purpose,
+            reference,
+            text,
+            information,
+        ];
+        while lines.last().is_some_and(String::is_empty) { lines.pop(); }
+
+        Ok(lines)
+    }
+
+    // lines joined by LF in the charset, 331 bytes at most
+    pub fn payload(&self) -> Result<Vec<u8>, PaymentError> {
+        let payload = self.lines()?.join("\n");
+        let payload = match self.charset {
+            EpcCharset::Utf8 => payload.into_bytes(),
+            EpcCharset::Latin1 => {
+                if let Some(c) = payload.chars().find(|&c| c as u32 > 0xff) {
+                    return Err(PaymentError::InvalidCharacter { field: "payload", c });
+                }
+
+                payload.chars().map(|c| c as u8).collect()
+            }
+        };
+
+        if payload.len() > 331 { return Err(PaymentError::TooLong { field: "payload", max: 331 }); }
+
+        Ok(payload)
+    }
+
+    // ec level M and version 13 at most, as EPC069-12 asks
+    pub fn encode(&self) -> Result<Matrix, PaymentError> {
+        Ok(Encoder::new().ec_level("M").max_version(13).encode_bytes(&self.payload()?)?.as_matrix())
+    }
+}
+
+#[test]
+fn test_epc() {
+    let epc = Epc::new("Red Cross", "DE89 3704 0044 0532 0130 00").bic("COBADEFFXXX").amount("12.3").text("Donation");
+    assert_eq!(
+        epc.payload().unwrap(),
+        b"BCD\n002\n1\nSCT\nCOBADEFFXXX\nRed Cross\nDE89370400440532013000\nEUR12.30\n\n\nDonation"
+    );
+
+    // the empty lines at the end are left out
+    assert_eq!(
+        Epc::new("Müller", "DE89370400440532013000").charset(EpcCharset::Latin1).purpose("CHAR").reference("RF18539007547034").payload().unwrap(),
+        b"BCD\n002\n2\nSCT\n\nM\xfcller\nDE89370400440532013000\n\nCHAR\nRF18539007547034"
+    );
+
+    let decoded = crate::decode(&epc.encode().unwrap()).unwrap();
+    assert_eq!((decoded.ec_level, decoded.data), ('M', epc.payload().unwrap()));
+}
+
+#[test]
+fn test_epc_errors() {
+    let epc = Epc::new("Red Cross", "DE89370400440532013000");
+
+    for (epc, e) in [
+        (Epc::new(" ", "DE89370400440532013000"), PaymentError::InvalidField("name")),
+        (Epc::new("Red Cross", "DE89370400440532013001"), PaymentError::InvalidIban),
+        (epc.clone().bic("COBADE"), PaymentError::InvalidBic),
+        (epc.clone().amount("0"), PaymentError::InvalidAmount),
+        (epc.clone().amount("1000000000"), PaymentError::InvalidAmount),
+        (epc.clone().purpose("charity"), PaymentError::InvalidField("purpose")),
+        (epc.clone().reference("RF00539007547034"), PaymentError::InvalidReference),
+        (epc.clone().reference("RF18539007547034").text("both"), PaymentError::InvalidReference),
+        (epc.clone().text(&"a".repeat(141)), PaymentError::TooLong { field: "text", max: 140 }),
+        (epc.clone().charset(EpcCharset::Latin1).text("€"), PaymentError::InvalidCharacter { field: "payload", c: '€' }),
+    ].iter() {
+        assert_eq!(epc.payload().as_ref(), Err(e));
+    }
+
+    // 3 bytes per euro sign
+    let epc = epc.text(&"a".repeat(140)).information(&"€".repeat(70));
+    assert_eq!(epc.payload(), Err(PaymentError::TooLong { field: "payload", max: 331 }));
+}
@@ -0,0 +1,45 @@
+use core::fmt;
+
+use crate::encoder::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaymentError {
+    // length, country or checksum of the IBAN is wrong, or the country isn't allowed by the format
+    InvalidIban,
+    // not 8 or 11 characters of a BIC
+    InvalidBic,
+    // not 0.01 ~ 999999999.99 with at most 2 decimals
+    InvalidAmount,
+    // check digits of the reference are wrong, or its type doesn't go with the IBAN
+    InvalidReference,
+    // a required field is empty or a fixed format field is wrong, with the field
+    InvalidField(&'static str),
+    // a character out of the character set of the format, with the field
+    InvalidCharacter { field: &'static str, c: char },
+    // a field is longer than the format allows, with the max characters
+    TooLong { field: &'static str, max: usize },
+    // the payload doesn't fit in the symbols the format allows
+    Encode(Error),
+}
+
+impl From<Error> for PaymentError {
+    fn from(e: Error) -> PaymentError { PaymentError::Encode(e) }
+}
+
+impl fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaymentError::InvalidIban => write!(f, "invalid IBAN"),
+            PaymentError::InvalidBic => write!(f, "invalid BIC"),
+            PaymentError::InvalidAmount => write!(f, "amount is not 0.01 ~ 999999999.99"),
+            PaymentError::InvalidReference => write!(f, "invalid reference"),
+            PaymentError::InvalidField(field) => write!(f, "invalid {}", field),
+            PaymentError::InvalidCharacter { field, c } => write!(f, "{} can't contain {:?}", field, c),
+            PaymentError::TooLong { field, max } => write!(f, "{} is longer than {} characters", field, max),
+            PaymentError::Encode(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PaymentError {}
@@ -0,0 +1,88 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::{escape, fold, Payload};
+
+// iCalendar `VEVENT` of RFC 5545
+//
+// times are `YYYYMMDDTHHMMSS` in local time, `YYYYMMDDTHHMMSSZ` in UTC, or `YYYYMMDD` for a whole day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    summary: String,
+    start: String,
+    end: Option<String>,
+    location: Option<String>,
+    description: Option<String>,
+}
+
+// TEXT values, newlines become `\n`
+fn text(value: &str) -> String { escape(value, &['\\', ';', ',']).replace("\r\n", "\\n").replace('\n', "\\n") }
+
+// `VALUE=DATE` for a whole day
+fn time(name: &str, time: &str) -> String {
+    if time.len() == 8 { format!("{};VALUE=DATE:{}", name, time) } else { format!("{}:{}", name, time) }
+}
+
+impl Event {
+    pub fn new(summary: &str, start: &str) -> Event {
+        Event {
+            summary: summary.to_string(),
+            start: start.to_string(),
+            end: None,
+            location: None,
+            description: None,
+        }
+    }
+
+    pub fn end(mut self, end: &str) -> Event {
+        self.end = Some(end.to_string());
+
+        self
+    }
+
+    pub fn location(mut self, location: &str) -> Event {
+        self.location = Some(location.to_string());
+
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Event {
+        self.description = Some(description.to_string());
+
+        self
+    }
+}
+
+// the longest of the URI payloads, density comes first
+impl Payload for Event {
+    fn ec_level(&self) -> &'static str { "L" }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = vec!["BEGIN:VEVENT".to_string(), format!("SUMMARY:{}", text(&self.summary)), time("DTSTART", &self.start)];
+        if let Some(end) = &self.end { lines.push(time("DTEND", end)); }
+        if let Some(location) = &self.location { lines.push(format!("LOCATION:{}", text(location))); }
+        if let Some(description) = &self.description { lines.push(format!("DESCRIPTION:{}", text(description))); }
+        lines.push("END:VEVENT".to_string());
+
+        for line in lines.iter() { write!(f, "{}", fold(line))?; }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_event() {
+    assert_eq!(
+        Event::new("Launch; party", "20250601T170000Z").end("20250601T200000Z").location("Room 1, HQ").to_string(),
+        "BEGIN:VEVENT\r\nSUMMARY:Launch\\; party\r\nDTSTART:20250601T170000Z\r\nDTEND:20250601T200000Z\r\n\
+        LOCATION:Room 1\\, HQ\r\nEND:VEVENT\r\n"
+    );
+    assert_eq!(
+        Event::new("Holiday", "20251225").description("a\nb").to_string(),
+        "BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20251225\r\nDESCRIPTION:a\\nb\r\nEND:VEVENT\r\n"
+    );
+    assert!(Event::new("x", "20250101").description(&"a".repeat(100)).to_string().contains("\r\n a"));
+}
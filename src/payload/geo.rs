@@ -0,0 +1,70 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::{percent_encode, Payload};
+
+// `geo:<latitude>,<longitude>[,<altitude>][?q=<query>]` of RFC 5870, the query is read by Android maps
+#[derive(Debug, Clone, PartialEq)]
+pub struct Geo {
+    latitude: f64,
+    longitude: f64,
+    // meters
+    altitude: Option<f64>,
+    query: Option<String>,
+    // `GEO:`
+    uppercase: bool,
+}
+
+impl Geo {
+    // degrees of WGS 84, clamped to -90 ~ 90 and -180 ~ 180
+    pub fn new(latitude: f64, longitude: f64) -> Geo {
+        Geo {
+            latitude: latitude.clamp(-90., 90.),
+            longitude: longitude.clamp(-180., 180.),
+            altitude: None,
+            query: None,
+            uppercase: false,
+        }
+    }
+
+    pub fn altitude(mut self, altitude: f64) -> Geo {
+        self.altitude = Some(altitude);
+
+        self
+    }
+
+    pub fn query(mut self, query: &str) -> Geo {
+        self.query = Some(query.to_string());
+
+        self
+    }
+
+    pub fn uppercase(mut self, uppercase: bool) -> Geo {
+        self.uppercase = uppercase;
+
+        self
+    }
+}
+
+// a few dozen characters, H still fits in a small version
+impl Payload for Geo {
+    fn ec_level(&self) -> &'static str { "H" }
+}
+
+impl fmt::Display for Geo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{},{}", if self.uppercase { "GEO" } else { "geo" }, self.latitude, self.longitude)?;
+        if let Some(altitude) = self.altitude { write!(f, ",{}", altitude)?; }
+        if let Some(query) = &self.query { write!(f, "?q={}", percent_encode(query, b","))?; }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_geo() {
+    assert_eq!(Geo::new(37.786971, -122.399677).to_string(), "geo:37.786971,-122.399677");
+    assert_eq!(Geo::new(91., 0.5).altitude(12.5).uppercase(true).to_string(), "GEO:90,0.5,12.5");
+    assert_eq!(Geo::new(46.2, 6.1).query("Palais des Nations").to_string(), "geo:46.2,6.1?q=Palais%20des%20Nations");
+}
@@ -0,0 +1,111 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::{percent_encode, Payload};
+
+// `mailto:<to>?cc=<cc>&bcc=<bcc>&subject=<subject>&body=<body>` of RFC 6068
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mailto {
+    to: Vec<String>,
+    cc: Vec<String>,
+    bcc: Vec<String>,
+    subject: Option<String>,
+    body: Option<String>,
+    // `MAILTO:` and the domains of the addresses in upper case
+    uppercase: bool,
+}
+
+impl Mailto {
+    pub fn new(to: &str) -> Mailto {
+        Mailto {
+            to: vec![to.to_string()],
+            cc: vec![],
+            bcc: vec![],
+            subject: None,
+            body: None,
+            uppercase: false,
+        }
+    }
+
+    // every call adds one
+    pub fn to(mut self, to: &str) -> Mailto {
+        self.to.push(to.to_string());
+
+        self
+    }
+
+    // every call adds one
+    pub fn cc(mut self, cc: &str) -> Mailto {
+        self.cc.push(cc.to_string());
+
+        self
+    }
+
+    // every call adds one
+    pub fn bcc(mut self, bcc: &str) -> Mailto {
+        self.bcc.push(bcc.to_string());
+
+        self
+    }
+
+    pub fn subject(mut self, subject: &str) -> Mailto {
+        self.subject = Some(subject.to_string());
+
+        self
+    }
+
+    // line breaks become CRLF as RFC 6068 asks
+    pub fn body(mut self, body: &str) -> Mailto {
+        self.body = Some(body.replace("\r\n", "\n").replace('\n', "\r\n"));
+
+        self
+    }
+
+    pub fn uppercase(mut self, uppercase: bool) -> Mailto {
+        self.uppercase = uppercase;
+
+        self
+    }
+
+    // addresses joined by commas, the domain is case-insensitive but the local part is not
+    fn addresses(&self, addresses: &[String]) -> String {
+        addresses.iter()
+            .map(|address| match address.rfind('@') {
+                Some(i) if self.uppercase => format!("{}@{}", percent_encode(&address[..i], b""), address[i + 1..].to_ascii_uppercase()),
+                _ => percent_encode(address, b"@"),
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+// subject and body make it longer
+impl Payload for Mailto {
+    fn ec_level(&self) -> &'static str { "M" }
+}
+
+impl fmt::Display for Mailto {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", if self.uppercase { "MAILTO" } else { "mailto" }, self.addresses(&self.to))?;
+
+        let mut fields = vec![];
+        if !self.cc.is_empty() { fields.push(format!("cc={}", self.addresses(&self.cc))); }
+        if !self.bcc.is_empty() { fields.push(format!("bcc={}", self.addresses(&self.bcc))); }
+        if let Some(subject) = &self.subject { fields.push(format!("subject={}", percent_encode(subject, b""))); }
+        if let Some(body) = &self.body { fields.push(format!("body={}", percent_encode(body, b""))); }
+        if !fields.is_empty() { write!(f, "?{}", fields.join("&"))?; }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_mailto() {
+    assert_eq!(Mailto::new("jane@example.com").to_string(), "mailto:jane@example.com");
+    assert_eq!(
+        Mailto::new("jane@example.com").to("john@example.com").cc("boss@example.com").subject("Q&A: 100%").body("Hi,\nsee you").to_string(),
+        "mailto:jane@example.com,john@example.com?cc=boss@example.com&subject=Q%26A%3A%20100%25&body=Hi%2C%0D%0Asee%20you"
+    );
+    assert_eq!(Mailto::new("Jane+news@example.com").uppercase(true).to_string(), "MAILTO:Jane%2Bnews@EXAMPLE.COM");
+}
@@ -0,0 +1,127 @@
+// text of the common payloads which scanners act on, to be encoded by `Encoder::encode`
+//
+// `Encoder::split` keeps the alphanumeric and numeric runs of a payload in their own segments
+
+use core::fmt;
+
+use crate::prelude::*;
+
+use crate::encoder::Encoder;
+
+mod bank;
+mod contact;
+mod epc;
+mod error;
+mod event;
+mod geo;
+mod mailto;
+mod otpauth;
+mod sms;
+mod swiss;
+mod tel;
+mod url;
+mod wifi;
+
+pub use self::{
+    contact::{Address, Contact, ContactFormat},
+    epc::{Epc, EpcCharset},
+    error::PaymentError,
+    event::Event,
+    geo::Geo,
+    mailto::Mailto,
+    otpauth::{Algorithm, Otpauth},
+    sms::Sms,
+    swiss::{SwissAddress, SwissQrBill, SwissReference},
+    tel::Tel,
+    url::OptimizedUrl,
+    wifi::{Security, Wifi},
+};
+
+// a payload as text, with the ec level which suits it
+pub trait Payload: fmt::Display {
+    // as `Encoder::ec_level` takes
+    fn ec_level(&self) -> &'static str;
+
+    // an encoder in the advised ec level which splits the payload into its best modes
+    fn encoder(&self) -> Encoder { Encoder::new().ec_level(self.ec_level()).split(true) }
+}
+
+// a backslash before every character in `special`
+pub(crate) fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) { escaped.push('\\'); }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// lines end with CRLF, lines longer than 75 octets continue on lines which start with a space, characters are not
+// split
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 74 * 3 + 2);
+    let mut octets = 0;
+
+    for c in line.chars() {
+        if octets + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
+
+// `%XX` in upper case for every byte which isn't an unreserved character of RFC 3986 or in `keep`
+pub(crate) fn percent_encode(value: &str, keep: &[u8]) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for &b in value.as_bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) || keep.contains(&b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+
+    encoded
+}
+
+#[test]
+fn test_escape() {
+    assert_eq!(escape(r#"a\b;c,d:e"f"#, &['\\', ';', ',', ':', '"']), r#"a\\b\;c\,d\:e\"f"#);
+    assert_eq!(escape("plain", &['\\']), "plain");
+}
+
+#[test]
+fn test_fold() {
+    assert_eq!(fold("short"), "short\r\n");
+
+    let folded = fold(&format!("NOTE:{}", "a".repeat(200)));
+    assert!(folded.split("\r\n").all(|line| line.len() <= 75));
+    assert_eq!(folded.replace("\r\n ", ""), format!("NOTE:{}\r\n", "a".repeat(200)));
+
+    // 3 octets per character, the first line holds 5 + 3 * 23 = 74 octets
+    let folded = fold(&format!("NOTE:{}", "点".repeat(30)));
+    assert_eq!(folded, format!("NOTE:{}\r\n {}\r\n", "点".repeat(23), "点".repeat(7)));
+}
+
+#[test]
+fn test_percent_encode() {
+    assert_eq!(percent_encode("a b&c=d/é~", &[]), "a%20b%26c%3Dd%2F%C3%A9~");
+    assert_eq!(percent_encode("a@b.c", b"@"), "a@b.c");
+}
+
+#[test]
+fn test_payload_encoder() {
+    let payload = Tel::new("+41 44 668 18 00").uppercase(true);
+    let matrix = payload.encoder().encode(&payload.to_string()).unwrap().as_matrix();
+    let decoded = crate::decode(&matrix).unwrap();
+
+    assert_eq!(decoded.ec_level, 'H');
+    assert_eq!(decoded.data, b"TEL:+41446681800");
+}
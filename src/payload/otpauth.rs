@@ -0,0 +1,152 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::{percent_encode, Payload};
+
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// RFC 4648 without padding, as the key URI format asks
+fn base32(bytes: &[u8]) -> String {
+    let mut base32 = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut buffer, mut bits) = (0u16, 0);
+
+    for &b in bytes {
+        buffer = buffer << 8 | b as u16;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            base32.push(BASE32[(buffer >> bits & 0x1f) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 { base32.push(BASE32[(buffer << (5 - bits) & 0x1f) as usize] as char); }
+
+    base32
+}
+
+// HMAC hash of the one-time passwords
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+// `otpauth://totp/<issuer>:<account>?secret=<secret>&issuer=<issuer>` of the Google Authenticator key URI format
+#[derive(Debug, Clone, PartialEq)]
+pub struct Otpauth {
+    secret: Vec<u8>,
+    issuer: String,
+    account: String,
+    // left out if SHA1
+    algorithm: Algorithm,
+    // left out if 6
+    digits: u32,
+    // seconds, left out if 30
+    period: u32,
+    // `OTPAUTH://TOTP/`
+    uppercase: bool,
+}
+
+impl Otpauth {
+    // `secret` as raw bytes, it is written in Base32
+    pub fn totp(secret: &[u8], issuer: &str, account: &str) -> Otpauth {
+        Otpauth {
+            secret: secret.to_vec(),
+            issuer: issuer.to_string(),
+            account: account.to_string(),
+            algorithm: Algorithm::Sha1,
+            digits: 6,
+            period: 30,
+            uppercase: false,
+        }
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Otpauth {
+        self.algorithm = algorithm;
+
+        self
+    }
+
+    pub fn digits(mut self, digits: u32) -> Otpauth {
+        self.digits = digits;
+
+        self
+    }
+
+    pub fn period(mut self, period: u32) -> Otpauth {
+        self.period = period;
+
+        self
+    }
+
+    pub fn uppercase(mut self, uppercase: bool) -> Otpauth {
+        self.uppercase = uppercase;
+
+        self
+    }
+}
+
+// shown on screens which don't get damaged, the secret makes it long
+impl Payload for Otpauth {
+    fn ec_level(&self) -> &'static str { "L" }
+}
+
+impl fmt::Display for Otpauth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", if self.uppercase { "OTPAUTH://TOTP/" } else { "otpauth://totp/" })?;
+
+        // a colon in the issuer or the account would split the label in the wrong place
+        if !self.issuer.is_empty() { write!(f, "{}:", percent_encode(&self.issuer, b""))?; }
+        write!(f, "{}?secret={}", percent_encode(&self.account, b"@"), base32(&self.secret))?;
+        if !self.issuer.is_empty() { write!(f, "&issuer={}", percent_encode(&self.issuer, b""))?; }
+
+        match self.algorithm {
+            Algorithm::Sha1 => (),
+            Algorithm::Sha256 => write!(f, "&algorithm=SHA256")?,
+            Algorithm::Sha512 => write!(f, "&algorithm=SHA512")?,
+        }
+        if self.digits != 6 { write!(f, "&digits={}", self.digits)?; }
+        if self.period != 30 { write!(f, "&period={}", self.period)?; }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_base32() {
+    for &(bytes, base32_str) in [
+        (&b""[..], ""),
+        (&b"f"[..], "MY"),
+        (&b"fo"[..], "MZXQ"),
+        (&b"foo"[..], "MZXW6"),
+        (&b"foob"[..], "MZXW6YQ"),
+        (&b"fooba"[..], "MZXW6YTB"),
+        (&b"foobar"[..], "MZXW6YTBOI"),
+    ].iter() {
+        assert_eq!(base32(bytes), base32_str);
+    }
+}
+
+#[test]
+fn test_otpauth() {
+    assert_eq!(
+        Otpauth::totp(b"12345678901234567890", "ACME Co", "jane@example.com").to_string(),
+        "otpauth://totp/ACME%20Co:jane@example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=ACME%20Co"
+    );
+    assert_eq!(
+        Otpauth::totp(b"key", "a:b", "").algorithm(Algorithm::Sha256).digits(8).period(60).uppercase(true).to_string(),
+        "OTPAUTH://TOTP/a%3Ab:?secret=NNSXS&issuer=a%3Ab&algorithm=SHA256&digits=8&period=60"
+    );
+    assert_eq!(Otpauth::totp(b"key", "", "jane").to_string(), "otpauth://totp/jane?secret=NNSXS");
+    assert_eq!(
+        Otpauth::totp(b"key", "", "jane").algorithm(Algorithm::Sha512).to_string(),
+        "otpauth://totp/jane?secret=NNSXS&algorithm=SHA512"
+    );
+    assert_eq!(
+        Otpauth::totp(b"key", "", "jane").algorithm(Algorithm::Sha1).to_string(),
+        "otpauth://totp/jane?secret=NNSXS"
+    );
+}
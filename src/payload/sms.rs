@@ -0,0 +1,47 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::{tel::phone_number, Payload};
+
+// `SMSTO:<number>:<message>`, the message is taken as it is up to the end
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sms {
+    number: String,
+    message: Option<String>,
+}
+
+impl Sms {
+    pub fn new(number: &str) -> Sms {
+        Sms {
+            number: phone_number(number),
+            message: None,
+        }
+    }
+
+    pub fn message(mut self, message: &str) -> Sms {
+        self.message = Some(message.to_string());
+
+        self
+    }
+}
+
+// the message makes it longer than a number alone
+impl Payload for Sms {
+    fn ec_level(&self) -> &'static str { "Q" }
+}
+
+impl fmt::Display for Sms {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SMSTO:{}", self.number)?;
+        if let Some(message) = &self.message { write!(f, ":{}", message)?; }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sms() {
+    assert_eq!(Sms::new("+1 555 0100").to_string(), "SMSTO:+15550100");
+    assert_eq!(Sms::new("12345").message("STOP: now").to_string(), "SMSTO:12345:STOP: now");
+}
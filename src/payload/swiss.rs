@@ -0,0 +1,262 @@
+use crate::prelude::*;
+
+use crate::encoder::{Encoder, Matrix};
+
+use super::{
+    bank::{amount, check_len, creditor_reference, format_amount, iban},
+    error::PaymentError,
+};
+
+// structured address of the creditor or the debtor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SwissAddress {
+    pub name: String,
+    pub street: String,
+    pub building_number: String,
+    pub postal_code: String,
+    pub town: String,
+    // ISO 3166-1 alpha-2, e.g. CH
+    pub country: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwissReference {
+    // QRR, 27 digits with a MOD 10 recursive check digit, only with a QR-IBAN
+    Qr(String),
+    // SCOR, ISO 11649 creditor reference
+    Creditor(String),
+    // NON
+    None,
+}
+
+// Swiss Payment Standards QR-bill, version 2.2 with structured addresses
+//
+// render it with `Renderer::swiss_cross`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwissQrBill {
+    iban: String,
+    creditor: SwissAddress,
+    amount: Option<String>,
+    currency: String,
+    debtor: Option<SwissAddress>,
+    reference: SwissReference,
+    message: Option<String>,
+    bill_information: Option<String>,
+}
+
+// Latin characters which the standard allows
+fn is_allowed(c: char) -> bool {
+    matches!(c, ' '..='~' | '\u{a0}'..='\u{17f}' | 'Ș' | 'ș' | 'Ț' | 'ț' | '€')
+}
+
+fn text(field: &'static str, value: &str, max: usize) -> Result<String, PaymentError> {
+    if let Some(c) = value.chars().find(|&c| !is_allowed(c)) { return Err(PaymentError::InvalidCharacter { field, c }); }
+    check_len(field, value, max)?;
+
+    Ok(value.to_string())
+}
+
+fn address(lines: &mut Vec<String>, address: &SwissAddress) -> Result<(), PaymentError> {
+    if address.name.trim().is_empty() { return Err(PaymentError::InvalidField("name")); }
+    if address.town.trim().is_empty() { return Err(PaymentError::InvalidField("town")); }
+    if !(address.country.len() == 2 && address.country.bytes().all(|b| b.is_ascii_uppercase())) {
+        return Err(PaymentError::InvalidField("country"));
+    }
+
+    lines.extend_from_slice(&[
+        "S".to_string(),
+        text("name", &address.name, 70)?,
+        text("street", &address.street, 70)?,
+        text("building number", &address.building_number, 16)?,
+        text("postal code", &address.postal_code, 16)?,
+        text("town", &address.town, 35)?,
+        address.country.clone(),
+    ]);
+
+    Ok(())
+}
+
+// 27 digits, the last one is the MOD 10 recursive check digit of the others
+fn qr_reference(reference: &str) -> Result<String, PaymentError> {
+    const CARRY: [u32; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+
+    let reference = reference.chars().filter(|&c| c != ' ').collect::<String>();
+    let digits = reference.chars().map(|c| c.to_digit(10)).collect::<Option<Vec<u32>>>().ok_or(PaymentError::InvalidReference)?;
+    if digits.len() != 27 { return Err(PaymentError::InvalidReference); }
+
+    let carry = digits[..26].iter().fold(0, |carry, &digit| CARRY[((carry + digit) % 10) as usize]);
+    if (10 - carry) % 10 == digits[26] { Ok(reference) } else { Err(PaymentError::InvalidReference) }
+}
+
+impl SwissQrBill {
+    pub fn new(iban: &str, creditor: SwissAddress) -> SwissQrBill {
+        SwissQrBill {
+            iban: iban.to_string(),
+            creditor,
+            amount: None,
+            currency: "CHF".to_string(),
+            debtor: None,
+            reference: SwissReference::None,
+            message: None,
+            bill_information: None,
+        }
+    }
+
+    // left open for the debtor if it isn't set
+    pub fn amount(mut self, amount: &str) -> SwissQrBill {
+        self.amount = Some(amount.to_string());
+
+        self
+    }
+
+    // CHF or EUR, CHF by default
+    pub fn currency(mut self, currency: &str) -> SwissQrBill {
+        self.currency = currency.to_string();
+
+        self
+    }
+
+    pub fn debtor(mut self, debtor: SwissAddress) -> SwissQrBill {
+        self.debtor = Some(debtor);
+
+        self
+    }
+
+    pub fn reference(mut self, reference: SwissReference) -> SwissQrBill {
+        self.reference = reference;
+
+        self
+    }
+
+    // unstructured message, 140 characters with `bill_information`
+    pub fn message(mut self, message: &str) -> SwissQrBill {
+        self.message = Some(message.to_string());
+
+        self
+    }
+
+    pub fn bill_information(mut self, bill_information: &str) -> SwissQrBill {
+        self.bill_information = Some(bill_information.to_string());
+
+        self
+    }
+
+    // lines joined by LF, the last one doesn't end with LF
+    pub fn payload(&self) -> Result<String, PaymentError> {
+        let iban = iban(&self.iban)?;
+        if !(iban.starts_with("CH") || iban.starts_with("LI")) || iban.len() != 21 { return Err(PaymentError::InvalidIban); }
+        // institution ids 30000 ~ 31999 are QR-IBANs, which take QR references only
+        let qr_iban = (30000..=31999).contains(&iban[4..9].parse::<u32>().unwrap_or(0));
+
+        let mut lines = vec!["SPC".to_string(), "0200".to_string(), "1".to_string(), iban];
+        address(&mut lines, &self.creditor)?;
+        // ultimate creditor, for future use
+        lines.resize(lines.len() + 7, String::new());
+
+        lines.push(match &self.amount {
+            Some(amount_str) => format_amount(amount(amount_str)?),
+            None => String::new(),
+        });
+        if self.currency != "CHF" && self.currency != "EUR" { return Err(PaymentError::InvalidField("currency")); }
+        lines.push(self.currency.clone());
+
+        match &self.debtor {
+            Some(debtor) => address(&mut lines, debtor)?,
+            None => lines.resize(lines.len() + 7, String::new()),
+        }
+
+        let (reference_type, reference) = match &self.reference {
+            SwissReference::Qr(reference) if qr_iban => ("QRR", qr_reference(reference)?),
+            SwissReference::Creditor(reference) if !qr_iban => ("SCOR", creditor_reference(reference)?),
+            SwissReference::None if !qr_iban => ("NON", String::new()),
+            _ => return Err(PaymentError::InvalidReference),
+        };
+        lines.push(reference_type.to_string());
+        lines.push(reference);
+
+        let message = text("message", self.message.as_deref().unwrap_or(""), 140)?;
+        let bill_information = text("bill information", self.bill_information.as_deref().unwrap_or(""), 140)?;
+        check_len("message", &format!("{}{}", message, bill_information), 140)?;
+        lines.push(message);
+        lines.push("EPD".to_string());
+        if !bill_information.is_empty() { lines.push(bill_information); }
+
+        Ok(lines.join("\n"))
+    }
+
+    // ec level M and version 25 at most, as the standard asks
+    pub fn encode(&self) -> Result<Matrix, PaymentError> {
+        Ok(Encoder::new().ec_level("M").max_version(25).encode_bytes(self.payload()?.as_bytes())?.as_matrix())
+    }
+}
+
+#[cfg(test)]
+fn creditor() -> SwissAddress {
+    SwissAddress {
+        name: "Robert Schneider AG".to_string(),
+        street: "Rue du Lac".to_string(),
+        building_number: "1268".to_string(),
+        postal_code: "2501".to_string(),
+        town: "Biel".to_string(),
+        country: "CH".to_string(),
+    }
+}
+
+#[test]
+fn test_swiss_qr_bill() {
+    let debtor = SwissAddress {
+        name: "Pia-Maria Rutschmann-Schnyder".to_string(),
+        street: "Grosse Marktgasse".to_string(),
+        building_number: "28".to_string(),
+        postal_code: "9400".to_string(),
+        town: "Rorschach".to_string(),
+        country: "CH".to_string(),
+    };
+    let bill = SwissQrBill::new("CH44 3199 9123 0008 8901 2", creditor())
+        .amount("1949.75")
+        .debtor(debtor)
+        .reference(SwissReference::Qr("21 00000 00003 13947 14300 09017".to_string()))
+        .message("Order of 15 June 2020")
+        .bill_information("//S1/10/10201409/11/200701/20/140.000-53/30/102673831/31/200615/32/7.7/33/7.7:139.40/40/0:30");
+
+    assert_eq!(
+        bill.payload().unwrap(),
+        "SPC\n0200\n1\nCH4431999123000889012\nS\nRobert Schneider AG\nRue du Lac\n1268\n2501\nBiel\nCH\n\n\n\n\n\n\n\n\
+        1949.75\nCHF\nS\nPia-Maria Rutschmann-Schnyder\nGrosse Marktgasse\n28\n9400\nRorschach\nCH\n\
+        QRR\n210000000003139471430009017\nOrder of 15 June 2020\nEPD\n\
+        //S1/10/10201409/11/200701/20/140.000-53/30/102673831/31/200615/32/7.7/33/7.7:139.40/40/0:30"
+    );
+
+    // without amount and debtor, and the trailer ends the payload
+    let payload = SwissQrBill::new("CH9300762011623852957", creditor()).currency("EUR").payload().unwrap();
+    assert!(payload.ends_with("\n\nEUR\n\n\n\n\n\n\n\nNON\n\n\nEPD"));
+    assert_eq!(payload.lines().count(), 31);
+
+    let decoded = crate::decode(&bill.encode().unwrap()).unwrap();
+    assert_eq!((decoded.ec_level, decoded.data), ('M', bill.payload().unwrap().into_bytes()));
+}
+
+#[test]
+fn test_swiss_qr_bill_errors() {
+    let qr_iban = SwissQrBill::new("CH4431999123000889012", creditor());
+    let iban = SwissQrBill::new("CH9300762011623852957", creditor());
+
+    for (bill, e) in [
+        (SwissQrBill::new("DE89370400440532013000", creditor()), PaymentError::InvalidIban),
+        (SwissQrBill::new("CH9300762011623852958", creditor()), PaymentError::InvalidIban),
+        // QR-IBANs take QR references, other IBANs take the others
+        (qr_iban.clone(), PaymentError::InvalidReference),
+        (qr_iban.clone().reference(SwissReference::Creditor("RF18539007547034".to_string())), PaymentError::InvalidReference),
+        (iban.clone().reference(SwissReference::Qr("210000000003139471430009017".to_string())), PaymentError::InvalidReference),
+        (qr_iban.reference(SwissReference::Qr("210000000003139471430009018".to_string())), PaymentError::InvalidReference),
+        (iban.clone().reference(SwissReference::Creditor("RF19539007547034".to_string())), PaymentError::InvalidReference),
+        (iban.clone().amount("1949.755"), PaymentError::InvalidAmount),
+        (iban.clone().currency("USD"), PaymentError::InvalidField("currency")),
+        (iban.clone().debtor(SwissAddress { country: "Switzerland".to_string(), ..creditor() }), PaymentError::InvalidField("country")),
+        (iban.clone().message("Grüße 😀"), PaymentError::InvalidCharacter { field: "message", c: '😀' }),
+        (iban.clone().message(&"a".repeat(100)).bill_information(&"b".repeat(41)), PaymentError::TooLong { field: "message", max: 140 }),
+        (SwissQrBill::new("CH9300762011623852957", SwissAddress { name: "".to_string(), ..creditor() }), PaymentError::InvalidField("name")),
+    ].iter() {
+        assert_eq!(bill.payload().as_ref(), Err(e));
+    }
+}
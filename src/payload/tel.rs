@@ -0,0 +1,58 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::Payload;
+
+// `+`, digits, `*` and `#`, visual separators and spaces are dropped
+pub(crate) fn phone_number(number: &str) -> String {
+    number.chars().filter(|c| c.is_ascii_digit() || matches!(c, '+' | '*' | '#')).collect()
+}
+
+// `tel:<number>` of RFC 3966
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tel {
+    number: String,
+    // `TEL:`, then a number in international format is all alphanumeric
+    uppercase: bool,
+}
+
+impl Tel {
+    pub fn new(number: &str) -> Tel {
+        Tel {
+            number: phone_number(number),
+            uppercase: false,
+        }
+    }
+
+    pub fn uppercase(mut self, uppercase: bool) -> Tel {
+        self.uppercase = uppercase;
+
+        self
+    }
+}
+
+// a few dozen characters, H still fits in a small version
+impl Payload for Tel {
+    fn ec_level(&self) -> &'static str { "H" }
+}
+
+impl fmt::Display for Tel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", if self.uppercase { "TEL" } else { "tel" }, self.number)
+    }
+}
+
+#[test]
+fn test_tel() {
+    use crate::{capacity::min_version, Segment};
+
+    assert_eq!(Tel::new("+1 (555) 010-0").to_string(), "tel:+15550100");
+
+    let tel = Tel::new("+41 44 668 18 00").uppercase(true);
+    assert_eq!(tel.to_string(), "TEL:+41446681800");
+    assert_eq!(Segment::auto(&tel.to_string()).mode, crate::Mode::Alphanumeric);
+    // 16 alphanumeric characters instead of 16 bytes
+    assert_eq!(min_version(&[Segment::auto(&tel.to_string())], tel.ec_level()), Ok(Some(2)));
+    assert_eq!(min_version(&[Segment::auto(&tel.clone().uppercase(false).to_string())], tel.ec_level()), Ok(Some(3)));
+}
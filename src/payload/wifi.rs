@@ -0,0 +1,174 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+use super::{escape, Payload};
+
+const SPECIAL: [char; 5] = ['\\', ';', ',', ':', '"'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Security {
+    None,
+    Wep,
+    // WPA, WPA2 and WPA3 personal
+    Wpa,
+    // WPA2 enterprise, with the EAP fields
+    Wpa2Eap,
+}
+
+// `WIFI:T:WPA;S:<ssid>;P:<password>;H:true;;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wifi {
+    ssid: String,
+    security: Security,
+    password: Option<String>,
+    hidden: bool,
+
+    // WPA2-EAP:
+    //     E   -> EAP method, e.g. PEAP, TTLS
+    //     A   -> anonymous identity
+    //     I   -> identity
+    //     PH2 -> phase 2 method, e.g. MSCHAPV2
+    eap: Option<String>,
+    anonymous_identity: Option<String>,
+    identity: Option<String>,
+    phase2: Option<String>,
+}
+
+impl Wifi {
+    // an open network
+    pub fn new(ssid: &str) -> Wifi {
+        Wifi {
+            ssid: ssid.to_string(),
+            security: Security::None,
+            password: None,
+            hidden: false,
+            eap: None,
+            anonymous_identity: None,
+            identity: None,
+            phase2: None,
+        }
+    }
+
+    pub fn wep(mut self, key: &str) -> Wifi {
+        self.security = Security::Wep;
+        self.password = Some(key.to_string());
+
+        self
+    }
+
+    pub fn wpa(mut self, password: &str) -> Wifi {
+        self.security = Security::Wpa;
+        self.password = Some(password.to_string());
+
+        self
+    }
+
+    pub fn wpa2_eap(mut self, eap: &str, identity: &str, password: &str) -> Wifi {
+        self.security = Security::Wpa2Eap;
+        self.eap = Some(eap.to_string());
+        self.identity = Some(identity.to_string());
+        self.password = Some(password.to_string());
+
+        self
+    }
+
+    // WPA2-EAP only
+    pub fn anonymous_identity(mut self, anonymous_identity: &str) -> Wifi {
+        self.anonymous_identity = Some(anonymous_identity.to_string());
+
+        self
+    }
+
+    // WPA2-EAP only
+    pub fn phase2(mut self, phase2: &str) -> Wifi {
+        self.phase2 = Some(phase2.to_string());
+
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Wifi {
+        self.hidden = hidden;
+
+        self
+    }
+}
+
+// scanners take a value of hex digits as hex, quotes keep it text
+fn field(f: &mut fmt::Formatter, key: &str, value: &str) -> fmt::Result {
+    if !value.is_empty() && value.len().is_multiple_of(2) && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        write!(f, "{}:\"{}\";", key, value)
+    } else {
+        write!(f, "{}:{};", key, escape(value, &SPECIAL))
+    }
+}
+
+// short, and stuck on walls and tables where it gets scratched
+impl Payload for Wifi {
+    fn ec_level(&self) -> &'static str { "Q" }
+}
+
+impl fmt::Display for Wifi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WIFI:T:{};", match self.security {
+            Security::None => "nopass",
+            Security::Wep => "WEP",
+            Security::Wpa => "WPA",
+            Security::Wpa2Eap => "WPA2-EAP",
+        })?;
+        field(f, "S", &self.ssid)?;
+
+        if self.security != Security::None {
+            if let Some(password) = &self.password { field(f, "P", password)?; }
+        }
+        if self.security == Security::Wpa2Eap {
+            for (key, value) in [
+                ("E", &self.eap),
+                ("A", &self.anonymous_identity),
+                ("I", &self.identity),
+                ("PH2", &self.phase2),
+            ].iter() {
+                if let Some(value) = value { field(f, key, value)?; }
+            }
+        }
+
+        if self.hidden { write!(f, "H:true;")?; }
+
+        write!(f, ";")
+    }
+}
+
+#[test]
+fn test_wifi() {
+    assert_eq!(Wifi::new("guest").to_string(), "WIFI:T:nopass;S:guest;;");
+    assert_eq!(
+        Wifi::new("HOME").wpa("p@ss;word").hidden(true).to_string(),
+        r#"WIFI:T:WPA;S:HOME;P:p@ss\;word;H:true;;"#
+    );
+    assert_eq!(Wifi::new(r#"a\b,c:"d""#).wep("0123456789").to_string(), r#"WIFI:T:WEP;S:a\\b\,c\:\"d\";P:"0123456789";;"#);
+    assert_eq!(
+        Wifi::new("corp").wpa2_eap("PEAP", "alice", "secret").anonymous_identity("anon").phase2("MSCHAPV2").to_string(),
+        "WIFI:T:WPA2-EAP;S:corp;P:secret;E:PEAP;A:anon;I:alice;PH2:MSCHAPV2;;"
+    );
+    // EAP fields only go with WPA2-EAP
+    assert_eq!(Wifi::new("bistro").phase2("GTC").wpa("coffee").to_string(), "WIFI:T:WPA;S:bistro;P:coffee;;");
+}
+
+#[test]
+fn test_wifi_encode() {
+    use crate::{capacity::remaining_bits, decode, Encoder, Mode, Segment};
+
+    let wifi = Wifi::new("HOME NETWORK 5G").wpa("31415926535897932384").to_string();
+    let mut encoder = Encoder::new().ec_level("M").split(true);
+    let decoded = decode(&encoder.encode(&wifi).unwrap().as_matrix()).unwrap();
+
+    assert_eq!(decoded.data, wifi.as_bytes());
+    assert!(decoded.segments.iter().any(|segment| segment.mode == Mode::Alphanumeric));
+    assert!(decoded.segments.iter().any(|segment| segment.mode == Mode::Numeric));
+
+    // fewer bits than the whole payload in Byte mode
+    let single = Encoder::new().ec_level("M").encode(&wifi).unwrap().as_matrix();
+    assert!(decoded.version <= (single.width() - 17) / 4);
+    let remaining = |segments: &[Segment]| remaining_bits(segments, 4, "M").unwrap();
+    assert!(remaining(&decoded.segments) > remaining(&[Segment::auto(&wifi)]) + 40);
+}
@@ -0,0 +1,7 @@
+// items of the std prelude which are not in the core prelude
+pub(crate) use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
@@ -0,0 +1,155 @@
+use crate::prelude::*;
+
+use crate::encoder::{
+    Matrix,
+    Region,
+    RegionMap,
+};
+
+// pixels per module
+const MODULE_SIZE: usize = 16;
+
+// fill colors of regions, data and ec codewords alternate between two colors to show their boundaries
+const LEGEND: [(&str, &str); 12] = [
+    ("finder", "#d62728"),
+    ("separator", "#f7b6d2"),
+    ("timing", "#2ca02c"),
+    ("alignment", "#ff7f0e"),
+    ("format", "#9467bd"),
+    ("version", "#c5b0d5"),
+    ("dark module", "#8c564b"),
+    ("data codeword (even)", "#1f77b4"),
+    ("data codeword (odd)", "#aec7e8"),
+    ("ec codeword (even)", "#17becf"),
+    ("ec codeword (odd)", "#9edae5"),
+    ("remainder", "#7f7f7f"),
+];
+
+fn color(region: Region) -> &'static str {
+    LEGEND[match region {
+        Region::Finder => 0,
+        Region::Separator => 1,
+        Region::Timing => 2,
+        Region::Alignment => 3,
+        Region::Format => 4,
+        Region::Version => 5,
+        Region::DarkModule => 6,
+        Region::Data { codeword, .. } => 7 + codeword % 2,
+        Region::Ec { codeword, .. } => 9 + codeword % 2,
+        Region::Remainder => 11,
+    }].1
+}
+
+fn describe(region: Region) -> String {
+    match region {
+        Region::Data { codeword, bit } => format!("data codeword {} bit {}", codeword, bit),
+        Region::Ec { codeword, bit } => format!("ec codeword {} bit {}", codeword, bit),
+        Region::DarkModule => "dark module".to_string(),
+        _ => format!("{:?}", region).to_lowercase(),
+    }
+}
+
+// every module is colored by its region and carries a tooltip,
+// dark modules are marked by a black square in the center,
+// the polyline follows the order of data placement
+pub fn svg(matrix: &Matrix, region_map: &RegionMap) -> String {
+    let len = region_map.width();
+    // one module of margin
+    let size = (len + 2) * MODULE_SIZE;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n<rect width=\"{0}\" height=\"{0}\" fill=\"#fff\"/>\n",
+        size
+    );
+
+    for y in 0..len {
+        for x in 0..len {
+            let region = region_map.get(x, y);
+            let (left, top) = ((x + 1) * MODULE_SIZE, (y + 1) * MODULE_SIZE);
+
+            svg.push_str(&format!(
+                "<rect x=\"{left}\" y=\"{top}\" width=\"{size}\" height=\"{size}\" fill=\"{fill}\"><title>({x}, {y}) {title}</title></rect>\n",
+                left = left,
+                top = top,
+                size = MODULE_SIZE,
+                fill = color(region),
+                x = x,
+                y = y,
+                title = describe(region)
+            ));
+            if matrix.is_dark(x, y) {
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"#000\"/>\n",
+                    left + MODULE_SIZE / 4,
+                    top + MODULE_SIZE / 4,
+                    MODULE_SIZE / 2
+                ));
+            }
+        }
+    }
+
+    svg.push_str("<polyline fill=\"none\" stroke=\"#000\" stroke-opacity=\"0.4\" points=\"");
+    svg.push_str(&region_map.placement()
+        .iter()
+        .map(|&(x, y)| format!("{},{}", (x + 1) * MODULE_SIZE + MODULE_SIZE / 2, (y + 1) * MODULE_SIZE + MODULE_SIZE / 2))
+        .collect::<Vec<String>>()
+        .join(" "));
+    svg.push_str("\"/>\n");
+
+    // label the most significant bit of every codeword
+    for &(x, y) in region_map.placement() {
+        let label = match region_map.get(x, y) {
+            Region::Data { codeword, bit: 0 } => format!("D{}", codeword),
+            Region::Ec { codeword, bit: 0 } => format!("E{}", codeword),
+            _ => continue,
+        };
+
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" fill=\"#d62728\">{}</text>\n",
+            (x + 1) * MODULE_SIZE,
+            (y + 1) * MODULE_SIZE + MODULE_SIZE / 2,
+            MODULE_SIZE / 2,
+            label
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+// `svg` with a legend
+pub fn html(matrix: &Matrix, region_map: &RegionMap) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>qrustcode regions</title>\n</head>\n<body>\n");
+
+    html.push_str(&svg(matrix, region_map));
+    html.push_str("<ul style=\"list-style: none; font-family: monospace\">\n");
+    for (name, color) in LEGEND.iter() {
+        html.push_str(&format!(
+            "<li><span style=\"display: inline-block; width: 1em; height: 1em; background: {}\"></span> {}</li>\n",
+            color,
+            name
+        ));
+    }
+    html.push_str("</ul>\n</body>\n</html>\n");
+
+    html
+}
+
+#[test]
+fn test_svg() {
+    use crate::encoder::Encoder;
+
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let region_map = encoder.region_map().unwrap();
+    let svg = svg(&matrix, &region_map);
+
+    assert_eq!(svg.matches("<title>").count(), 21 * 21);
+    assert_eq!(svg.matches("<title>(0, 0) finder</title>").count(), 1);
+    assert_eq!(svg.matches("<title>(20, 20) data codeword 0 bit 0</title>").count(), 1);
+    assert_eq!(svg.matches("<title>(8, 13) dark module</title>").count(), 1);
+    // 16 data codewords + 10 ec codewords
+    assert_eq!(svg.matches("<text").count(), 26);
+
+    assert!(html(&matrix, &region_map).contains("remainder</li>"));
+}
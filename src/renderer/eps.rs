@@ -0,0 +1,53 @@
+use crate::prelude::*;
+
+use super::Renderer;
+
+impl<'a> Renderer<'a> {
+    // Encapsulated PostScript, one point per pixel, the origin is the bottom left corner
+    pub fn eps(&self) -> String {
+        let modules = self.modules();
+        let mut eps = format!(
+            "%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 {0} {0}\n%%Creator: qrustcode\n%%EndComments\ngsave\n{1} {1} scale\n{3} setgray\n0 0 {2} {2} rectfill\n{4} setgray\n",
+            self.pixels(),
+            self.scale,
+            modules,
+            // background, then the dark modules
+            if self.invert { 0 } else { 1 },
+            if self.invert { 1 } else { 0 }
+        );
+
+        // merge horizontal runs of dark modules into one rectangle
+        for y in 0..modules {
+            let mut x = 0;
+            while x < modules {
+                if !self.is_dark(x, y) {
+                    x += 1;
+
+                    continue;
+                }
+
+                let start = x;
+                while x < modules && self.is_dark(x, y) { x += 1; }
+                eps.push_str(&format!("{} {} {} 1 rectfill\n", start, modules - 1 - y, x - start));
+            }
+        }
+        eps.push_str("grestore\n%%EOF\n");
+
+        eps
+    }
+}
+
+#[test]
+fn test_eps() {
+    use crate::encoder::Encoder;
+
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let eps = Renderer::new(&matrix).quiet_zone(0).scale(3).eps();
+
+    assert!(eps.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 63 63\n"));
+    // the top row of the top left and top right finder patterns
+    assert!(eps.contains("\n0 20 7 1 rectfill\n"));
+    assert!(eps.contains("\n14 20 7 1 rectfill\n"));
+    assert!(eps.ends_with("%%EOF\n"));
+}
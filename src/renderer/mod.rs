@@ -0,0 +1,166 @@
+use crate::prelude::*;
+
+use crate::{
+    decoder::Image,
+    encoder::{halftone::dither, Error, Logo, Matrix},
+};
+
+pub use self::style::{EyeShape, Fill, ModuleShape, Style};
+
+pub mod debug;
+mod eps;
+mod pbm;
+pub(crate) mod png;
+mod ppm;
+mod style;
+mod svg;
+mod term;
+
+// draw a `Matrix` as an image, every module is `scale` x `scale` pixels
+// and the symbol is surrounded by `quiet_zone` light modules
+#[derive(Debug, Clone)]
+pub struct Renderer<'a> {
+    matrix: &'a Matrix,
+    scale: usize,
+    quiet_zone: usize,
+    // Swiss QR-bill cross in the center, drawn by `svg`
+    swiss_cross: bool,
+    // modules under a clearing logo are drawn light, `svg` draws its image
+    logo: Option<Logo>,
+    // shapes and fills of `svg`
+    style: Option<Style>,
+    // dots of the image at 3 x 3 per module, drawn around the center of data modules by `png` and `pbm`
+    halftone: Option<Vec<Vec<bool>>>,
+    // red, green and blue symbols drawn by `png` and `ppm`, `matrix` is the red one
+    layers: Option<&'a [Matrix; 3]>,
+    // light modules on a dark quiet zone
+    invert: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(matrix: &'a Matrix) -> Renderer<'a> {
+        Renderer {
+            matrix,
+            scale: 4,
+            // ISO/IEC 18004 asks for 4 modules
+            quiet_zone: 4,
+            swiss_cross: false,
+            logo: None,
+            style: None,
+            halftone: None,
+            layers: None,
+            invert: false,
+        }
+    }
+
+    // symbols of `Encoder::encode_layers` in the red, green and blue channels, a channel is off where its symbol is
+    // dark, so the modules which are dark in every symbol, finder patterns included, are black; the other formats
+    // draw the red symbol
+    pub fn layers(layers: &'a [Matrix; 3]) -> Result<Renderer<'a>, Error> {
+        let width = layers[0].width();
+        if let Some(layer) = layers.iter().find(|layer| layer.width() != width) {
+            return Err(Error::LayersMismatch(width, layer.width()));
+        }
+
+        Ok(Renderer {
+            layers: Some(layers),
+            ..Renderer::new(&layers[0])
+        })
+    }
+
+    pub fn scale(mut self, scale: usize) -> Renderer<'a> {
+        self.scale = scale.max(1);
+
+        self
+    }
+
+    pub fn quiet_zone(mut self, quiet_zone: usize) -> Renderer<'a> {
+        self.quiet_zone = quiet_zone;
+
+        self
+    }
+
+    pub fn swiss_cross(mut self, swiss_cross: bool) -> Renderer<'a> {
+        self.swiss_cross = swiss_cross;
+
+        self
+    }
+
+    // the same logo as `Encoder::logo`, which made sure that every block can correct the modules it covers
+    pub fn logo(mut self, logo: &Logo) -> Renderer<'a> {
+        self.logo = Some(logo.clone());
+
+        self
+    }
+
+    pub fn style(mut self, style: &Style) -> Renderer<'a> {
+        self.style = Some(style.clone());
+
+        self
+    }
+
+    // reflectance reversed, as etched or printed light on dark; `Matrix::transposed` makes a mirror image
+    pub fn invert(mut self, invert: bool) -> Renderer<'a> {
+        self.invert = invert;
+
+        self
+    }
+
+    // the image shows through every data module but its center, which carries the bit; a scale that is a multiple of
+    // 3 draws even dots, 6 or more leaves scanners room to hit the centers; an empty image draws no halftone
+    pub fn halftone(mut self, image: &Image) -> Renderer<'a> {
+        if image.width() > 0 && image.height() > 0 { self.halftone = Some(dither(image, 3 * self.matrix.width())); }
+
+        self
+    }
+
+    // modules per side, quiet zone included
+    fn modules(&self) -> usize { self.matrix.width() + 2 * self.quiet_zone }
+
+    // pixels per side
+    fn pixels(&self) -> usize { self.modules() * self.scale }
+
+    // module(x, y) counted from the outer edge of the quiet zone, before `invert`
+    fn is_dark(&self, x: usize, y: usize) -> bool {
+        let (x, y) = (x.wrapping_sub(self.quiet_zone), y.wrapping_sub(self.quiet_zone));
+
+        x < self.matrix.width() && y < self.matrix.width() && self.matrix.is_dark(x, y) && !self.is_cleared(x, y)
+    }
+
+    // pixel(x, y) counted from the outer edge of the quiet zone
+    fn is_dark_pixel(&self, x: usize, y: usize) -> bool {
+        if let Some(dots) = &self.halftone {
+            // the dot of the 3 x 3 grid in the symbol
+            let dot = |pixel: usize| (pixel * 3 / self.scale).wrapping_sub(3 * self.quiet_zone);
+            let (dx, dy) = (dot(x), dot(y));
+            let len = 3 * self.matrix.width();
+
+            if dx < len && dy < len && (dx % 3, dy % 3) != (1, 1) && !self.matrix.is_function(dx / 3, dy / 3) {
+                return dots[dy][dx] != self.invert;
+            }
+        }
+
+        self.is_dark(x / self.scale, y / self.scale) != self.invert
+    }
+
+    // red, green and blue of pixel(x, y), black or white without layers
+    fn color(&self, x: usize, y: usize) -> [u8; 3] {
+        let layers = match self.layers {
+            Some(layers) => layers,
+            None => return if self.is_dark_pixel(x, y) { [0; 3] } else { [255; 3] },
+        };
+        let (x, y) = ((x / self.scale).wrapping_sub(self.quiet_zone), (y / self.scale).wrapping_sub(self.quiet_zone));
+
+        let mut color = [255; 3];
+        for (channel, layer) in color.iter_mut().zip(layers.iter()) {
+            if (x < layer.width() && y < layer.width() && layer.is_dark(x, y)) != self.invert { *channel = 0; }
+        }
+
+        color
+    }
+
+    // a data module under a clearing logo, function patterns stay
+    fn is_cleared(&self, x: usize, y: usize) -> bool {
+        self.logo.as_ref().is_some_and(|logo| logo.clear && !self.matrix.is_function(x, y) && logo.overlaps(self.matrix.width(), x, y))
+    }
+}
@@ -0,0 +1,60 @@
+use crate::prelude::*;
+
+use super::Renderer;
+
+impl<'a> Renderer<'a> {
+    // binary portable bitmap(P4), rows are packed from the most significant bit and 1 is black
+    pub fn pbm(&self) -> Vec<u8> {
+        let pixels = self.pixels();
+        let mut pbm = format!("P4\n{0} {0}\n", pixels).into_bytes();
+
+        for y in 0..pixels {
+            let row = pbm.len();
+            pbm.resize(row + pixels.div_ceil(8), 0);
+
+            for x in 0..pixels {
+                if self.is_dark_pixel(x, y) { pbm[row + x / 8] |= 0x80 >> (x % 8); }
+            }
+        }
+
+        pbm
+    }
+}
+
+#[test]
+fn test_pbm() {
+    use crate::encoder::Encoder;
+
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let pbm = Renderer::new(&matrix).quiet_zone(1).scale(1).pbm();
+
+    // 23 pixels -> 3 bytes per row
+    assert!(pbm.starts_with(b"P4\n23 23\n"));
+    assert_eq!(pbm.len(), 9 + 23 * 3);
+    // quiet zone, then the top rows of the finder patterns at 1 ~ 7 and 15 ~ 21
+    assert_eq!(pbm[9 + 3], 0b0111_1111);
+    assert_eq!(pbm[9 + 4] & 1, 1);
+    assert_eq!(pbm[9 + 5], 0b1111_1100);
+}
+
+#[test]
+fn test_invert() {
+    use crate::{decoder::decode_image, encoder::Encoder};
+
+    let matrix = Encoder::new().ec_level("M").encode("HELLO WORLD").unwrap().as_matrix();
+
+    // the quiet zone is dark, the finder pattern starts light
+    let pbm = Renderer::new(&matrix).quiet_zone(1).scale(1).invert(true).pbm();
+    assert_eq!(pbm[9..12], [0xff, 0xff, 0b1111_1110]);
+    assert_eq!((pbm[12], pbm[14]), (0b1000_0000, 0b0000_0010));
+    let decoded = decode_image(&pbm).unwrap();
+    assert_eq!((&decoded.data[..], decoded.inverted, decoded.mirrored), (&b"HELLO WORLD"[..], true, false));
+
+    for symbol in [matrix.transposed(), matrix.mirrored()].iter() {
+        for &invert in [false, true].iter() {
+            let decoded = decode_image(&Renderer::new(symbol).invert(invert).png()).unwrap();
+            assert_eq!((&decoded.data[..], decoded.inverted, decoded.mirrored), (&b"HELLO WORLD"[..], invert, true));
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::prelude::*;
+
+use super::Renderer;
+
+// CRC-32 of PNG chunks, ISO 3309
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+            j += 1;
+        }
+        table[i] = crc;
+
+        i += 1;
+    }
+
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+pub(crate) fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+
+        (a, (b + a) % 65521)
+    });
+
+    b << 16 | a
+}
+
+// zlib stream of stored deflate blocks, the bitmap of a symbol is small enough to skip compression
+pub(crate) fn zlib_stored(bytes: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+
+    let mut blocks = bytes.chunks(0xffff).peekable();
+    if blocks.peek().is_none() { zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]); }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(bytes).to_be_bytes());
+
+    zlib
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+impl<'a> Renderer<'a> {
+    // 1 bit grayscale PNG, 0 is black, or 8 bit RGB with layers
+    pub fn png(&self) -> Vec<u8> {
+        let pixels = self.pixels();
+        let (stride, depth, color_type) = match self.layers {
+            Some(_) => (3 * pixels, 8, 2),
+            None => (pixels.div_ceil(8), 1, 0),
+        };
+
+        // every row starts with filter type 0
+        let mut raw = vec![0; (stride + 1) * pixels];
+        for (y, row) in raw.chunks_mut(stride + 1).enumerate() {
+            for x in 0..pixels {
+                match self.layers {
+                    Some(_) => row[1 + 3 * x..4 + 3 * x].copy_from_slice(&self.color(x, y)),
+                    None => if !self.is_dark_pixel(x, y) { row[1 + x / 8] |= 0x80 >> (x % 8); },
+                }
+            }
+        }
+
+        let mut header = [0; 13];
+        header[..4].copy_from_slice(&(pixels as u32).to_be_bytes());
+        header[4..8].copy_from_slice(&(pixels as u32).to_be_bytes());
+        // deflate, adaptive filtering, no interlace
+        header[8..].copy_from_slice(&[depth, color_type, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        push_chunk(&mut png, b"IHDR", &header);
+        push_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        push_chunk(&mut png, b"IEND", &[]);
+
+        png
+    }
+}
+
+#[test]
+fn test_checksums() {
+    assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+}
+
+#[test]
+fn test_png() {
+    use crate::encoder::Encoder;
+
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let png = Renderer::new(&matrix).scale(2).png();
+
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00\x00\x00\x3a\x00\x00\x00\x3a\x01\x00"));
+    assert!(png.ends_with(b"\x00\x00\x00\x00IEND\xae\x42\x60\x82"));
+}
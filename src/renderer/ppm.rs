@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+use super::Renderer;
+
+impl<'a> Renderer<'a> {
+    // binary portable pixmap(P6), 8 bit red, green and blue of every pixel
+    pub fn ppm(&self) -> Vec<u8> {
+        let pixels = self.pixels();
+        let mut ppm = format!("P6\n{0} {0}\n255\n", pixels).into_bytes();
+
+        for y in 0..pixels {
+            for x in 0..pixels { ppm.extend_from_slice(&self.color(x, y)); }
+        }
+
+        ppm
+    }
+}
+
+#[test]
+fn test_ppm() {
+    use crate::{decoder::Image, encoder::Encoder};
+
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let renderer = Renderer::new(&matrix).quiet_zone(1).scale(1);
+    let ppm = renderer.ppm();
+
+    assert!(ppm.starts_with(b"P6\n23 23\n255\n"));
+    assert_eq!(ppm.len(), 13 + 23 * 23 * 3);
+    // the quiet zone, then the top left finder pattern
+    assert_eq!(ppm[13 + 23 * 3..13 + 23 * 3 + 6], [255, 255, 255, 0, 0, 0]);
+    assert_eq!(Image::parse(&ppm).unwrap(), Image::parse(&renderer.pbm()).unwrap());
+}
@@ -0,0 +1,323 @@
+use crate::prelude::*;
+
+use super::{svg::escape_attribute, Renderer};
+
+// shape of every data module, function modules other than the finder patterns are always full squares
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModuleShape {
+    Square,
+    // diameter of one module
+    Circle,
+    Rounded,
+    // rounded corners only where no dark neighbor joins, runs of modules flow into each other
+    Liquid,
+}
+
+// shape of the 7 x 7 ring or the 3 x 3 center of a finder pattern, the ring stays one module thick and the center
+// three modules wide whatever the shape is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EyeShape {
+    Square,
+    Rounded,
+    Circle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    // any SVG color
+    Solid(String),
+    // from `start` to `end` across the symbol, `angle` in degrees clockwise, 0 goes from left to right
+    Linear { start: String, end: String, angle: f64 },
+    // from `center` in the center of the symbol to `edge` in its corners
+    Radial { center: String, edge: String },
+}
+
+// how `Renderer::svg` draws dark modules, the defaults draw the same symbol as without a style
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    module: ModuleShape,
+    eye_outer: EyeShape,
+    eye_inner: EyeShape,
+    fill: Fill,
+    // finder patterns in `fill` if `None`
+    eye_fill: Option<Fill>,
+    background: String,
+}
+
+impl Style {
+    pub fn new() -> Style {
+        Style {
+            module: ModuleShape::Square,
+            eye_outer: EyeShape::Square,
+            eye_inner: EyeShape::Square,
+            fill: Fill::Solid("#000".to_string()),
+            eye_fill: None,
+            background: "#fff".to_string(),
+        }
+    }
+
+    pub fn module(mut self, module: ModuleShape) -> Style {
+        self.module = module;
+
+        self
+    }
+
+    pub fn eye_outer(mut self, eye_outer: EyeShape) -> Style {
+        self.eye_outer = eye_outer;
+
+        self
+    }
+
+    pub fn eye_inner(mut self, eye_inner: EyeShape) -> Style {
+        self.eye_inner = eye_inner;
+
+        self
+    }
+
+    pub fn fill(mut self, fill: Fill) -> Style {
+        self.fill = fill;
+
+        self
+    }
+
+    pub fn eye_fill(mut self, eye_fill: Fill) -> Style {
+        self.eye_fill = Some(eye_fill);
+
+        self
+    }
+
+    pub fn background(mut self, background: &str) -> Style {
+        self.background = background.to_string();
+
+        self
+    }
+}
+
+impl Fill {
+    // the color of a solid fill, where a gradient starts
+    fn color(&self) -> &str {
+        match self {
+            Fill::Solid(color) | Fill::Linear { start: color, .. } | Fill::Radial { center: color, .. } => color,
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style { Style::new() }
+}
+
+// path of a rectangle whose corners (top left, top right, bottom right, bottom left) are rounded by their radii
+fn rounded_rect(x: f64, y: f64, width: f64, height: f64, [tl, tr, br, bl]: [f64; 4]) -> String {
+    if [tl, tr, br, bl] == [0.; 4] { return format!("M{},{}h{}v{}h{}z", x, y, width, height, -width); }
+
+    let mut path = format!("M{},{}h{}", x + tl, y, width - tl - tr);
+    if tr > 0. { path.push_str(&format!("a{0},{0} 0 0 1 {0},{0}", tr)); }
+    path.push_str(&format!("v{}", height - tr - br));
+    if br > 0. { path.push_str(&format!("a{0},{0} 0 0 1 {1},{0}", br, -br)); }
+    path.push_str(&format!("h{}", br + bl - width));
+    if bl > 0. { path.push_str(&format!("a{0},{0} 0 0 1 {1},{1}", bl, -bl)); }
+    path.push_str(&format!("v{}", bl + tl - height));
+    if tl > 0. { path.push_str(&format!("a{0},{0} 0 0 1 {0},{1}", tl, -tl)); }
+    path.push('z');
+
+    path
+}
+
+// corner radius of a square `side` modules wide
+fn eye_radius(shape: EyeShape, side: f64) -> f64 {
+    match shape {
+        EyeShape::Square => 0.,
+        EyeShape::Rounded => side / 3.5,
+        EyeShape::Circle => side / 2.,
+    }
+}
+
+impl<'a> Renderer<'a> {
+    // top left corner of the finder pattern which module(x, y) of the matrix belongs to
+    fn finder(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let last = self.matrix.width() - 7;
+
+        [(0, 0), (last, 0), (0, last)]
+            .iter()
+            .find(|&&(fx, fy)| (fx..fx + 7).contains(&x) && (fy..fy + 7).contains(&y))
+            .cloned()
+    }
+
+    fn module_path(&self, style: &Style, x: usize, y: usize) -> String {
+        let (mx, my) = (x - self.quiet_zone, y - self.quiet_zone);
+        let radii = match style.module {
+            // function patterns keep their full modules
+            _ if self.matrix.is_function(mx, my) => [0.; 4],
+            ModuleShape::Square => [0.; 4],
+            ModuleShape::Circle => [0.5; 4],
+            ModuleShape::Rounded => [0.25; 4],
+            ModuleShape::Liquid => {
+                // a corner is rounded if neither module next to it is dark
+                let dark = |dx: isize, dy: isize| self.is_dark((x as isize + dx) as usize, (y as isize + dy) as usize);
+                let (up, right, down, left) = (dark(0, -1), dark(1, 0), dark(0, 1), dark(-1, 0));
+                let radius = |a: bool, b: bool| if a || b { 0. } else { 0.5 };
+
+                [radius(up, left), radius(up, right), radius(down, right), radius(down, left)]
+            }
+        };
+
+        rounded_rect(x as f64, y as f64, 1., 1., radii)
+    }
+
+    // ring and center of the finder pattern with its top left corner at module(x, y) of the matrix
+    fn eye_path(&self, style: &Style, x: usize, y: usize) -> String {
+        let (x, y) = ((x + self.quiet_zone) as f64, (y + self.quiet_zone) as f64);
+        let outer = eye_radius(style.eye_outer, 7.);
+        let inner = eye_radius(style.eye_inner, 3.);
+
+        [
+            rounded_rect(x, y, 7., 7., [outer; 4]),
+            // the hole follows the ring so that it stays one module thick
+            rounded_rect(x + 1., y + 1., 5., 5., [(outer - 1.).max(0.); 4]),
+            rounded_rect(x + 2., y + 2., 3., 3., [inner; 4]),
+        ].concat()
+    }
+
+    // a gradient in the coordinates of the symbol, so that modules and eyes share it
+    fn paint(&self, fill: &Fill, id: &str) -> (String, String) {
+        let width = self.matrix.width() as f64;
+        let (start, center, end) = (self.quiet_zone as f64, self.quiet_zone as f64 + width / 2., self.quiet_zone as f64 + width);
+
+        match fill {
+            Fill::Solid(color) => (String::new(), escape_attribute(color)),
+            Fill::Linear { start: from, end: to, angle } => (
+                format!(
+                    "<linearGradient id=\"{0}\" gradientUnits=\"userSpaceOnUse\" x1=\"{1}\" y1=\"{2}\" x2=\"{3}\" y2=\"{2}\" gradientTransform=\"rotate({4} {2} {2})\"><stop offset=\"0\" stop-color=\"{5}\"/><stop offset=\"1\" stop-color=\"{6}\"/></linearGradient>\n",
+                    id, start, center, end, angle, escape_attribute(from), escape_attribute(to)
+                ),
+                format!("url(#{})", id),
+            ),
+            Fill::Radial { center: from, edge: to } => (
+                format!(
+                    "<radialGradient id=\"{0}\" gradientUnits=\"userSpaceOnUse\" cx=\"{1}\" cy=\"{1}\" r=\"{2:.3}\"><stop offset=\"0\" stop-color=\"{3}\"/><stop offset=\"1\" stop-color=\"{4}\"/></radialGradient>\n",
+                    id, center, width * core::f64::consts::FRAC_1_SQRT_2, escape_attribute(from), escape_attribute(to)
+                ),
+                format!("url(#{})", id),
+            ),
+        }
+    }
+
+    // finder patterns as eyes, other function modules as squares, data modules in the module shape
+    pub(super) fn styled_svg(&self, style: &Style) -> String {
+        let modules = self.modules();
+        // inverted, the dark modules take the background color and the background the fill's, the first color of a
+        // gradient; a set eye fill is kept
+        let (fill_defs, fill, background) = if self.invert {
+            (String::new(), escape_attribute(&style.background), escape_attribute(style.fill.color()))
+        } else {
+            let (fill_defs, fill) = self.paint(&style.fill, "fill");
+
+            (fill_defs, fill, escape_attribute(&style.background))
+        };
+        let (eye_defs, eye_fill) = match &style.eye_fill {
+            Some(eye_fill) => self.paint(eye_fill, "eye-fill"),
+            None => (String::new(), fill.clone()),
+        };
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {1} {1}\">\n",
+            self.pixels(),
+            modules
+        );
+        if !fill_defs.is_empty() || !eye_defs.is_empty() { svg.push_str(&format!("<defs>\n{}{}</defs>\n", fill_defs, eye_defs)); }
+        svg.push_str(&format!("<rect width=\"{0}\" height=\"{0}\" fill=\"{1}\"/>\n", modules, background));
+
+        svg.push_str(&format!("<path fill=\"{}\" d=\"", fill));
+        for y in 0..modules {
+            for x in 0..modules {
+                let inside = (self.quiet_zone..self.quiet_zone + self.matrix.width()).contains(&x)
+                    && (self.quiet_zone..self.quiet_zone + self.matrix.width()).contains(&y);
+
+                if self.is_dark(x, y) && !(inside && self.finder(x - self.quiet_zone, y - self.quiet_zone).is_some()) {
+                    svg.push_str(&self.module_path(style, x, y));
+                }
+            }
+        }
+        svg.push_str("\"/>\n");
+
+        // the holes of the rings are cut by the even-odd rule
+        let last = self.matrix.width() - 7;
+        svg.push_str(&format!("<path fill=\"{}\" fill-rule=\"evenodd\" d=\"", eye_fill));
+        for &(x, y) in [(0, 0), (last, 0), (0, last)].iter() { svg.push_str(&self.eye_path(style, x, y)); }
+        svg.push_str("\"/>\n");
+
+        svg
+    }
+}
+
+#[test]
+fn test_rounded_rect() {
+    assert_eq!(rounded_rect(4., 5., 1., 1., [0.; 4]), "M4,5h1v1h-1z");
+    assert_eq!(
+        rounded_rect(4., 5., 1., 1., [0.5; 4]),
+        "M4.5,5h0a0.5,0.5 0 0 1 0.5,0.5v0a0.5,0.5 0 0 1 -0.5,0.5h0a0.5,0.5 0 0 1 -0.5,-0.5v0a0.5,0.5 0 0 1 0.5,-0.5z"
+    );
+    assert_eq!(rounded_rect(0., 0., 2., 1., [0., 0.5, 0., 0.]), "M0,0h1.5a0.5,0.5 0 0 1 0.5,0.5v0.5h-2v-1z");
+}
+
+#[test]
+fn test_styled_svg() {
+    use crate::encoder::Encoder;
+
+    let matrix = Encoder::new().version(1).ec_level("M").encode("01234567").unwrap().as_matrix();
+
+    // the default style draws the same modules
+    let svg = Renderer::new(&matrix).style(&Style::new()).svg();
+    let dark_modules = (0..21).flat_map(|y| (0..21).map(move |x| (x, y))).filter(|&(x, y)| matrix.is_dark(x, y)).count();
+    assert_eq!(svg.matches('z').count(), dark_modules - 3 * 33 + 3 * 3);
+    assert!(svg.contains("d=\"M4,4h7v7h-7zM5,5h5v5h-5zM6,6h3v3h-3z"));
+    // inverted, the fill and the background swap
+    let svg = Renderer::new(&matrix).style(&Style::new()).invert(true).svg();
+    assert!(svg.contains("<rect width=\"29\" height=\"29\" fill=\"#000\"/>\n<path fill=\"#fff\" d=\""));
+    assert!(svg.contains("<path fill=\"#fff\" fill-rule=\"evenodd\""));
+    // only the colors swap, a gradient leaves its first color to the background and the eye fill is kept
+    let style = Style::new()
+        .fill(Fill::Linear { start: "#036".to_string(), end: "#90c".to_string(), angle: 45. })
+        .eye_fill(Fill::Solid("#c00".to_string()));
+    let svg = Renderer::new(&matrix).style(&style).invert(true).svg();
+    assert!(!svg.contains("linearGradient"));
+    assert!(svg.contains("<rect width=\"29\" height=\"29\" fill=\"#036\"/>\n<path fill=\"#fff\" d=\""));
+    assert!(svg.contains("<path fill=\"#c00\" fill-rule=\"evenodd\""));
+
+    let style = Style::new()
+        .module(ModuleShape::Circle)
+        .eye_outer(EyeShape::Circle)
+        .eye_inner(EyeShape::Rounded)
+        .fill(Fill::Linear { start: "#036".to_string(), end: "#90c".to_string(), angle: 45. })
+        .eye_fill(Fill::Radial { center: "#000".to_string(), edge: "#c00".to_string() });
+    let svg = Renderer::new(&matrix).style(&style).svg();
+    assert!(svg.contains("<linearGradient id=\"fill\" gradientUnits=\"userSpaceOnUse\" x1=\"4\" y1=\"14.5\" x2=\"25\" y2=\"14.5\" gradientTransform=\"rotate(45 14.5 14.5)\">"));
+    assert!(svg.contains("<radialGradient id=\"eye-fill\" gradientUnits=\"userSpaceOnUse\" cx=\"14.5\" cy=\"14.5\" r=\"14.849\">"));
+    assert!(svg.contains("<path fill=\"url(#eye-fill)\" fill-rule=\"evenodd\" d=\"M7.5,4h0a3.5,3.5 0 0 1 3.5,3.5"));
+    // the timing pattern stays square, data modules are circles
+    assert!(svg.contains("M12,10h1v1h-1z"));
+    assert!(!svg.contains("M24,24h1v1h-1z"));
+}
+
+#[test]
+fn test_liquid() {
+    use crate::encoder::Encoder;
+
+    let matrix = Encoder::new().version(1).encode("HELLO").unwrap().as_matrix();
+    let renderer = Renderer::new(&matrix);
+    let style = Style::new().module(ModuleShape::Liquid);
+
+    for y in 4..25 {
+        for x in 4..25 {
+            if !renderer.is_dark(x, y) || matrix.is_function(x - 4, y - 4) { continue; }
+
+            // a lone module is a circle, a module with dark neighbors on all sides is a square
+            let path = renderer.module_path(&style, x, y);
+            let neighbors = [(0, -1), (1, 0), (0, 1), (-1, 0)].iter()
+                .filter(|&&(dx, dy)| renderer.is_dark((x as isize + dx) as usize, (y as isize + dy) as usize))
+                .count();
+            assert_eq!(path.matches('a').count() == 4, neighbors == 0);
+            if neighbors >= 3 { assert_eq!(path.matches('a').count(), 0); }
+        }
+    }
+}
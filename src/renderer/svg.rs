@@ -0,0 +1,153 @@
+use crate::prelude::*;
+
+use crate::encoder::{Logo, LogoShape};
+
+use super::Renderer;
+
+// `value` in double quotes
+pub(super) fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
+
+impl<'a> Renderer<'a> {
+    // one path of unit squares in module coordinates, scaled by the `viewBox`
+    pub fn svg(&self) -> String {
+        let mut svg = match &self.style {
+            Some(style) => self.styled_svg(style),
+            None => self.square_svg(),
+        };
+        if self.swiss_cross { svg.push_str(&self.swiss_cross_svg()); }
+        if let Some(logo) = &self.logo { svg.push_str(&self.logo_svg(logo)); }
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+
+    fn square_svg(&self) -> String {
+        let modules = self.modules();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {1} {1}\" shape-rendering=\"crispEdges\">\n<rect width=\"{1}\" height=\"{1}\" fill=\"{2}\"/>\n<path fill=\"{3}\" d=\"",
+            self.pixels(),
+            modules,
+            // the dark modules are drawn in the light color on a dark background when inverted
+            if self.invert { "#000" } else { "#fff" },
+            if self.invert { "#fff" } else { "#000" }
+        );
+
+        for y in 0..modules {
+            for x in 0..modules {
+                if self.is_dark(x, y) { svg.push_str(&format!("M{},{}h1v1h-1z", x, y)); }
+            }
+        }
+        svg.push_str("\"/>\n");
+
+        svg
+    }
+
+    // 7 mm in the center of a 46 mm symbol: a black square in a white border with a white cross, the cross is 20 / 32
+    // of the black square and its arms are 6 / 32 thick as the flag
+    fn swiss_cross_svg(&self) -> String {
+        let width = self.matrix.width() as f64;
+        let center = self.quiet_zone as f64 + width / 2.;
+        let side = width * 7. / 46.;
+        let black = side * 17. / 19.8;
+        let (length, thickness) = (black * 20. / 32., black * 6. / 32.);
+
+        let rect = |width: f64, height: f64, fill: &str| format!(
+            "<rect x=\"{:.3}\" y=\"{:.3}\" width=\"{:.3}\" height=\"{:.3}\" fill=\"{}\"/>\n",
+            center - width / 2.,
+            center - height / 2.,
+            width,
+            height,
+            fill
+        );
+
+        [rect(side, side, "#fff"), rect(black, black, "#000"), rect(length, thickness, "#fff"), rect(thickness, length, "#fff")].concat()
+    }
+
+    // the image in the logo's bounding box, clipped to a circle
+    fn logo_svg(&self, logo: &Logo) -> String {
+        let href = match &logo.href {
+            Some(href) => escape_attribute(href),
+            None => return String::new(),
+        };
+        let width = self.matrix.width() as f64;
+        let center = self.quiet_zone as f64 + width / 2.;
+        let (w, h) = (logo.width * width, logo.height * width);
+
+        let (clip, clip_path) = match logo.shape {
+            LogoShape::Rectangle => (String::new(), ""),
+            LogoShape::Circle => (
+                format!("<clipPath id=\"logo\"><circle cx=\"{0:.3}\" cy=\"{0:.3}\" r=\"{1:.3}\"/></clipPath>\n", center, w / 2.),
+                " clip-path=\"url(#logo)\"",
+            ),
+        };
+
+        format!(
+            "{}<image href=\"{}\" x=\"{:.3}\" y=\"{:.3}\" width=\"{:.3}\" height=\"{:.3}\"{}/>\n",
+            clip,
+            href,
+            center - w / 2.,
+            center - h / 2.,
+            w,
+            h,
+            clip_path
+        )
+    }
+}
+
+#[test]
+fn test_svg() {
+    use crate::encoder::Encoder;
+
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let svg = Renderer::new(&matrix).scale(2).svg();
+    let dark_modules = (0..21).flat_map(|y| (0..21).map(move |x| (x, y))).filter(|&(x, y)| matrix.is_dark(x, y)).count();
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"58\" height=\"58\" viewBox=\"0 0 29 29\""));
+    assert_eq!(svg.matches('z').count(), dark_modules);
+    // the top left corner of the finder pattern
+    assert!(svg.contains("d=\"M4,4h1v1h-1z"));
+    assert!(!svg.contains("<rect x="));
+
+    let svg = Renderer::new(&matrix).invert(true).svg();
+    assert!(svg.contains("<rect width=\"29\" height=\"29\" fill=\"#000\"/>\n<path fill=\"#fff\" d=\"M4,4h1v1h-1z"));
+}
+
+#[test]
+fn test_swiss_cross() {
+    use crate::encoder::Encoder;
+
+    // version 10, 57 modules -> 57 * 7 / 46 = 8.674 modules, centered at 4 + 28.5
+    let matrix = Encoder::new().version(10).ec_level("M").encode("SPC").unwrap().as_matrix();
+    let svg = Renderer::new(&matrix).swiss_cross(true).svg();
+
+    assert!(svg.ends_with(
+        "<rect x=\"28.163\" y=\"28.163\" width=\"8.674\" height=\"8.674\" fill=\"#fff\"/>\n\
+        <rect x=\"28.776\" y=\"28.776\" width=\"7.447\" height=\"7.447\" fill=\"#000\"/>\n\
+        <rect x=\"30.173\" y=\"31.802\" width=\"4.655\" height=\"1.396\" fill=\"#fff\"/>\n\
+        <rect x=\"31.802\" y=\"30.173\" width=\"1.396\" height=\"4.655\" fill=\"#fff\"/>\n\
+        </svg>\n"
+    ));
+}
+
+#[test]
+fn test_logo_svg() {
+    use crate::encoder::Encoder;
+
+    let matrix = Encoder::new().version(1).ec_level("H").encode("1").unwrap().as_matrix();
+
+    // function patterns are never cleared
+    let logo = Logo::rectangle(1., 1.).href("logo.svg?a=1&b=2");
+    let svg = Renderer::new(&matrix).logo(&logo).svg();
+    assert!(svg.contains("d=\"M4,4h1v1h-1z"));
+    assert!(svg.contains("<image href=\"logo.svg?a=1&amp;b=2\" x=\"4.000\" y=\"4.000\" width=\"21.000\" height=\"21.000\"/>"));
+
+    // an overlay keeps every module
+    let logo = Logo::circle(0.2).clear(false).href("logo.png");
+    let svg = Renderer::new(&matrix).logo(&logo).svg();
+    assert_eq!(svg.matches('z').count(), Renderer::new(&matrix).svg().matches('z').count());
+    assert!(svg.contains("<circle cx=\"14.500\" cy=\"14.500\" r=\"2.100\"/>"));
+    assert!(svg.contains("clip-path=\"url(#logo)\"/>"));
+}
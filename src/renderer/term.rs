@@ -0,0 +1,48 @@
+use crate::prelude::*;
+
+use super::Renderer;
+
+impl<'a> Renderer<'a> {
+    // two modules per character with half blocks, `scale` is ignored
+    //
+    // light modules are drawn, so the symbol reads right on a dark terminal
+    pub fn term(&self) -> String {
+        let modules = self.modules();
+        let is_light = |x: usize, y: usize| self.is_dark(x, y) == self.invert;
+        let mut term = String::new();
+
+        for y in (0..modules).step_by(2) {
+            for x in 0..modules {
+                // the row under the last one is dark, it is not printed
+                let bottom = y + 1 < modules && is_light(x, y + 1);
+
+                term.push(match (is_light(x, y), bottom) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            term.push('\n');
+        }
+
+        term
+    }
+}
+
+#[test]
+fn test_term() {
+    use crate::encoder::Encoder;
+
+    let mut encoder = Encoder::new().version(1).ec_level("M");
+    let matrix = encoder.encode("01234567").unwrap().as_matrix();
+    let term = Renderer::new(&matrix).quiet_zone(2).term();
+    let lines = term.lines().collect::<Vec<&str>>();
+
+    // 25 rows -> 13 lines
+    assert_eq!(lines.len(), 13);
+    assert!(lines.iter().all(|line| line.chars().count() == 25));
+    assert_eq!(lines[0], "█".repeat(25));
+    // the top row of the finder pattern under the quiet zone
+    assert!(lines[1].starts_with("██ ▄▄▄▄▄ █"));
+}
@@ -0,0 +1,60 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use qrustcode::{Encoder, Workspace};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) { System.dealloc(ptr, layout) }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_encode_into_without_allocation() {
+    let long = "hello, world! ".repeat(50);
+    let messages = ["01234567", "HELLO WORLD", "ハロー・ワールド", "https://github.com/AurevoirXavier/qrustcode", &long];
+
+    let mut encoders = [
+        Encoder::new().ec_level("L"),
+        Encoder::new().ec_level("M"),
+        Encoder::new().ec_level("Q").boost_ec(true),
+        Encoder::new().ec_level("H").min_version(5),
+    ];
+    let mut workspace = Workspace::new();
+
+    // warm up the lazily built tables
+    for encoder in encoders.iter_mut() {
+        for message in messages.iter() { encoder.encode_into(message, &mut workspace).unwrap(); }
+    }
+
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    for _ in 0..3 {
+        for encoder in encoders.iter_mut() {
+            for message in messages.iter() {
+                let matrix = encoder.encode_into(message, &mut workspace).unwrap();
+                assert!(matrix.is_dark(0, 0));
+            }
+        }
+    }
+
+    assert_eq!(ALLOCATIONS.load(Ordering::SeqCst) - before, 0);
+}
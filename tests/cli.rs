@@ -0,0 +1,178 @@
+// the binary needs std
+#![cfg(feature = "std")]
+
+use std::{
+    env,
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn qrustcode(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_qrustcode"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn temp_file(name: &str) -> PathBuf { env::temp_dir().join(format!("qrustcode-cli-{}-{}", std::process::id(), name)) }
+
+// P4 -> P5, 1 is black in PBM and 0 is black in PGM
+fn pbm_to_pgm(pbm: &[u8]) -> Vec<u8> {
+    let header = String::from_utf8_lossy(&pbm[..pbm.len().min(32)]).into_owned();
+    let mut fields = header.split_ascii_whitespace();
+    assert_eq!(fields.next(), Some("P4"));
+    let width = fields.next().unwrap().parse::<usize>().unwrap();
+    let raster = &pbm[format!("P4\n{0} {0}\n", width).len()..];
+
+    let mut pgm = format!("P5\n# converted\n{0} {0}\n255\n", width).into_bytes();
+    for row in raster.chunks(width.div_ceil(8)) {
+        pgm.extend((0..width).map(|x| if row[x / 8] & 0x80 >> (x % 8) != 0 { 30 } else { 220 }));
+    }
+
+    pgm
+}
+
+#[test]
+fn test_roundtrip() {
+    for &(message, args) in [
+        ("01234567", &["--ec", "M"][..]),
+        ("HELLO WORLD", &["--ec", "Q", "--mask", "6"][..]),
+        ("hello, world!", &["--ec", "h", "--scale", "1"][..]),
+        ("ハロー・ワールド", &["--mode", "kanji"][..]),
+        ("https://github.com/AurevoirXavier/qrustcode", &["--version=10", "--quiet-zone=1"][..]),
+    ].iter() {
+        for format in ["png", "pbm"].iter() {
+            // the message as an argument, the image through a file
+            let path = temp_file(&format!("roundtrip.{}", format));
+            let mut encode = vec!["encode", message, "--format", format, "-o", path.to_str().unwrap()];
+            encode.extend_from_slice(args);
+            let output = qrustcode(&encode, &[]);
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+            let output = qrustcode(&["decode", path.to_str().unwrap()], &[]);
+            fs::remove_file(&path).unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            assert_eq!(output.stdout, message.as_bytes());
+        }
+    }
+}
+
+#[test]
+fn test_stdin() {
+    // bytes which aren't UTF-8 go to Byte mode, the image through pipes
+    let message = [0xff, 0x00, 0xfe, b'\n'];
+    let pbm = qrustcode(&["encode", "--format", "pbm"], &message).stdout;
+    assert!(pbm.starts_with(b"P4\n116 116\n"));
+
+    let output = qrustcode(&["decode"], &pbm);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, message);
+
+    let output = qrustcode(&["decode", "-"], &pbm_to_pgm(&pbm));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(output.stdout, message);
+}
+
+#[test]
+fn test_formats() {
+    for &(format, prefix) in [
+        ("svg", &b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"116\""[..]),
+        ("png", &b"\x89PNG\r\n\x1a\n"[..]),
+        ("pbm", &b"P4\n116 116\n"[..]),
+        ("eps", &b"%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 116 116\n"[..]),
+        ("term", "█████████████████████████████\n".as_bytes()),
+    ].iter() {
+        let output = qrustcode(&["encode", "01234567", "--format", format], &[]);
+
+        assert!(output.status.success());
+        assert!(output.stdout.starts_with(prefix), "{}", format);
+    }
+
+    // svg is the default
+    assert!(qrustcode(&["encode", "01234567"], &[]).stdout.starts_with(b"<svg"));
+}
+
+#[test]
+fn test_inspect() {
+    let output = qrustcode(&["inspect", "HELLO WORLD", "--ec", "Q", "--version", "2", "--mask", "5"], &[]);
+    let report = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(report.starts_with("version: 2 (25x25 modules)\nec level: Q\nmask: 5\nsegments: 1\n    Alphanumeric: 11 characters \"HELLO WORLD\"\n"));
+    assert!(report.contains("penalties:\n    mask 0: "));
+    assert!(report.contains("\n    mask 5: "));
+    assert_eq!(report.matches(" *\n").count(), 1);
+    assert!(report.lines().any(|line| line.starts_with("    mask 5: ") && line.ends_with(" *")));
+
+    // the symbol in an image reports the same
+    let png = qrustcode(&["encode", "HELLO WORLD", "--ec", "Q", "--version", "2", "--mask", "5", "--format", "png"], &[]);
+    let path = temp_file("inspect.png");
+    fs::write(&path, &png.stdout).unwrap();
+    let output = qrustcode(&["inspect", "--image", path.to_str().unwrap()], &[]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), report);
+}
+
+#[test]
+fn test_capacity() {
+    let output = qrustcode(&["capacity", "1"], &[]);
+    let table = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(table.lines().count(), 1 + 4);
+    assert_eq!(table.lines().nth(1), Some("      1        L      41           25     17     10"));
+
+    // every version by default
+    assert_eq!(String::from_utf8(qrustcode(&["capacity"], &[]).stdout).unwrap().lines().count(), 1 + 40 * 4);
+}
+
+#[test]
+fn test_help() {
+    for args in [
+        &["help"][..],
+        &["--help"][..],
+        &["encode", "--help"][..],
+        &["decode", "-h"][..],
+        &["inspect", "1", "--ec", "Q", "--help"][..],
+    ].iter() {
+        let output = qrustcode(args, &[]);
+
+        assert!(output.status.success(), "{:?}", args);
+        assert!(String::from_utf8(output.stdout).unwrap().starts_with("usage:"), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_errors() {
+    // usage errors exit with 2
+    for args in [&["bogus"][..], &[][..], &["encode", "1", "--mode", "octal"][..], &["encode", "--scale"][..]].iter() {
+        let output = qrustcode(args, &[]);
+
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("usage:"));
+    }
+
+    // failures exit with 1
+    for &(args, stdin, message) in [
+        (&["encode", "1", "--mask", "8"][..], &b""[..], "mask 8 is not in 0 ~ 7"),
+        (&["encode", "12a4", "--mode", "numeric"][..], &b""[..], "Numeric mode can't encode 'a'"),
+        (&["encode", "--version", "1", "--ec", "H"][..], &[b'a'; 20][..], "data needs 172 bits but only 72 bits fit"),
+        (&["decode"][..], &b"GIF89a"[..], "image is not a supported PBM, PGM, PPM or PNG"),
+        (&["capacity", "41"][..], &b""[..], "version 41 is not in 1 ~ 40"),
+        (&["decode"][..], &b"P1 2 2 0 0 0 0"[..], "no symbol found"),
+        (&["decode", "/nonexistent/qrustcode.png"][..], &b""[..], "/nonexistent/qrustcode.png: "),
+    ].iter() {
+        let output = qrustcode(args, stdin);
+
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with(&format!("qrustcode: {}", message)), "{:?}", args);
+    }
+}